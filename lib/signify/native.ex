@@ -43,6 +43,13 @@ defmodule Signify.Native do
   # CESR File Parsing NIFs
   def parse_cesr_file(_cesr_data), do: :erlang.nif_error(:nif_not_loaded)
 
+  # Kevery NIFs
+  def kevery_new(_db_path), do: :erlang.nif_error(:nif_not_loaded)
+  def kevery_process(_resource, _cesr_data), do: :erlang.nif_error(:nif_not_loaded)
+
+  def kevery_verify_by_aid(_resource, _prefix, _sn, _payload, _signatures),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   # Utility NIFs
   def blake3_digest(_data), do: :erlang.nif_error(:nif_not_loaded)
   def version(), do: :erlang.nif_error(:nif_not_loaded)
@@ -4,29 +4,55 @@
 //! including HTTP Signatures authentication, agent bootstrapping, and state management.
 
 use crate::app::controller::Controller;
+use crate::cesr_parser::ResponseMessageReader;
 use crate::core::signer::Signer;
+use crate::core::state::KeyStateRecord;
 use crate::core::verfer::Verfer;
 use crate::error::{Result, SignifyError};
 use reqwest::{Client, Method, Response};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
 const DEFAULT_BOOT_URL: &str = "http://localhost:3903";
 
+/// Controller state returned from KERIA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControllerState {
+    /// Key state of the controller AID
+    pub state: KeyStateRecord,
+    /// Latest establishment event of the controller AID
+    pub ee: Value,
+}
+
 /// Agent state information returned from KERIA
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentState {
-    /// Agent AID information
-    pub agent: Option<Value>,
-    /// Controller AID information
-    pub controller: Option<Value>,
+    /// Key state of the agent AID, `None` when missing or not a key state
+    /// record this client can parse
+    #[serde(default, deserialize_with = "lenient")]
+    pub agent: Option<KeyStateRecord>,
+    /// Controller AID state, `None` when missing or not a controller state
+    /// this client can parse
+    #[serde(default, deserialize_with = "lenient")]
+    pub controller: Option<ControllerState>,
     /// Rotation index
     pub ridx: Option<usize>,
     /// Path index
     pub pidx: Option<usize>,
 }
 
+/// Deserialize a field KERIA may send in another shape, falling back to
+/// `None` instead of failing the whole response
+fn lenient<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
 /// HTTP Signatures authenticator
 #[derive(Debug)]
 pub struct Authenticater {
@@ -317,6 +343,33 @@ impl SignifyClient {
 
         Ok(response)
     }
+
+    /// Fetch a CESR stream from the KERIA agent and parse it incrementally
+    ///
+    /// # Arguments
+    /// * `path` - Resource path
+    /// * `method` - HTTP method
+    /// * `body` - Optional request body
+    ///
+    /// # Returns
+    /// Reader yielding one message with its attachments at a time
+    pub async fn fetch_messages(
+        &self,
+        path: &str,
+        method: Method,
+        body: Option<Value>,
+    ) -> Result<ResponseMessageReader> {
+        let response = self.fetch(path, method, body).await?;
+        if !response.status().is_success() {
+            return Err(SignifyError::NetworkError(format!(
+                "Fetch {} failed with status {}",
+                path,
+                response.status()
+            )));
+        }
+
+        Ok(ResponseMessageReader::new(response))
+    }
 }
 
 #[cfg(test)]
@@ -359,4 +412,48 @@ mod tests {
         let result = client.state().await;
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_agent_state() {
+        let ksr = |pre: &str, et: &str| {
+            serde_json::json!({
+                "vn": [1, 0], "i": pre, "s": "0", "p": "", "d": pre, "f": "0",
+                "dt": "2023-08-23T15:16:07.553000+00:00", "et": et, "kt": "1",
+                "k": ["DMZh_y-H5C3cSbZZST-fqnsmdNTReZxIh0t2xSTOJQ8q"], "nt": "1",
+                "n": ["EM9M2EQNCBK0MyAhVYBvR98Q0tefpvHgE-lHLs82XgqC"], "bt": "0",
+                "b": [], "c": [], "ee": {"s": "0", "d": pre, "br": [], "ba": []}, "di": ""
+            })
+        };
+        let body = serde_json::json!({
+            "agent": ksr("EAgent", "dip"),
+            "controller": {
+                "state": ksr("ECtrl", "icp"),
+                "ee": {"v": "KERI10JSON00012b_", "t": "icp", "d": "ECtrl", "i": "ECtrl", "s": "0"}
+            },
+            "ridx": 0,
+            "pidx": 2
+        });
+
+        let state: AgentState = serde_json::from_value(body).unwrap();
+        let agent = state.agent.unwrap();
+        assert_eq!(agent.i, "EAgent");
+        assert_eq!(agent.et, "dip");
+        assert_eq!(agent.k, ["DMZh_y-H5C3cSbZZST-fqnsmdNTReZxIh0t2xSTOJQ8q"]);
+        let controller = state.controller.unwrap();
+        assert_eq!(controller.state.i, "ECtrl");
+        assert_eq!(controller.ee["s"], "0");
+        assert_eq!(state.pidx, Some(2));
+
+        // Records in another shape do not fail the rest of the state
+        let body = serde_json::json!({
+            "agent": {"i": "EAgent"},
+            "controller": {"state": ksr("ECtrl", "icp")},
+            "ridx": 1
+        });
+        let state: AgentState = serde_json::from_value(body).unwrap();
+        assert!(state.agent.is_none());
+        assert!(state.controller.is_none());
+        assert_eq!(state.ridx, Some(1));
+        assert_eq!(state.pidx, None);
+    }
 }
@@ -349,6 +349,39 @@ mod tests {
         assert!(sad.get("a").is_some());
     }
 
+    #[test]
+    fn test_credential_saids_unchanged() {
+        let stamp = "2024-01-01T00:00:00.000000+00:00";
+        let subject = CredentialSubject::new()
+            .with_issuee("EaU6JR2nmwyZ-i0d8JZAoTNZH3ULvYAfSVPzhzS6b5CM".to_string())
+            .with_timestamp(stamp.to_string())
+            .with_data("name".to_string(), json!("John Doe"));
+        let cred = CredentialBuilder::new(
+            "EaU6JR2nmwyZ-i0d8JZAoTNZH3ULvYAfSVPzhzS6b5CM".to_string(),
+            "EWCeT9zTxaZkaC_3-amV2JtG6oUxNA36sCC0P5MI7Buw".to_string(),
+            subject,
+        )
+        .issuer("EKYLUMmNPZeEs77Zvclf0bSN5IN-mLfLpx2ySb-HDlk4".to_string())
+        .build()
+        .unwrap();
+        assert_eq!(
+            cred.said_field(),
+            Some("EAGHHieSq37e3pokpEkZjT8mZDpeqbitDB-uId_QJ2f8")
+        );
+        assert_eq!(cred.sad()["v"], "ACDC10JSON000159_");
+
+        let iss = create_issuance_event(
+            cred.said_field().unwrap().to_string(),
+            "EaU6JR2nmwyZ-i0d8JZAoTNZH3ULvYAfSVPzhzS6b5CM".to_string(),
+            stamp.to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            iss.said_field(),
+            Some("EEIaAt2Cw6lDTOpHPBAhSMmgh12p42DODuJEcPFPub44")
+        );
+    }
+
     #[test]
     fn test_issuance_event() {
         let timestamp = chrono::Utc::now().to_rfc3339();
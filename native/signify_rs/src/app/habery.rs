@@ -5,26 +5,16 @@
 
 use crate::core::codes::matter_codes;
 use crate::core::diger::Diger;
+pub use crate::core::eventing::TraitCodex;
 use crate::core::manager::{Algos, Keeper, KeyStore, Manager};
 use crate::core::salter::{Salter, Tier};
 use crate::core::serder::Serder;
 use crate::core::signer::Signer;
+use crate::core::tholder::Tholder;
 use crate::core::verfer::Verfer;
 use crate::error::{Result, SignifyError};
 use std::collections::HashMap;
 
-/// Trait configuration codes for identifier properties
-pub struct TraitCodex;
-
-impl TraitCodex {
-    /// Only allow establishment events
-    pub const EST_ONLY: &'static str = "EO";
-    /// Do not allow delegated identifiers
-    pub const DO_NOT_DELEGATE: &'static str = "DND";
-    /// Do not allow backers
-    pub const NO_BACKERS: &'static str = "NB";
-}
-
 /// Arguments for creating a new Habery
 #[derive(Debug, Clone)]
 pub struct HaberyArgs {
@@ -51,12 +41,12 @@ pub struct MakeHabArgs {
     pub code: Option<String>,
     /// Whether keys are transferable (default: true)
     pub transferable: Option<bool>,
-    /// Initial signing threshold (hex string, e.g. "1", "2")
-    pub isith: Option<String>,
+    /// Initial signing threshold (unweighted or weighted)
+    pub isith: Option<Tholder>,
     /// Initial key count (default: 1)
     pub icount: Option<usize>,
-    /// Next signing threshold (hex string)
-    pub nsith: Option<String>,
+    /// Next signing threshold (unweighted or weighted)
+    pub nsith: Option<Tholder>,
     /// Next key count (default: same as icount)
     pub ncount: Option<usize>,
     /// Witness threshold (default: ample majority)
@@ -234,7 +224,7 @@ impl Habery {
         // Note: We use the same ED25519_SEED but set transferable=false
         let final_code = if !transferable {
             ncount = 0;
-            nsith = Some("0".to_string());
+            nsith = Some(Tholder::unweighted(0));
             transferable = false;
             code.clone()
         } else {
@@ -266,11 +256,17 @@ impl Habery {
         let actual_ncount = digers.len();
 
         if isith.is_none() {
-            isith = Some(format!("{:x}", std::cmp::max(1, (actual_icount + 1) / 2)));
+            isith = Some(Tholder::unweighted(std::cmp::max(
+                1,
+                actual_icount.div_ceil(2),
+            )));
         }
 
         if nsith.is_none() {
-            nsith = Some(format!("{:x}", std::cmp::max(1, (actual_ncount + 1) / 2)));
+            nsith = Some(Tholder::unweighted(std::cmp::max(
+                1,
+                actual_ncount.div_ceil(2),
+            )));
         }
 
         // Build configuration traits
@@ -289,9 +285,9 @@ impl Habery {
         // Create inception event using incept helper
         let icp = crate::core::eventing::incept(
             keys,
-            isith.as_deref(),
+            isith.as_ref(),
             ndigs,
-            nsith.as_deref(),
+            nsith.as_ref(),
             toad,
             Some(wits),
             Some(cnfg),
@@ -363,6 +359,38 @@ mod tests {
         assert_eq!(habery.habs().len(), 1);
     }
 
+    #[test]
+    fn test_habery_make_hab_weighted() {
+        let mut habery = Habery::new(HaberyArgs {
+            name: "test-habery".to_string(),
+            passcode: Some("GCiBGAhduxcggJE4qJeaA".to_string()),
+            seed: None,
+            aeid: None,
+            pidx: None,
+            salt: None,
+            tier: None,
+        })
+        .unwrap();
+
+        let sith = serde_json::json!([["1/2", "1/2"], ["1/3", "1/3", "1/3"]]);
+        let hab = habery
+            .make_hab(
+                "weighted".to_string(),
+                MakeHabArgs {
+                    icount: Some(5),
+                    isith: Some(Tholder::new(&sith).unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Next threshold defaults to the signing threshold
+        let sad = hab.serder.sad();
+        assert_eq!(sad["kt"], sith);
+        assert_eq!(sad["nt"], sith);
+        assert_eq!(sad["k"].as_array().unwrap().len(), 5);
+    }
+
     #[test]
     fn test_habery_passcode_too_short() {
         let result = Habery::new(HaberyArgs {
@@ -7,7 +7,7 @@ pub mod controller;
 pub mod credentialing;
 pub mod habery;
 
-pub use clienting::{AgentState, Authenticater, SignifyClient};
+pub use clienting::{AgentState, Authenticater, ControllerState, SignifyClient};
 pub use controller::Controller;
 pub use credentialing::{
     create_issuance_event, credential_types, CredentialBuilder, CredentialData, CredentialSubject,
@@ -1,11 +1,31 @@
-//! CESR file parsing using cesride library
+//! CESR stream and file parsing
 //!
-//! This module provides CESR (Composable Event Streaming Representation) parsing
-//! functionality using the cesride library for better compatibility with files
-//! generated by various KERI tools.
+//! This module provides CESR (Composable Event Streaming Representation) parsing.
+//! `Parser` frames each message body by its version string and decodes the
+//! attachment groups that follow it into typed primitives, in either the text
+//! (qb64) or binary (qb2) domain. Key extraction from inception events uses the
+//! cesride library for better compatibility with files generated by various
+//! KERI tools.
+//!
+//! `MessageReader` and `AsyncMessageReader` pull messages one at a time from
+//! blocking or async byte sources through a bounded `MessageBuffer`, so large
+//! KEL exports never need to be held in memory whole. `ResponseMessageReader`
+//! does the same for HTTP bodies returned by `SignifyClient::fetch`.
 
-use cesride::{Matter, Prefixer};
+use crate::core::codes::{sizage, HARDS};
+use crate::core::counter::{counter_sizes, hard_size};
+use crate::core::indexer::indexer_sizage;
+use crate::core::utils::{b2_to_b64, b64_to_int, deversify, Serials, Version, VRSN_1_0};
+use crate::core::{
+    Cigar, Counter, CounterCodex, CounterCodexV2, Dater, Diger, Pather, Prefixer, Seqner, Serder,
+    Siger, Verfer,
+};
+use crate::error::{Result as SignifyResult, SignifyError};
+use cesride::Matter as _;
+use reqwest::Response;
 use serde_json::Value;
+use std::io::Read;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 /// Result of parsing a CESR file
 #[derive(Debug, Clone)]
@@ -17,8 +37,8 @@ pub struct CesrKeys {
 
 /// Parse CESR file and extract keys
 ///
-/// Handles KERI event streams containing multiple JSON events.
-/// Extracts public key from the first inception event (icp or dip).
+/// Handles KERI event streams containing multiple JSON events with their
+/// attachments. Extracts public key from the first inception event (icp or dip).
 ///
 /// **Note:** CESR files only contain public keys. Private key is returned
 /// as zeros (mock) and must be provided separately for signing operations.
@@ -29,78 +49,26 @@ pub fn parse_cesr(cesr_data: &str) -> Result<CesrKeys, String> {
         return Err("Empty CESR data".to_string());
     }
 
-    // Check if this is a KERI event stream (starts with JSON)
-    if trimmed.starts_with('{') {
-        return parse_keri_event_stream(trimmed);
-    }
-
-    Err("Invalid CESR format. Expected KERI event stream starting with '{'".to_string())
+    parse_keri_event_stream(trimmed)
 }
 
-/// Parse KERI event stream (multiple JSON events concatenated)
+/// Parse KERI event stream and select the inception event to extract keys from
 fn parse_keri_event_stream(data: &str) -> Result<CesrKeys, String> {
-    let mut current_pos = 0;
-    let data_bytes = data.as_bytes();
+    let messages = parse_stream(data.as_bytes()).map_err(|e| e.to_string())?;
     let mut first_inception: Option<Value> = None;
     let mut delegated_inception: Option<Value> = None;
 
-    // Parse each JSON object
-    while current_pos < data_bytes.len() {
-        // Skip whitespace and non-JSON characters
-        while current_pos < data_bytes.len() && data_bytes[current_pos] != b'{' {
-            current_pos += 1;
-        }
-
-        if current_pos >= data_bytes.len() {
-            break;
-        }
-
-        // Find matching closing brace
-        let start = current_pos;
-        let mut depth = 0;
-        let mut in_string = false;
-        let mut escape = false;
-
-        while current_pos < data_bytes.len() {
-            let ch = data_bytes[current_pos];
-
-            if escape {
-                escape = false;
-                current_pos += 1;
-                continue;
-            }
-
-            if ch == b'\\' {
-                escape = true;
-            } else if ch == b'"' {
-                in_string = !in_string;
-            } else if !in_string {
-                if ch == b'{' {
-                    depth += 1;
-                } else if ch == b'}' {
-                    depth -= 1;
-                    if depth == 0 {
-                        current_pos += 1;
-                        break;
-                    }
-                }
-            }
-            current_pos += 1;
-        }
-
-        // Parse this JSON object
-        let json_str = &data[start..current_pos];
-        if let Ok(event) = serde_json::from_str::<Value>(json_str) {
-            // Check if this is an inception event
-            if let Some(event_type) = event.get("t").and_then(|t| t.as_str()) {
-                if event_type == "dip" && first_inception.is_none() {
-                    first_inception = Some(event.clone());
-                } else if event_type == "dip" && event.get("di").is_some() {
-                    // This is a delegated inception with more keys
-                    delegated_inception = Some(event);
-                } else if event_type == "icp" && first_inception.is_none() {
-                    first_inception = Some(event);
-                }
+    for message in messages {
+        let event = message.serder.sad();
+        // Check if this is an inception event
+        if let Some(event_type) = message.serder.ilk() {
+            if event_type == "dip" && first_inception.is_none() {
+                first_inception = Some(event.clone());
+            } else if event_type == "dip" && event.get("di").is_some() {
+                // This is a delegated inception with more keys
+                delegated_inception = Some(event.clone());
+            } else if event_type == "icp" && first_inception.is_none() {
+                first_inception = Some(event.clone());
             }
         }
     }
@@ -138,7 +106,7 @@ fn extract_keys_from_inception(event: Value) -> Result<CesrKeys, String> {
     let first_key_str = keys[0].as_str().ok_or("Invalid key format")?;
 
     // Use cesride Prefixer to parse CESR public key
-    let prefixer = Prefixer::new_with_qb64(first_key_str)
+    let prefixer = cesride::Prefixer::new_with_qb64(first_key_str)
         .map_err(|e| format!("Failed to parse CESR key with cesride: {:?}", e))?;
 
     // Get raw bytes from Prefixer
@@ -170,9 +138,1577 @@ fn extract_keys_from_inception(event: Value) -> Result<CesrKeys, String> {
     })
 }
 
+/// Non-transferable receipt couple (`-C`): receiptor prefix and its signature
+///
+/// The cigar carries a verfer for the receiptor prefix when the prefix is a
+/// basic (key) prefix.
+#[derive(Debug, Clone)]
+pub struct NonTransReceipt {
+    pub prefixer: Prefixer,
+    pub cigar: Cigar,
+}
+
+/// Transferable receipt quadruple (`-D`): validator's prefix, the sn and
+/// digest of its latest establishment event and its indexed signature
+#[derive(Debug, Clone)]
+pub struct TransReceipt {
+    pub prefixer: Prefixer,
+    pub seqner: Seqner,
+    pub diger: Diger,
+    pub siger: Siger,
+}
+
+/// Transferable indexed signature group (`-F`): signer's prefix, the sn and
+/// digest of its establishment event and its indexed signatures
+#[derive(Debug, Clone)]
+pub struct TransIdxSigGroup {
+    pub prefixer: Prefixer,
+    pub seqner: Seqner,
+    pub diger: Diger,
+    pub sigers: Vec<Siger>,
+}
+
+/// Transferable last indexed signature group (`-H`): signer's prefix and
+/// indexed signatures made with its latest establishment event keys
+#[derive(Debug, Clone)]
+pub struct TransLastIdxSigGroup {
+    pub prefixer: Prefixer,
+    pub sigers: Vec<Siger>,
+}
+
+/// SAD path signature (`-J`): signatures over the field of the message body
+/// located by a SAD path, made by transferable signers (`-F`) or
+/// non-transferable signers (`-C`)
+#[derive(Debug, Clone, Default)]
+pub struct SadPathSig {
+    /// Path of the signed field from the message body, under the root path
+    /// of its `-K` group if any
+    pub path: Vec<String>,
+    pub trans_sig_groups: Vec<TransIdxSigGroup>,
+    pub receipts: Vec<NonTransReceipt>,
+}
+
+/// Pathed material group (`-L`): attachments of the embedded message located
+/// by a SAD path, e.g. the signatures of an event embedded in an exchange
+#[derive(Debug, Clone)]
+pub struct PathedMaterial {
+    pub path: Vec<String>,
+    pub attachments: Attachments,
+}
+
+/// Attachments decoded from the groups that follow a message body
+#[derive(Debug, Clone, Default)]
+pub struct Attachments {
+    /// Controller indexed signatures (`-A`)
+    pub sigers: Vec<Siger>,
+    /// Witness indexed signatures (`-B`)
+    pub wigers: Vec<Siger>,
+    /// Non-transferable receipt couples (`-C`)
+    pub receipts: Vec<NonTransReceipt>,
+    /// Transferable receipt quadruples (`-D`)
+    pub trans_receipts: Vec<TransReceipt>,
+    /// First seen replay couples (`-E`): first seen ordinal and datetime
+    pub first_seen: Vec<(Seqner, Dater)>,
+    /// Transferable indexed signature groups (`-F`)
+    pub trans_sig_groups: Vec<TransIdxSigGroup>,
+    /// Seal source couples (`-G`): sn and digest of the anchoring event
+    pub seal_sources: Vec<(Seqner, Diger)>,
+    /// Transferable last indexed signature groups (`-H`)
+    pub trans_last_sig_groups: Vec<TransLastIdxSigGroup>,
+    /// SAD path signatures (`-J`, or `-K` under a root path)
+    pub sad_path_sigs: Vec<SadPathSig>,
+    /// Pathed material groups (`-L`)
+    pub pathed: Vec<PathedMaterial>,
+}
+
+impl Attachments {
+    /// Move the attachments of `other` to the end of these
+    fn append(&mut self, mut other: Attachments) {
+        self.sigers.append(&mut other.sigers);
+        self.wigers.append(&mut other.wigers);
+        self.receipts.append(&mut other.receipts);
+        self.trans_receipts.append(&mut other.trans_receipts);
+        self.first_seen.append(&mut other.first_seen);
+        self.trans_sig_groups.append(&mut other.trans_sig_groups);
+        self.seal_sources.append(&mut other.seal_sources);
+        self.trans_last_sig_groups
+            .append(&mut other.trans_last_sig_groups);
+        self.sad_path_sigs.append(&mut other.sad_path_sigs);
+        self.pathed.append(&mut other.pathed);
+    }
+}
+
+/// A framed message body with its attachments
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub serder: Serder,
+    pub attachments: Attachments,
+    /// Byte offset of the message body in the stream
+    pub offset: usize,
+}
+
+/// Incremental CESR stream parser
+///
+/// Feed the unconsumed front of a stream to `next_message`. The parser keeps
+/// the stream's protocol version (switched by genus version counters) and the
+/// absolute byte offset across calls so errors point into the whole stream.
+/// A message whose attachments have not all arrived is kept framed up to its
+/// last complete attachment group, and the next call resumes there, so the
+/// same front must be fed again with more bytes appended until it completes.
+#[derive(Debug, Clone)]
+pub struct Parser {
+    version: Version,
+    offset: usize,
+    pending: Option<Pending>,
+}
+
+/// Message framed up to its last complete attachment group
+#[derive(Debug, Clone)]
+struct Pending {
+    message: Message,
+    version: Version,
+    /// Bytes framed from the front of the unconsumed stream
+    pos: usize,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    /// Create parser for a CESR 1.0 stream
+    pub fn new() -> Self {
+        Self {
+            version: VRSN_1_0,
+            offset: 0,
+            pending: None,
+        }
+    }
+
+    /// Active CESR protocol version of the stream
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Absolute byte offset of the next unconsumed byte
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Parse the next message and its attachments from the front of `buf`
+    ///
+    /// Returns the message and the number of bytes consumed, or `None` when
+    /// `buf` holds no complete message yet. Attachments only end at the next
+    /// message or at end of stream, so unless `eof` is set a message that
+    /// reaches the end of `buf` is treated as incomplete. With `eof` set,
+    /// `None` means only whitespace remains.
+    pub fn next_message(
+        &mut self,
+        buf: &[u8],
+        eof: bool,
+    ) -> SignifyResult<Option<(Message, usize)>> {
+        let mut version = self.version;
+        let mut reader = Reader {
+            buf,
+            pos: 0,
+            base: self.offset,
+            depth: 0,
+        };
+
+        match reader.message(&mut version, eof, &mut self.pending) {
+            Ok(Some(message)) => {
+                self.version = version;
+                self.offset += reader.pos;
+                Ok(Some((message, reader.pos)))
+            }
+            Ok(None) => Ok(None),
+            Err(Fail::Shortage) if !eof => Ok(None),
+            Err(Fail::Shortage) => Err(SignifyError::StreamError {
+                offset: self.offset + buf.len(),
+                message: "Unexpected end of stream".to_string(),
+            }),
+            Err(Fail::Error { offset, message }) => {
+                Err(SignifyError::StreamError { offset, message })
+            }
+        }
+    }
+}
+
+/// Parse a complete CESR stream into its messages
+pub fn parse_stream(data: &[u8]) -> SignifyResult<Vec<Message>> {
+    let mut parser = Parser::new();
+    let mut messages = Vec::new();
+    let mut rest = data;
+
+    while let Some((message, consumed)) = parser.next_message(rest, true)? {
+        messages.push(message);
+        rest = &rest[consumed..];
+    }
+
+    Ok(messages)
+}
+
+/// Default limit on bytes buffered for a single message and its attachments
+pub const DEFAULT_MAX_BUFFER: usize = 4 * 1024 * 1024;
+
+/// Bytes requested from the underlying source per read
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Bounded buffer feeding a `Parser`, independent of how bytes arrive
+///
+/// Consumed messages are dropped from the front so the buffer only ever
+/// holds the message being framed, up to `max_buffer` bytes.
+#[derive(Debug, Clone)]
+pub struct MessageBuffer {
+    parser: Parser,
+    buf: Vec<u8>,
+    start: usize,
+    max_buffer: usize,
+    eof: bool,
+}
+
+impl Default for MessageBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_BUFFER)
+    }
+}
+
+impl MessageBuffer {
+    /// Create buffer holding at most `max_buffer` unconsumed bytes
+    pub fn new(max_buffer: usize) -> Self {
+        Self {
+            parser: Parser::new(),
+            buf: Vec::new(),
+            start: 0,
+            max_buffer,
+            eof: false,
+        }
+    }
+
+    /// Number of bytes that can be fed before the limit is reached
+    pub fn space(&self) -> usize {
+        self.max_buffer - (self.buf.len() - self.start)
+    }
+
+    /// Append bytes read from the stream
+    pub fn feed(&mut self, data: &[u8]) -> SignifyResult<()> {
+        if data.len() > self.space() {
+            return Err(self.overflow());
+        }
+
+        // Drop consumed bytes before growing
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.start = 0;
+        }
+        self.buf.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Mark the end of the stream so trailing messages complete
+    pub fn finish(&mut self) {
+        self.eof = true;
+    }
+
+    /// Whether the end of the stream has been reached
+    pub fn is_finished(&self) -> bool {
+        self.eof
+    }
+
+    /// Next complete message, or `None` when more bytes are needed (or the
+    /// stream is finished)
+    pub fn next_message(&mut self) -> SignifyResult<Option<Message>> {
+        match self
+            .parser
+            .next_message(&self.buf[self.start..], self.eof)?
+        {
+            Some((message, consumed)) => {
+                self.start += consumed;
+                Ok(Some(message))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Error for a message that does not fit in the buffer
+    fn overflow(&self) -> SignifyError {
+        SignifyError::StreamError {
+            offset: self.parser.offset(),
+            message: format!("Message exceeds buffer limit of {} bytes", self.max_buffer),
+        }
+    }
+}
+
+/// Pull parser yielding messages from a `std::io::Read` source
+///
+/// Used as an iterator, e.g. over a `File` for KEL imports.
+pub struct MessageReader<R: Read> {
+    reader: R,
+    buffer: MessageBuffer,
+    chunk: Vec<u8>,
+}
+
+impl<R: Read> MessageReader<R> {
+    /// Create reader with the default buffer limit
+    pub fn new(reader: R) -> Self {
+        Self::with_max_buffer(reader, DEFAULT_MAX_BUFFER)
+    }
+
+    /// Create reader buffering at most `max_buffer` bytes per message
+    pub fn with_max_buffer(reader: R, max_buffer: usize) -> Self {
+        Self {
+            reader,
+            buffer: MessageBuffer::new(max_buffer),
+            chunk: vec![0u8; READ_CHUNK.min(max_buffer)],
+        }
+    }
+
+    /// Next message, or `None` at end of stream
+    pub fn next_message(&mut self) -> SignifyResult<Option<Message>> {
+        loop {
+            if let Some(message) = self.buffer.next_message()? {
+                return Ok(Some(message));
+            }
+            if self.buffer.is_finished() {
+                return Ok(None);
+            }
+
+            let space = self.buffer.space().min(self.chunk.len());
+            if space == 0 {
+                return Err(self.buffer.overflow());
+            }
+            let n = match self.reader.read(&mut self.chunk[..space]) {
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(SignifyError::Other(format!("Stream read error: {}", e))),
+            };
+            if n == 0 {
+                self.buffer.finish();
+            } else {
+                self.buffer.feed(&self.chunk[..n])?;
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for MessageReader<R> {
+    type Item = SignifyResult<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_message().transpose()
+    }
+}
+
+/// Pull parser yielding messages from a `tokio::io::AsyncRead` source
+pub struct AsyncMessageReader<R: AsyncRead + Unpin> {
+    reader: R,
+    buffer: MessageBuffer,
+    chunk: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncMessageReader<R> {
+    /// Create reader with the default buffer limit
+    pub fn new(reader: R) -> Self {
+        Self::with_max_buffer(reader, DEFAULT_MAX_BUFFER)
+    }
+
+    /// Create reader buffering at most `max_buffer` bytes per message
+    pub fn with_max_buffer(reader: R, max_buffer: usize) -> Self {
+        Self {
+            reader,
+            buffer: MessageBuffer::new(max_buffer),
+            chunk: vec![0u8; READ_CHUNK.min(max_buffer)],
+        }
+    }
+
+    /// Next message, or `None` at end of stream
+    pub async fn next_message(&mut self) -> SignifyResult<Option<Message>> {
+        loop {
+            if let Some(message) = self.buffer.next_message()? {
+                return Ok(Some(message));
+            }
+            if self.buffer.is_finished() {
+                return Ok(None);
+            }
+
+            let space = self.buffer.space().min(self.chunk.len());
+            if space == 0 {
+                return Err(self.buffer.overflow());
+            }
+            let n = self
+                .reader
+                .read(&mut self.chunk[..space])
+                .await
+                .map_err(|e| SignifyError::Other(format!("Stream read error: {}", e)))?;
+            if n == 0 {
+                self.buffer.finish();
+            } else {
+                self.buffer.feed(&self.chunk[..n])?;
+            }
+        }
+    }
+}
+
+/// Pull parser yielding messages from a `reqwest::Response` body
+///
+/// Body chunks larger than the free buffer space are held back and fed as
+/// messages are consumed.
+pub struct ResponseMessageReader {
+    response: Response,
+    buffer: MessageBuffer,
+    pending: Vec<u8>,
+}
+
+impl ResponseMessageReader {
+    /// Create reader with the default buffer limit
+    pub fn new(response: Response) -> Self {
+        Self::with_max_buffer(response, DEFAULT_MAX_BUFFER)
+    }
+
+    /// Create reader buffering at most `max_buffer` bytes per message
+    pub fn with_max_buffer(response: Response, max_buffer: usize) -> Self {
+        Self {
+            response,
+            buffer: MessageBuffer::new(max_buffer),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Next message, or `None` at end of body
+    pub async fn next_message(&mut self) -> SignifyResult<Option<Message>> {
+        loop {
+            if let Some(message) = self.buffer.next_message()? {
+                return Ok(Some(message));
+            }
+            if self.buffer.is_finished() {
+                return Ok(None);
+            }
+
+            if self.pending.is_empty() {
+                match self
+                    .response
+                    .chunk()
+                    .await
+                    .map_err(|e| SignifyError::NetworkError(e.to_string()))?
+                {
+                    Some(chunk) => self.pending.extend_from_slice(&chunk),
+                    None => {
+                        self.buffer.finish();
+                        continue;
+                    }
+                }
+            }
+
+            let n = self.buffer.space().min(self.pending.len());
+            if n == 0 {
+                return Err(self.buffer.overflow());
+            }
+            self.buffer.feed(&self.pending[..n])?;
+            self.pending.drain(..n);
+        }
+    }
+}
+
+/// Stream domain selected by the top three bits of the next byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cold {
+    /// Message body ('{' for JSON)
+    Msg,
+    /// Text domain counter or primitive (qb64)
+    Txt,
+    /// Binary domain counter or primitive (qb2)
+    Bny,
+}
+
+/// Attachment group kinds shared by the 1.0 and 2.0 counter tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    ControllerSigs,
+    WitnessSigs,
+    NonTransReceipts,
+    TransReceipts,
+    FirstSeen,
+    TransSigGroups,
+    SealSources,
+    TransLastSigGroups,
+    SadPathSigs,
+    RootSadPathSigs,
+    Pathed,
+    Quadlets,
+    GenusVersion,
+}
+
+impl Group {
+    fn of(code: &str, version: &Version) -> Option<Self> {
+        if code == CounterCodex::KERI_ACDC_GENUS_VERSION {
+            return Some(Group::GenusVersion);
+        }
+
+        if version.major == 1 {
+            match code {
+                CounterCodex::CONTROLLER_IDX_SIGS => Some(Group::ControllerSigs),
+                CounterCodex::WITNESS_IDX_SIGS => Some(Group::WitnessSigs),
+                CounterCodex::NON_TRANS_RCT => Some(Group::NonTransReceipts),
+                CounterCodex::TRANS_RCT => Some(Group::TransReceipts),
+                CounterCodex::FIRST_SEEN_RPY => Some(Group::FirstSeen),
+                CounterCodex::TRANS_IDX_SIG_GROUPS => Some(Group::TransSigGroups),
+                CounterCodex::SEAL_SOURCE_COUPLES => Some(Group::SealSources),
+                CounterCodex::TRANS_LAST_IDX_SIG_GROUPS => Some(Group::TransLastSigGroups),
+                CounterCodex::SAD_PATH_SIG => Some(Group::SadPathSigs),
+                CounterCodex::SAD_PATH_SIG_GROUP => Some(Group::RootSadPathSigs),
+                CounterCodex::PATHED_MATERIAL_QUADLETS
+                | CounterCodex::BIG_PATHED_MATERIAL_QUADLETS => Some(Group::Pathed),
+                CounterCodex::ATTACHED_MATERIAL_QUADLETS
+                | CounterCodex::BIG_ATTACHED_MATERIAL_QUADLETS => Some(Group::Quadlets),
+                _ => None,
+            }
+        } else {
+            match code {
+                CounterCodexV2::CONTROLLER_IDX_SIGS | CounterCodexV2::BIG_CONTROLLER_IDX_SIGS => {
+                    Some(Group::ControllerSigs)
+                }
+                CounterCodexV2::WITNESS_IDX_SIGS | CounterCodexV2::BIG_WITNESS_IDX_SIGS => {
+                    Some(Group::WitnessSigs)
+                }
+                CounterCodexV2::NON_TRANS_RCT | CounterCodexV2::BIG_NON_TRANS_RCT => {
+                    Some(Group::NonTransReceipts)
+                }
+                CounterCodexV2::TRANS_RCT | CounterCodexV2::BIG_TRANS_RCT => {
+                    Some(Group::TransReceipts)
+                }
+                CounterCodexV2::FIRST_SEEN_RPY | CounterCodexV2::BIG_FIRST_SEEN_RPY => {
+                    Some(Group::FirstSeen)
+                }
+                CounterCodexV2::TRANS_IDX_SIG_GROUPS | CounterCodexV2::BIG_TRANS_IDX_SIG_GROUPS => {
+                    Some(Group::TransSigGroups)
+                }
+                CounterCodexV2::SEAL_SOURCE_COUPLES | CounterCodexV2::BIG_SEAL_SOURCE_COUPLES => {
+                    Some(Group::SealSources)
+                }
+                CounterCodexV2::TRANS_LAST_IDX_SIG_GROUPS
+                | CounterCodexV2::BIG_TRANS_LAST_IDX_SIG_GROUPS => Some(Group::TransLastSigGroups),
+                CounterCodexV2::ATTACHMENT_GROUP | CounterCodexV2::BIG_ATTACHMENT_GROUP => {
+                    Some(Group::Quadlets)
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Internal parse failure: either more bytes are needed or the stream is bad
+#[derive(Debug)]
+enum Fail {
+    Shortage,
+    Error { offset: usize, message: String },
+}
+
+type Parse<T> = std::result::Result<T, Fail>;
+
+/// Longest offset of the version string in a message body
+const MAX_VS_OFFSET: usize = 12;
+
+/// Length of a CESR 1.0 version string
+const VS_LEN: usize = 17;
+
+/// Deepest nesting of attachment groups inside counted groups
+const MAX_GROUP_DEPTH: usize = 16;
+
+/// Cursor over a buffer whose first byte sits at absolute offset `base`
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    base: usize,
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn fail<T>(&self, at: usize, message: impl Into<String>) -> Parse<T> {
+        Err(Fail::Error {
+            offset: self.base + at,
+            message: message.into(),
+        })
+    }
+
+    fn need(&self, n: usize) -> Parse<()> {
+        if self.buf.len() - self.pos < n {
+            Err(Fail::Shortage)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.buf.len() && self.buf[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn sniff(&self) -> Parse<Cold> {
+        self.need(1)?;
+        match self.buf[self.pos] >> 5 {
+            0b001 | 0b010 => Ok(Cold::Txt),
+            0b011 => Ok(Cold::Msg),
+            0b111 => Ok(Cold::Bny),
+            0b100..=0b110 => self.fail(self.pos, "CBOR and MGPK message bodies not supported"),
+            _ => self.fail(
+                self.pos,
+                format!("Unexpected byte 0x{:02x}", self.buf[self.pos]),
+            ),
+        }
+    }
+
+    /// Parse a message body and its attachment groups, resuming after the
+    /// last complete group of a `pending` message
+    ///
+    /// On a shortage after the body, the message framed so far is left in
+    /// `pending`.
+    fn message(
+        &mut self,
+        version: &mut Version,
+        eof: bool,
+        pending: &mut Option<Pending>,
+    ) -> Parse<Option<Message>> {
+        let (mut message, mut current) = match pending.take() {
+            Some(framed) => {
+                self.pos = framed.pos;
+                (framed.message, framed.version)
+            }
+            None => {
+                // Genus version counters may precede a message body
+                loop {
+                    self.skip_whitespace();
+                    if self.pos >= self.buf.len() {
+                        return if eof { Ok(None) } else { Err(Fail::Shortage) };
+                    }
+                    match self.sniff()? {
+                        Cold::Msg => break,
+                        cold => {
+                            let start = self.pos;
+                            let counter = self.counter(cold, *version)?;
+                            if Group::of(counter.code(), version) != Some(Group::GenusVersion) {
+                                return self.fail(
+                                    start,
+                                    format!("Attachment group {} without message", counter.code()),
+                                );
+                            }
+                            *version = self.genus_version(&counter, start)?;
+                        }
+                    }
+                }
+
+                let offset = self.base + self.pos;
+                let serder = self.body()?;
+                let message = Message {
+                    serder,
+                    attachments: Attachments::default(),
+                    offset,
+                };
+                (message, *version)
+            }
+        };
+
+        // Attachments run until whitespace, the next message or end of stream
+        loop {
+            if self.pos >= self.buf.len() {
+                if eof {
+                    break;
+                }
+                *pending = Some(Pending {
+                    message,
+                    version: current,
+                    pos: self.pos,
+                });
+                return Err(Fail::Shortage);
+            }
+            if self.buf[self.pos].is_ascii_whitespace() {
+                break;
+            }
+            let cold = match self.sniff()? {
+                Cold::Msg => break,
+                cold => cold,
+            };
+
+            // Groups apply only once complete so a shortage can resume here
+            let start = self.pos;
+            let mut group_version = current;
+            let mut group = Attachments::default();
+            match self.group(cold, &mut group_version, &mut group) {
+                Ok(()) => {
+                    message.attachments.append(group);
+                    current = group_version;
+                }
+                Err(Fail::Shortage) => {
+                    *pending = Some(Pending {
+                        message,
+                        version: current,
+                        pos: start,
+                    });
+                    return Err(Fail::Shortage);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        *version = current;
+        Ok(Some(message))
+    }
+
+    /// Frame a message body by the size in its version string
+    fn body(&mut self) -> Parse<Serder> {
+        let start = self.pos;
+        self.need(MAX_VS_OFFSET + VS_LEN)?;
+
+        let window = &self.buf[start..start + MAX_VS_OFFSET + VS_LEN];
+        let key = b"\"v\":\"";
+        let at = match window.windows(key.len()).position(|w| w == key) {
+            Some(at) if at + key.len() + VS_LEN <= window.len() => at + key.len(),
+            _ => return self.fail(start, "Missing version string in message body"),
+        };
+
+        let vs = String::from_utf8_lossy(&window[at..at + VS_LEN]);
+        let (_, _, kind, size) = match deversify(&vs) {
+            Ok(vrsn) => vrsn,
+            Err(e) => return self.fail(start + at, e.to_string()),
+        };
+        if kind != Serials::JSON {
+            return self.fail(
+                start + at,
+                format!("{} message bodies not supported", kind.as_str()),
+            );
+        }
+
+        self.need(size)?;
+        let raw = match std::str::from_utf8(&self.buf[start..start + size]) {
+            Ok(raw) => raw,
+            Err(e) => return self.fail(start, format!("Message body not UTF-8: {}", e)),
+        };
+        let serder = match Serder::from_raw(raw) {
+            Ok(serder) => serder,
+            Err(e) => return self.fail(start, e.to_string()),
+        };
+
+        self.pos += size;
+        Ok(serder)
+    }
+
+    /// Parse one attachment group into `attachments`
+    fn group(
+        &mut self,
+        cold: Cold,
+        version: &mut Version,
+        attachments: &mut Attachments,
+    ) -> Parse<()> {
+        let start = self.pos;
+        let counter = self.counter(cold, *version)?;
+        let count = counter.count() as usize;
+
+        let group = match Group::of(counter.code(), version) {
+            Some(group) => group,
+            None => {
+                return self.fail(
+                    start,
+                    format!("Unsupported attachment group {}", counter.code()),
+                )
+            }
+        };
+
+        match group {
+            Group::GenusVersion => *version = self.genus_version(&counter, start)?,
+            Group::ControllerSigs => {
+                for _ in 0..count {
+                    attachments.sigers.push(self.siger(cold)?);
+                }
+            }
+            Group::WitnessSigs => {
+                for _ in 0..count {
+                    attachments.wigers.push(self.siger(cold)?);
+                }
+            }
+            Group::NonTransReceipts => {
+                for _ in 0..count {
+                    attachments.receipts.push(self.receipt(cold)?);
+                }
+            }
+            Group::TransReceipts => {
+                for _ in 0..count {
+                    let prefixer = self.matter(cold, Prefixer::from_qb64, Prefixer::from_qb2)?;
+                    let seqner = self.matter(cold, Seqner::from_qb64, Seqner::from_qb2)?;
+                    let diger = self.matter(cold, Diger::from_qb64, Diger::from_qb2)?;
+                    let siger = self.siger(cold)?;
+                    attachments.trans_receipts.push(TransReceipt {
+                        prefixer,
+                        seqner,
+                        diger,
+                        siger,
+                    });
+                }
+            }
+            Group::FirstSeen => {
+                for _ in 0..count {
+                    let seqner = self.matter(cold, Seqner::from_qb64, Seqner::from_qb2)?;
+                    let dater = self.matter(cold, Dater::from_qb64, Dater::from_qb2)?;
+                    attachments.first_seen.push((seqner, dater));
+                }
+            }
+            Group::TransSigGroups => {
+                for _ in 0..count {
+                    let group = self.trans_sig_group(cold, version)?;
+                    attachments.trans_sig_groups.push(group);
+                }
+            }
+            Group::SealSources => {
+                for _ in 0..count {
+                    let seqner = self.matter(cold, Seqner::from_qb64, Seqner::from_qb2)?;
+                    let diger = self.matter(cold, Diger::from_qb64, Diger::from_qb2)?;
+                    attachments.seal_sources.push((seqner, diger));
+                }
+            }
+            Group::TransLastSigGroups => {
+                for _ in 0..count {
+                    let prefixer = self.matter(cold, Prefixer::from_qb64, Prefixer::from_qb2)?;
+                    let sigers = self.controller_sigs(cold, version)?;
+                    attachments
+                        .trans_last_sig_groups
+                        .push(TransLastIdxSigGroup { prefixer, sigers });
+                }
+            }
+            Group::SadPathSigs => {
+                let sigs = self.sad_path_sigs(cold, version, count, &[])?;
+                attachments.sad_path_sigs.extend(sigs);
+            }
+            Group::RootSadPathSigs => {
+                let root = self.matter(cold, Pather::from_qb64, Pather::from_qb2)?;
+                for _ in 0..count {
+                    let at = self.pos;
+                    let counter = self.counter(cold, *version)?;
+                    if Group::of(counter.code(), version) != Some(Group::SadPathSigs) {
+                        return self.fail(
+                            at,
+                            format!(
+                                "Expected SAD path signature counter, got {}",
+                                counter.code()
+                            ),
+                        );
+                    }
+                    let sigs =
+                        self.sad_path_sigs(cold, version, counter.count() as usize, &root.path())?;
+                    attachments.sad_path_sigs.extend(sigs);
+                }
+            }
+            Group::Pathed => {
+                let end = self.quadlets_end(cold, count)?;
+                let mut nested = self.nested(start, end)?;
+                let path = match nested.matter(cold, Pather::from_qb64, Pather::from_qb2) {
+                    Err(Fail::Shortage) => {
+                        return self.fail(start, "Pathed material overruns its quadlet count")
+                    }
+                    result => result?.path(),
+                };
+
+                // The embedded attachments keep their own genus version
+                let mut pathed_version = *version;
+                let mut pathed = Attachments::default();
+                while nested.pos < end {
+                    match nested.group(cold, &mut pathed_version, &mut pathed) {
+                        Err(Fail::Shortage) => {
+                            return self.fail(start, "Pathed material overruns its quadlet count")
+                        }
+                        result => result?,
+                    }
+                }
+                attachments.pathed.push(PathedMaterial {
+                    path,
+                    attachments: pathed,
+                });
+                self.pos = end;
+            }
+            Group::Quadlets => {
+                let end = self.quadlets_end(cold, count)?;
+
+                // Nested groups must fit exactly inside the counted material
+                let mut nested = self.nested(start, end)?;
+                while nested.pos < end {
+                    match nested.group(cold, version, attachments) {
+                        Err(Fail::Shortage) => {
+                            return self.fail(start, "Attachment group overruns its quadlet count")
+                        }
+                        result => result?,
+                    }
+                }
+                self.pos = end;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reader over the counted material of the group at `start`, up to `end`
+    fn nested(&self, start: usize, end: usize) -> Parse<Reader<'a>> {
+        if self.depth >= MAX_GROUP_DEPTH {
+            return self.fail(
+                start,
+                format!("Attachment groups nested deeper than {}", MAX_GROUP_DEPTH),
+            );
+        }
+        Ok(Reader {
+            buf: &self.buf[..end],
+            pos: self.pos,
+            base: self.base,
+            depth: self.depth + 1,
+        })
+    }
+
+    /// End of `count` quadlets of 4 chars, or triplets of 3 bytes, at the cursor
+    fn quadlets_end(&self, cold: Cold, count: usize) -> Parse<usize> {
+        let size = match cold {
+            Cold::Bny => count * 3,
+            _ => count * 4,
+        };
+        self.need(size)?;
+        Ok(self.pos + size)
+    }
+
+    /// Parse one non-transferable receipt couple
+    fn receipt(&mut self, cold: Cold) -> Parse<NonTransReceipt> {
+        let prefixer = self.matter(cold, Prefixer::from_qb64, Prefixer::from_qb2)?;
+        let verfer = Verfer::from_qb64(prefixer.qb64()).ok();
+        let cigar = self.matter(
+            cold,
+            |qb64| Cigar::from_qb64(qb64, verfer.clone()),
+            |qb2| Cigar::from_qb2(qb2, verfer.clone()),
+        )?;
+        Ok(NonTransReceipt { prefixer, cigar })
+    }
+
+    /// Parse one transferable indexed signature group
+    fn trans_sig_group(&mut self, cold: Cold, version: &Version) -> Parse<TransIdxSigGroup> {
+        let prefixer = self.matter(cold, Prefixer::from_qb64, Prefixer::from_qb2)?;
+        let seqner = self.matter(cold, Seqner::from_qb64, Seqner::from_qb2)?;
+        let diger = self.matter(cold, Diger::from_qb64, Diger::from_qb2)?;
+        let sigers = self.controller_sigs(cold, version)?;
+        Ok(TransIdxSigGroup {
+            prefixer,
+            seqner,
+            diger,
+            sigers,
+        })
+    }
+
+    /// Parse the `count` entries of a SAD path signature group, each a path
+    /// under `root` followed by a `-F` or `-C` signature group
+    fn sad_path_sigs(
+        &mut self,
+        cold: Cold,
+        version: &Version,
+        count: usize,
+        root: &[String],
+    ) -> Parse<Vec<SadPathSig>> {
+        let mut sigs = Vec::with_capacity(count);
+        for _ in 0..count {
+            let subpath = self.matter(cold, Pather::from_qb64, Pather::from_qb2)?;
+            let mut sig = SadPathSig {
+                path: [root, subpath.path().as_slice()].concat(),
+                ..Default::default()
+            };
+
+            let start = self.pos;
+            let counter = self.counter(cold, *version)?;
+            match Group::of(counter.code(), version) {
+                Some(Group::TransSigGroups) => {
+                    for _ in 0..counter.count() {
+                        let group = self.trans_sig_group(cold, version)?;
+                        sig.trans_sig_groups.push(group);
+                    }
+                }
+                Some(Group::NonTransReceipts) => {
+                    for _ in 0..counter.count() {
+                        sig.receipts.push(self.receipt(cold)?);
+                    }
+                }
+                _ => {
+                    return self.fail(
+                        start,
+                        format!(
+                            "Expected signature group in SAD path signature, got {}",
+                            counter.code()
+                        ),
+                    )
+                }
+            }
+            sigs.push(sig);
+        }
+        Ok(sigs)
+    }
+
+    /// Parse a nested controller signatures group
+    fn controller_sigs(&mut self, cold: Cold, version: &Version) -> Parse<Vec<Siger>> {
+        let start = self.pos;
+        let counter = self.counter(cold, *version)?;
+        if Group::of(counter.code(), version) != Some(Group::ControllerSigs) {
+            return self.fail(
+                start,
+                format!(
+                    "Expected controller signatures counter, got {}",
+                    counter.code()
+                ),
+            );
+        }
+
+        (0..counter.count()).map(|_| self.siger(cold)).collect()
+    }
+
+    fn genus_version(&self, counter: &Counter, start: usize) -> Parse<Version> {
+        match counter.gvrsn() {
+            Ok(version) if counter_sizes(&version).is_ok() => Ok(version),
+            Ok(version) => self.fail(
+                start,
+                format!(
+                    "Unsupported CESR version {}.{}",
+                    version.major, version.minor
+                ),
+            ),
+            Err(e) => self.fail(start, e.to_string()),
+        }
+    }
+
+    /// Read the first `n` Base64 chars at the cursor in either domain
+    fn chars(&self, cold: Cold, n: usize) -> Parse<String> {
+        let rest = &self.buf[self.pos..];
+        match cold {
+            Cold::Bny => {
+                self.need((n * 3).div_ceil(4))?;
+                Ok(b2_to_b64(rest, n))
+            }
+            _ => {
+                self.need(n)?;
+                match std::str::from_utf8(&rest[..n]) {
+                    Ok(text) => Ok(text.to_string()),
+                    Err(_) => self.fail(self.pos, "Invalid text domain character"),
+                }
+            }
+        }
+    }
+
+    /// Consume a primitive of `fs` Base64 chars with the domain's constructor
+    fn primitive<T>(
+        &mut self,
+        cold: Cold,
+        fs: usize,
+        text: impl FnOnce(&str) -> SignifyResult<T>,
+        binary: impl FnOnce(&[u8]) -> SignifyResult<T>,
+    ) -> Parse<T> {
+        let size = match cold {
+            Cold::Bny => fs * 3 / 4,
+            _ => fs,
+        };
+        self.need(size)?;
+
+        let chunk = &self.buf[self.pos..self.pos + size];
+        let result = match cold {
+            Cold::Bny => binary(chunk),
+            _ => match std::str::from_utf8(chunk) {
+                Ok(qb64) => text(qb64),
+                Err(_) => return self.fail(self.pos, "Invalid text domain character"),
+            },
+        };
+
+        match result {
+            Ok(value) => {
+                self.pos += size;
+                Ok(value)
+            }
+            Err(e) => self.fail(self.pos, e.to_string()),
+        }
+    }
+
+    fn counter(&mut self, cold: Cold, version: Version) -> Parse<Counter> {
+        let hs = hard_size(&self.chars(cold, 2)?);
+        let code = self.chars(cold, hs)?;
+        let sizes = match counter_sizes(&version) {
+            Ok(sizes) => sizes,
+            Err(e) => return self.fail(self.pos, e.to_string()),
+        };
+        let fs = match sizes.get(code.as_str()) {
+            Some(sizage) => sizage.fs,
+            None => return self.fail(self.pos, format!("Unknown counter code {}", code)),
+        };
+
+        self.primitive(
+            cold,
+            fs,
+            |qb64| Counter::from_qb64_with_version(qb64, version),
+            |qb2| Counter::from_qb2_with_version(qb2, version),
+        )
+    }
+
+    fn siger(&mut self, cold: Cold) -> Parse<Siger> {
+        let first = self.chars(cold, 1)?;
+        let hs = if first.starts_with(|c: char| c.is_ascii_digit()) {
+            2
+        } else {
+            1
+        };
+        let code = self.chars(cold, hs)?;
+        let fs = match indexer_sizage(&code) {
+            Some(sizage) => sizage.fs,
+            None => return self.fail(self.pos, format!("Unknown indexer code {}", code)),
+        };
+
+        self.primitive(
+            cold,
+            fs,
+            |qb64| Siger::from_qb64(qb64, None),
+            |qb2| Siger::from_qb2(qb2, None),
+        )
+    }
+
+    fn matter<T>(
+        &mut self,
+        cold: Cold,
+        text: impl FnOnce(&str) -> SignifyResult<T>,
+        binary: impl FnOnce(&[u8]) -> SignifyResult<T>,
+    ) -> Parse<T> {
+        let first = self.chars(cold, 1)?;
+        let hs = match first.chars().next().and_then(|c| HARDS.get(&c)) {
+            Some(hs) => *hs,
+            None => return self.fail(self.pos, format!("Unknown matter code {}", first)),
+        };
+        let code = self.chars(cold, hs)?;
+        let sz = match sizage(&code) {
+            Ok(sz) => sz,
+            Err(e) => return self.fail(self.pos, e.to_string()),
+        };
+
+        // Variable size codes give the material size in quadlets in the soft part
+        let cs = sz.hs + sz.ss;
+        let fs = match sz.fs {
+            Some(fs) => fs,
+            None => match b64_to_int(&self.chars(cold, cs)?[sz.hs..]) {
+                Ok(size) => cs + size * 4,
+                Err(e) => return self.fail(self.pos, e.to_string()),
+            },
+        };
+
+        self.primitive(cold, fs, text, binary)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::utils::VRSN_2_0;
+    use crate::core::{incept, matter_codes, IndexerCodex, Signer, Tholder};
+
+    fn make_signer(seed: u8, transferable: bool) -> Signer {
+        Signer::from_seed(&[seed; 32], matter_codes::ED25519_SEED, transferable).unwrap()
+    }
+
+    fn inception(signer: &Signer) -> Serder {
+        incept(
+            vec![signer.verfer().qb64().to_string()],
+            Some(&Tholder::unweighted(1)),
+            vec![],
+            Some(&Tholder::unweighted(0)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn siger(signer: &Signer, serder: &Serder, index: u32) -> Siger {
+        let sig = signer.sign(serder.raw().as_bytes()).unwrap();
+        Siger::new(&sig, IndexerCodex::ED25519_SIG, index, None, None).unwrap()
+    }
+
+    fn counter(code: &str, count: usize) -> Counter {
+        Counter::new(code, count as u32).unwrap()
+    }
+
+    #[test]
+    fn test_parse_stream_text_attachments() {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let siger = siger(&signer, &serder, 0);
+
+        // Non-transferable witness receipt couple
+        let witness = make_signer(2, false);
+        let sig = witness.sign(serder.raw().as_bytes()).unwrap();
+        let cigar = Cigar::new(&sig, matter_codes::ED25519_SIG, None).unwrap();
+
+        let mut stream = serder.raw().to_string();
+        stream.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        stream.push_str(&siger.qb64());
+        stream.push_str(&counter(CounterCodex::NON_TRANS_RCT, 1).qb64());
+        stream.push_str(witness.verfer().qb64());
+        stream.push_str(cigar.qb64());
+
+        let messages = parse_stream(stream.as_bytes()).unwrap();
+        assert_eq!(messages.len(), 1);
+
+        let message = &messages[0];
+        assert_eq!(message.offset, 0);
+        assert_eq!(message.serder.raw(), serder.raw());
+
+        let sigers = &message.attachments.sigers;
+        assert_eq!(sigers.len(), 1);
+        assert_eq!(sigers[0].qb64(), siger.qb64());
+        assert!(signer
+            .verfer()
+            .verify(sigers[0].raw(), serder.raw().as_bytes())
+            .unwrap());
+
+        let receipts = &message.attachments.receipts;
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].prefixer.qb64(), witness.verfer().qb64());
+        assert!(receipts[0].cigar.verify(serder.raw().as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_parse_stream_binary_attachments() {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let siger0 = siger(&signer, &serder, 0);
+        let siger1 = siger(&signer, &serder, 1);
+
+        let mut stream = serder.raw().as_bytes().to_vec();
+        stream.extend(counter(CounterCodex::CONTROLLER_IDX_SIGS, 2).qb2());
+        stream.extend(siger0.qb2());
+        stream.extend(siger1.qb2());
+
+        let messages = parse_stream(&stream).unwrap();
+        assert_eq!(messages.len(), 1);
+
+        let sigers = &messages[0].attachments.sigers;
+        assert_eq!(sigers.len(), 2);
+        assert_eq!(sigers[0].qb64(), siger0.qb64());
+        assert_eq!(sigers[1].index(), 1);
+    }
+
+    #[test]
+    fn test_parse_stream_quadlet_group_and_groups() {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let siger = siger(&signer, &serder, 0);
+        let prefixer = Prefixer::from_qb64(serder.pre().unwrap()).unwrap();
+        let seqner = Seqner::new(0).unwrap();
+        let diger = Diger::from_qb64(serder.said_field().unwrap()).unwrap();
+        let dater = Dater::new("2020-08-22T17:50:09.988921+00:00").unwrap();
+
+        // Attachments as kli exports them, wrapped in an attached material group
+        let mut atc = String::new();
+        atc.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        atc.push_str(&siger.qb64());
+        atc.push_str(&counter(CounterCodex::TRANS_IDX_SIG_GROUPS, 1).qb64());
+        atc.push_str(prefixer.qb64());
+        atc.push_str(seqner.qb64());
+        atc.push_str(diger.qb64());
+        atc.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        atc.push_str(&siger.qb64());
+        atc.push_str(&counter(CounterCodex::SEAL_SOURCE_COUPLES, 1).qb64());
+        atc.push_str(seqner.qb64());
+        atc.push_str(diger.qb64());
+        atc.push_str(&counter(CounterCodex::FIRST_SEEN_RPY, 1).qb64());
+        atc.push_str(seqner.qb64());
+        atc.push_str(dater.qb64());
+
+        let mut stream = serder.raw().to_string();
+        stream.push_str(&counter(CounterCodex::ATTACHED_MATERIAL_QUADLETS, atc.len() / 4).qb64());
+        stream.push_str(&atc);
+
+        // Second message directly after the first, then a newline separated third
+        let second_offset = stream.len();
+        stream.push_str(serder.raw());
+        stream.push('\n');
+        let third_offset = stream.len();
+        stream.push_str(serder.raw());
+        stream.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        stream.push_str(&siger.qb64());
+
+        let messages = parse_stream(stream.as_bytes()).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1].offset, second_offset);
+        assert_eq!(messages[2].offset, third_offset);
+
+        let atc = &messages[0].attachments;
+        assert_eq!(atc.sigers.len(), 1);
+        assert_eq!(atc.trans_sig_groups.len(), 1);
+        assert_eq!(atc.trans_sig_groups[0].prefixer.qb64(), prefixer.qb64());
+        assert_eq!(atc.trans_sig_groups[0].sigers[0].qb64(), siger.qb64());
+        assert_eq!(atc.seal_sources[0].1.qb64(), diger.qb64());
+        assert_eq!(atc.first_seen[0].0.sn(), 0);
+        assert_eq!(atc.first_seen[0].1.dts(), dater.dts());
+
+        assert!(messages[1].attachments.sigers.is_empty());
+        assert_eq!(messages[2].attachments.sigers.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_stream_group_depth() {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let siger = siger(&signer, &serder, 0);
+
+        let nest = |depth: usize| {
+            let mut atc = counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64();
+            atc.push_str(&siger.qb64());
+            for _ in 0..depth {
+                atc.insert_str(
+                    0,
+                    &counter(CounterCodex::ATTACHED_MATERIAL_QUADLETS, atc.len() / 4).qb64(),
+                );
+            }
+            format!("{}{}", serder.raw(), atc)
+        };
+
+        let messages = parse_stream(nest(MAX_GROUP_DEPTH).as_bytes()).unwrap();
+        assert_eq!(messages[0].attachments.sigers.len(), 1);
+
+        // Deeper nesting fails instead of recursing without bound
+        let stream = nest(1000);
+        match parse_stream(stream.as_bytes()) {
+            Err(SignifyError::StreamError { offset, message }) => {
+                assert_eq!(offset, serder.raw().len() + MAX_GROUP_DEPTH * 4);
+                assert!(message.contains("nested deeper"));
+            }
+            other => panic!("Expected a stream error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_stream_genus_version() {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let siger = siger(&signer, &serder, 0);
+
+        // CESR 2.0 controller signatures use -K
+        let mut stream = Counter::genus_version(VRSN_2_0).unwrap().qb64();
+        stream.push_str(serder.raw());
+        stream.push_str(
+            &Counter::new_with_version(CounterCodexV2::CONTROLLER_IDX_SIGS, 1, VRSN_2_0)
+                .unwrap()
+                .qb64(),
+        );
+        stream.push_str(&siger.qb64());
+
+        let mut parser = Parser::new();
+        let (message, consumed) = parser
+            .next_message(stream.as_bytes(), true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(consumed, stream.len());
+        assert_eq!(message.offset, 8);
+        assert_eq!(message.attachments.sigers.len(), 1);
+        assert_eq!(parser.version(), VRSN_2_0);
+    }
+
+    #[test]
+    fn test_parser_incremental() {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let siger = siger(&signer, &serder, 0);
+
+        let mut stream = serder.raw().to_string();
+        stream.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        stream.push_str(&siger.qb64());
+        let first_len = stream.len();
+        stream.push_str(serder.raw());
+        let bytes = stream.as_bytes();
+
+        let mut parser = Parser::new();
+
+        // Partial body, partial signature and a message ending at the buffer
+        // end all need more bytes
+        assert!(parser.next_message(&bytes[..40], false).unwrap().is_none());
+        assert!(parser
+            .next_message(&bytes[..first_len - 10], false)
+            .unwrap()
+            .is_none());
+        assert!(parser
+            .next_message(&bytes[..first_len], false)
+            .unwrap()
+            .is_none());
+        assert_eq!(parser.offset(), 0);
+
+        // Start of the next message frames the first, resuming after the
+        // signature group without parsing the framed bytes again
+        let mut resumed = bytes[..first_len + 1].to_vec();
+        resumed[..first_len].fill(b'#');
+        let (message, consumed) = parser.next_message(&resumed, false).unwrap().unwrap();
+        assert_eq!(consumed, first_len);
+        assert_eq!(message.serder.raw(), serder.raw());
+        assert_eq!(message.attachments.sigers.len(), 1);
+        assert_eq!(parser.offset(), first_len);
+
+        // Last message completes at end of stream
+        let (message, _) = parser
+            .next_message(&bytes[first_len..], true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.offset, first_len);
+        assert!(parser.next_message(&[], true).unwrap().is_none());
+    }
+
+    /// Read source handing out at most `step` bytes per call
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.step.min(out.len()).min(self.data.len());
+            out[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn signed_stream(count: usize) -> (String, usize) {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let mut message = serder.raw().to_string();
+        message.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        message.push_str(&siger(&signer, &serder, 0).qb64());
+        (message.repeat(count), message.len())
+    }
+
+    #[test]
+    fn test_message_reader_small_chunks() {
+        let (stream, len) = signed_stream(3);
+        let reader = MessageReader::with_max_buffer(
+            Trickle {
+                data: stream.as_bytes(),
+                step: 7,
+            },
+            len + 1,
+        );
+
+        let messages: Vec<Message> = reader.collect::<SignifyResult<_>>().unwrap();
+        assert_eq!(messages.len(), 3);
+        for (i, message) in messages.iter().enumerate() {
+            assert_eq!(message.offset, i * len);
+            assert_eq!(message.attachments.sigers.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_message_reader_buffer_limit() {
+        let (stream, len) = signed_stream(2);
+        let mut reader = MessageReader::with_max_buffer(stream.as_bytes(), len - 1);
+
+        let err = reader.next_message().unwrap_err();
+        assert!(err.to_string().contains("buffer limit"));
+
+        // Feeding past the limit directly is also refused
+        let mut buffer = MessageBuffer::new(4);
+        assert!(buffer.feed(b"{\"v\"").is_ok());
+        assert!(buffer.feed(b":").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_async_message_reader() {
+        let (stream, len) = signed_stream(2);
+        let mut reader = AsyncMessageReader::with_max_buffer(stream.as_bytes(), len + 1);
+
+        let first = reader.next_message().await.unwrap().unwrap();
+        assert_eq!(first.offset, 0);
+        let second = reader.next_message().await.unwrap().unwrap();
+        assert_eq!(second.offset, len);
+        assert!(reader.next_message().await.unwrap().is_none());
+
+        // Truncated stream reports where the message broke off
+        let mut reader = AsyncMessageReader::new(&stream.as_bytes()[..len - 5]);
+        assert!(reader.next_message().await.is_err());
+    }
+
+    #[test]
+    fn test_parse_stream_error_offsets() {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let siger = siger(&signer, &serder, 0);
+        let body_len = serder.raw().len();
+
+        // Unsupported counter right after the body
+        let stream = format!("{}-ZAB", serder.raw());
+        match parse_stream(stream.as_bytes()) {
+            Err(SignifyError::StreamError { offset, .. }) => assert_eq!(offset, body_len),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Bad indexer code in the second signature
+        let mut stream = serder.raw().to_string();
+        stream.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 2).qb64());
+        stream.push_str(&siger.qb64());
+        stream.push_str(&siger.qb64().replacen('A', "Z", 1));
+        match parse_stream(stream.as_bytes()) {
+            Err(SignifyError::StreamError { offset, .. }) => {
+                assert_eq!(offset, body_len + 4 + 88)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Truncated signature at end of stream
+        let mut stream = serder.raw().to_string();
+        stream.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        stream.push_str(&siger.qb64()[..40]);
+        match parse_stream(stream.as_bytes()) {
+            Err(SignifyError::StreamError { offset, message }) => {
+                assert_eq!(offset, stream.len());
+                assert!(message.contains("end of stream"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Quadlet group whose nested content overruns its count
+        let mut stream = serder.raw().to_string();
+        stream.push_str(&counter(CounterCodex::ATTACHED_MATERIAL_QUADLETS, 2).qb64());
+        stream.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        stream.push_str(&siger.qb64());
+        match parse_stream(stream.as_bytes()) {
+            Err(SignifyError::StreamError { offset, .. }) => assert_eq!(offset, body_len),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Signature with a multi-byte char in its index
+        let mut stream = serder.raw().to_string();
+        stream.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        stream.push('A');
+        stream.push('é');
+        stream.push_str(&"A".repeat(85));
+        match parse_stream(stream.as_bytes()) {
+            Err(SignifyError::StreamError { offset, .. }) => assert_eq!(offset, body_len + 4),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Attachments without a message
+        assert!(parse_stream(b"-AAB").is_err());
+    }
+
+    #[test]
+    fn test_parse_cesr_extracts_inception_keys() {
+        let signer = make_signer(1, true);
+        let serder = inception(&signer);
+        let siger = siger(&signer, &serder, 0);
+
+        let mut stream = serder.raw().to_string();
+        stream.push_str(&counter(CounterCodex::CONTROLLER_IDX_SIGS, 1).qb64());
+        stream.push_str(&siger.qb64());
+        stream.push('\n');
+
+        let keys = parse_cesr(&stream).unwrap();
+        assert_eq!(keys.public_key, signer.verfer().raw());
+        assert_eq!(keys.did, format!("did:keri:{}", serder.pre().unwrap()));
+    }
 
     #[test]
     fn test_parse_empty_cesr() {
@@ -0,0 +1,62 @@
+//! BADA - Best available data acceptance
+//!
+//! Replies and key state notices are accepted under the keripy BADA rules.
+//! A signed reply replaces the one accepted before for the same record only
+//! when signed at a later establishment event of its signer or, at the same
+//! one or by a non-transferable signer, with a later datetime. Both the
+//! `Revery` and the `Kevery` apply them, and the `Baser` stores what they
+//! accept.
+
+use crate::core::serder::Serder;
+use crate::error::{Result, SignifyError};
+use chrono::{DateTime, FixedOffset};
+use serde_json::Value;
+
+/// Reply accepted for a record
+#[derive(Debug, Clone)]
+pub struct AcceptedReply {
+    /// Reply message
+    pub serder: Serder,
+    /// Identifier that signed the reply
+    pub aid: String,
+    /// Sn of the signer's establishment event the signatures verified
+    /// against, `None` for non-transferable signers
+    pub est_sn: Option<u64>,
+}
+
+impl AcceptedReply {
+    /// Route of the reply
+    pub fn route(&self) -> &str {
+        self.serder.sad()["r"].as_str().unwrap_or_default()
+    }
+
+    /// Datetime of the reply
+    pub fn dt(&self) -> &str {
+        self.serder.sad()["dt"].as_str().unwrap_or_default()
+    }
+
+    /// Body of the reply
+    pub fn data(&self) -> &Value {
+        &self.serder.sad()["a"]
+    }
+}
+
+/// Parse the datetime of a reply
+pub(crate) fn parse_dt(dt: &str) -> Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(dt)
+        .map_err(|_| SignifyError::InvalidEvent(format!("Invalid datetime {}", dt)))
+}
+
+/// Whether a reply signed at establishment event `est_sn` with datetime
+/// `dt` is fresher than the prior reply under the BADA rules
+pub(crate) fn fresher(
+    est_sn: Option<u64>,
+    dt: &str,
+    prior_est_sn: Option<u64>,
+    prior_dt: &str,
+) -> Result<bool> {
+    Ok(match (est_sn, prior_est_sn) {
+        (Some(sn), Some(prior_sn)) if sn != prior_sn => sn > prior_sn,
+        _ => parse_dt(dt)? > parse_dt(prior_dt)?,
+    })
+}
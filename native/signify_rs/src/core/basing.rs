@@ -0,0 +1,918 @@
+//! Baser - Persistent key event database
+//!
+//! Baser stores accepted key events on disk in an embedded redb database,
+//! analogous to the keripy Baser. Events are kept by digest key
+//! `(prefix, said)` together with their controller signatures, witness
+//! signatures, receipt couples, transferable receipt quadruples of
+//! validators, delegator seal source and the datetime they were first seen. Two indexes order the events of each
+//! prefix:
+//!
+//! * the key event log (KEL) by sn, holding the accepted event at each sn
+//! * the first seen event log (FEL) by first-seen ordinal, holding every
+//!   event in the order it was first accepted, including events a recovery
+//!   rotation superseded later
+//!
+//! Either log replays as a CESR stream laid out the way keripy clones events
+//! for `kli export`, so the stream is byte-compatible with keripy exports.
+//!
+//! Likely duplicitous events are kept by digest key like accepted events,
+//! with their signatures, and logged for their prefix together with the
+//! accepted event they conflict with and the datetime they were first seen.
+//!
+//! Replies accepted by the `Revery` are kept by the route and key of the
+//! record they update, with the signer and the establishment event its
+//! signatures verified against. Key state notices accepted by the `Kevery`
+//! are kept the same way by the identifier they are about and their source.
+
+use crate::cesr_parser::TransReceipt;
+use crate::core::bada::AcceptedReply;
+use crate::core::cigar::Cigar;
+use crate::core::counter::{Counter, CounterCodex};
+use crate::core::dater::Dater;
+use crate::core::diger::Diger;
+use crate::core::eventing::counted;
+use crate::core::kevery::{Duplicity, KelEvent, KeyStateNotice};
+use crate::core::prefixer::Prefixer;
+use crate::core::seal::Seal;
+use crate::core::seqner::Seqner;
+use crate::core::serder::Serder;
+use crate::core::siger::Siger;
+use crate::core::state::KeyStateRecord;
+use crate::core::utils::now_iso8601;
+use crate::core::verfer::Verfer;
+use crate::error::{Result, SignifyError};
+use redb::{Database, MultimapTableDefinition, ReadableTable, TableDefinition, WriteTransaction};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Raw events by `(prefix, said)`
+const EVTS: TableDefinition<(&str, &str), &str> = TableDefinition::new("evts");
+/// SAID of the accepted event by `(prefix, sn)`
+const KELS: TableDefinition<(&str, u64), &str> = TableDefinition::new("kels");
+/// SAID of the event by `(prefix, first-seen ordinal)`
+const FELS: TableDefinition<(&str, u64), &str> = TableDefinition::new("fels");
+/// First-seen ordinal by `(prefix, said)`
+const FONS: TableDefinition<(&str, &str), u64> = TableDefinition::new("fons");
+/// First-seen datetime by `(prefix, said)`
+const DTSS: TableDefinition<(&str, &str), &str> = TableDefinition::new("dtss");
+/// Delegator seal source by `(prefix, said)`
+const AESS: TableDefinition<(&str, &str), &str> = TableDefinition::new("aess");
+/// Controller indexed signatures by `(prefix, said)`
+const SIGS: MultimapTableDefinition<(&str, &str), &str> = MultimapTableDefinition::new("sigs");
+/// Witness indexed signatures by `(prefix, said)`
+const WIGS: MultimapTableDefinition<(&str, &str), &str> = MultimapTableDefinition::new("wigs");
+/// Witness receipt couples `<verfer>.<cigar>` by `(prefix, said)`
+const RCTS: MultimapTableDefinition<(&str, &str), &str> = MultimapTableDefinition::new("rcts");
+/// Transferable receipt quadruples `<prefix>.<seqner>.<diger>.<siger>` of
+/// validators by `(prefix, said)`
+const VRCS: MultimapTableDefinition<(&str, &str), &str> = MultimapTableDefinition::new("vrcs");
+/// Likely duplicitous events `{d, accepted, dt}` by `(prefix, ordinal)`
+const LDES: TableDefinition<(&str, u64), &str> = TableDefinition::new("ldes");
+/// Accepted replies `{aid, sn, rpy}` by `(route, record key)`
+const RPYS: TableDefinition<(&str, &str), &str> = TableDefinition::new("rpys");
+/// Accepted key state notices `{a, dt, d, sn}` by `(prefix, source)`
+const KSNS: TableDefinition<(&str, &str), &str> = TableDefinition::new("ksns");
+
+/// Event of a first seen event log with its first-seen ordinal and datetime
+#[derive(Debug, Clone)]
+pub struct FirstSeen {
+    /// First-seen ordinal of the event among the events of its prefix
+    pub fnu: u64,
+    /// ISO-8601 datetime the event was first seen
+    pub dt: String,
+    /// Event with its signatures and receipts
+    pub event: KelEvent,
+}
+
+/// Persistent key event database
+///
+/// Clones share the same database.
+#[derive(Debug, Clone)]
+pub struct Baser {
+    db: Arc<Database>,
+}
+
+impl Baser {
+    /// Open the database at `path`, creating it when missing
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let db = Database::create(path)?;
+
+        let txn = db.begin_write()?;
+        for table in [EVTS, DTSS, AESS, RPYS, KSNS] {
+            txn.open_table(table)?;
+        }
+        txn.open_table(KELS)?;
+        txn.open_table(FELS)?;
+        txn.open_table(LDES)?;
+        txn.open_table(FONS)?;
+        for table in [SIGS, WIGS, RCTS, VRCS] {
+            txn.open_multimap_table(table)?;
+        }
+        txn.commit()?;
+
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Log an accepted event with its signatures and receipts
+    ///
+    /// The event becomes the last event of the KEL of its prefix, dropping
+    /// any events at higher sns it supersedes. Events seen for the first
+    /// time get the next first-seen ordinal of their prefix and the current
+    /// datetime. Returns the first-seen ordinal of the event.
+    pub fn log_event(&self, event: &KelEvent) -> Result<u64> {
+        let (pre, sn, said) = event_key(&event.serder)?;
+
+        let txn = self.db.begin_write()?;
+        let fnu = {
+            let mut kels = txn.open_table(KELS)?;
+            kels.insert((pre, sn), said)?;
+            let superseded = (Bound::Excluded((pre, sn)), Bound::Included((pre, u64::MAX)));
+            kels.retain_in(superseded, |_, _| false)?;
+
+            txn.open_table(EVTS)?
+                .insert((pre, said), event.serder.raw())?;
+            if let Some(source) = &event.source {
+                let seal = Seal::Source(source.clone()).to_value().to_string();
+                txn.open_table(AESS)?.insert((pre, said), seal.as_str())?;
+            }
+
+            let mut fons = txn.open_table(FONS)?;
+            let seen = fons.get((pre, said))?.map(|fnu| fnu.value());
+            match seen {
+                Some(fnu) => fnu,
+                None => {
+                    let mut fels = txn.open_table(FELS)?;
+                    let fnu = fels
+                        .range((pre, 0)..=(pre, u64::MAX))?
+                        .next_back()
+                        .transpose()?
+                        .map_or(0, |(key, _)| key.value().1 + 1);
+                    fels.insert((pre, fnu), said)?;
+                    fons.insert((pre, said), fnu)?;
+                    txn.open_table(DTSS)?
+                        .insert((pre, said), now_iso8601().as_str())?;
+                    fnu
+                }
+            }
+        };
+        put_signatures(&txn, pre, said, &event.sigers)?;
+        put_receipts(
+            &txn,
+            pre,
+            said,
+            &event.wigers,
+            &event.cigars,
+            &event.trans_receipts,
+        )?;
+        txn.commit()?;
+
+        Ok(fnu)
+    }
+
+    /// Add controller signatures of a stored event
+    pub fn add_signatures(&self, pre: &str, said: &str, sigers: &[Siger]) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        put_signatures(&txn, pre, said, sigers)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Add witness signatures, receipt couples and transferable receipt
+    /// quadruples of a stored event
+    ///
+    /// Receipt couple cigars must carry the witness verfer.
+    pub fn add_receipts(
+        &self,
+        pre: &str,
+        said: &str,
+        wigers: &[Siger],
+        cigars: &[Cigar],
+        trans_receipts: &[TransReceipt],
+    ) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        put_receipts(&txn, pre, said, wigers, cigars, trans_receipts)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Stored event with its signatures and receipts
+    pub fn get_event(&self, pre: &str, said: &str) -> Result<Option<KelEvent>> {
+        let txn = self.db.begin_read()?;
+        let Some(raw) = txn.open_table(EVTS)?.get((pre, said))? else {
+            return Ok(None);
+        };
+        let serder = Serder::from_raw(raw.value())?;
+
+        let values = |table: MultimapTableDefinition<(&str, &str), &str>| -> Result<Vec<String>> {
+            txn.open_multimap_table(table)?
+                .get((pre, said))?
+                .map(|value| Ok(value?.value().to_string()))
+                .collect()
+        };
+        let sigers = values(SIGS)?
+            .iter()
+            .map(|qb64| Siger::from_qb64(qb64, None))
+            .collect::<Result<Vec<_>>>()?;
+        let wigers = values(WIGS)?
+            .iter()
+            .map(|qb64| Siger::from_qb64(qb64, None))
+            .collect::<Result<Vec<_>>>()?;
+        let cigars = values(RCTS)?
+            .iter()
+            .map(|couple| {
+                let (verfer, cigar) = couple.split_once('.').ok_or_else(|| {
+                    SignifyError::InvalidFormat(format!("Invalid receipt couple {}", couple))
+                })?;
+                Cigar::from_qb64(cigar, Some(Verfer::from_qb64(verfer)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let trans_receipts = values(VRCS)?
+            .iter()
+            .map(|quadruple| {
+                let parts: Vec<&str> = quadruple.split('.').collect();
+                let [prefixer, seqner, diger, siger] = parts[..] else {
+                    return Err(SignifyError::InvalidFormat(format!(
+                        "Invalid receipt quadruple {}",
+                        quadruple
+                    )));
+                };
+                Ok(TransReceipt {
+                    prefixer: Prefixer::from_qb64(prefixer)?,
+                    seqner: Seqner::from_qb64(seqner)?,
+                    diger: Diger::from_qb64(diger)?,
+                    siger: Siger::from_qb64(siger, None)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let source = match txn.open_table(AESS)?.get((pre, said))? {
+            Some(seal) => match Seal::from_value(&serde_json::from_str(seal.value())?)? {
+                Seal::Source(source) => Some(source),
+                _ => {
+                    return Err(SignifyError::InvalidFormat(format!(
+                        "Invalid seal source {}",
+                        seal.value()
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        Ok(Some(KelEvent {
+            serder,
+            sigers,
+            wigers,
+            cigars,
+            trans_receipts,
+            source,
+        }))
+    }
+
+    /// First-seen ordinal and datetime of a stored event
+    pub fn first_seen(&self, pre: &str, said: &str) -> Result<Option<(u64, String)>> {
+        let txn = self.db.begin_read()?;
+        let Some(fnu) = txn.open_table(FONS)?.get((pre, said))? else {
+            return Ok(None);
+        };
+        let dt = txn
+            .open_table(DTSS)?
+            .get((pre, said))?
+            .map(|dt| dt.value().to_string())
+            .unwrap_or_default();
+        Ok(Some((fnu.value(), dt)))
+    }
+
+    /// Accepted events of a prefix with sn in `sns`, in sn order
+    pub fn kel(&self, pre: &str, sns: impl RangeBounds<u64>) -> Result<Vec<KelEvent>> {
+        self.indexed(KELS, pre, sns)?
+            .into_iter()
+            .map(|(_, said)| self.stored(pre, &said))
+            .collect()
+    }
+
+    /// First seen events of a prefix with first-seen ordinal in `fns`, in
+    /// first seen order
+    pub fn fel(&self, pre: &str, fns: impl RangeBounds<u64>) -> Result<Vec<FirstSeen>> {
+        self.indexed(FELS, pre, fns)?
+            .into_iter()
+            .map(|(fnu, said)| {
+                let event = self.stored(pre, &said)?;
+                let dt = self
+                    .first_seen(pre, &said)?
+                    .map(|(_, dt)| dt)
+                    .unwrap_or_default();
+                Ok(FirstSeen { fnu, dt, event })
+            })
+            .collect()
+    }
+
+    /// Replay the accepted events of a prefix with sn in `sns` as a CESR
+    /// stream, each event with all its signatures and receipts attached
+    pub fn replay(&self, pre: &str, sns: impl RangeBounds<u64>) -> Result<Vec<u8>> {
+        let mut stream = Vec::new();
+        for event in self.kel(pre, sns)? {
+            let said = event.serder.said_field().unwrap_or_default();
+            let (fnu, dt) = self.first_seen(pre, said)?.ok_or_else(|| {
+                SignifyError::NotFound(format!("First seen of event {} of {}", said, pre))
+            })?;
+            stream.extend(cloned(&event, fnu, &dt)?);
+        }
+        Ok(stream)
+    }
+
+    /// Replay the first seen events of a prefix with first-seen ordinal in
+    /// `fns` as a CESR stream, like `kli export` does for the whole FEL
+    pub fn replay_fel(&self, pre: &str, fns: impl RangeBounds<u64>) -> Result<Vec<u8>> {
+        let mut stream = Vec::new();
+        for seen in self.fel(pre, fns)? {
+            stream.extend(cloned(&seen.event, seen.fnu, &seen.dt)?);
+        }
+        Ok(stream)
+    }
+
+    /// Log a likely duplicitous event with its signatures and receipts, or
+    /// add signatures and receipts of one logged before
+    pub fn log_duplicity(&self, duplicity: &Duplicity) -> Result<()> {
+        let (pre, _, said) = event_key(&duplicity.duplicitous.serder)?;
+        let accepted = duplicity.accepted.serder.said_field().unwrap_or_default();
+
+        let txn = self.db.begin_write()?;
+        {
+            let mut ldes = txn.open_table(LDES)?;
+            let mut next = 0;
+            let mut seen = false;
+            for entry in ldes.range((pre, 0)..=(pre, u64::MAX))? {
+                let (key, value) = entry?;
+                let logged: Value = serde_json::from_str(value.value())?;
+                seen |= logged["d"] == said;
+                next = key.value().1 + 1;
+            }
+            if !seen {
+                let value = json!({
+                    "d": said,
+                    "accepted": accepted,
+                    "dt": duplicity.dt,
+                })
+                .to_string();
+                ldes.insert((pre, next), value.as_str())?;
+                txn.open_table(EVTS)?
+                    .insert((pre, said), duplicity.duplicitous.serder.raw())?;
+            }
+        }
+        let event = &duplicity.duplicitous;
+        put_signatures(&txn, pre, said, &event.sigers)?;
+        put_receipts(
+            &txn,
+            pre,
+            said,
+            &event.wigers,
+            &event.cigars,
+            &event.trans_receipts,
+        )?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Likely duplicitous events logged for a prefix, in the order first seen
+    pub fn duplicity(&self, pre: &str) -> Result<Vec<Duplicity>> {
+        let logged = {
+            let txn = self.db.begin_read()?;
+            let ldes = txn.open_table(LDES)?;
+            let entries = ldes.range((pre, 0)..=(pre, u64::MAX))?;
+            entries
+                .map(|entry| Ok(serde_json::from_str::<Value>(entry?.1.value())?))
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        logged
+            .iter()
+            .map(|logged| {
+                let field = |label: &str| {
+                    logged[label].as_str().ok_or_else(|| {
+                        SignifyError::InvalidFormat(format!(
+                            "Missing {} of duplicitous event of {}",
+                            label, pre
+                        ))
+                    })
+                };
+                Ok(Duplicity {
+                    accepted: self.stored(pre, field("accepted")?)?,
+                    duplicitous: self.stored(pre, field("d")?)?,
+                    dt: field("dt")?.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Store the reply accepted for the record `key` of `route`, replacing
+    /// the reply accepted for it before
+    pub fn put_reply(&self, route: &str, key: &str, reply: &AcceptedReply) -> Result<()> {
+        let value = json!({
+            "aid": reply.aid,
+            "sn": reply.est_sn,
+            "rpy": reply.serder.raw(),
+        })
+        .to_string();
+
+        let txn = self.db.begin_write()?;
+        txn.open_table(RPYS)?.insert((route, key), value.as_str())?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Accepted replies with the route and key of the record they update
+    pub fn replies(&self) -> Result<Vec<(String, String, AcceptedReply)>> {
+        let txn = self.db.begin_read()?;
+        let mut replies = Vec::new();
+        for entry in txn.open_table(RPYS)?.iter()? {
+            let (key, value) = entry?;
+            let (route, key) = key.value();
+            let stored: Value = serde_json::from_str(value.value())?;
+            let field = |label: &str| {
+                stored[label].as_str().ok_or_else(|| {
+                    SignifyError::InvalidFormat(format!(
+                        "Missing {} of reply for {} on {}",
+                        label, key, route
+                    ))
+                })
+            };
+            let reply = AcceptedReply {
+                serder: Serder::from_raw(field("rpy")?)?,
+                aid: field("aid")?.to_string(),
+                est_sn: stored["sn"].as_u64(),
+            };
+            replies.push((route.to_string(), key.to_string(), reply));
+        }
+        Ok(replies)
+    }
+
+    /// Store the key state notice accepted from its source, replacing the
+    /// notice accepted from it before
+    pub fn put_ksn(&self, notice: &KeyStateNotice) -> Result<()> {
+        let value = json!({
+            "a": notice.record.to_value(),
+            "dt": notice.dt,
+            "d": notice.said,
+            "sn": notice.est_sn,
+        })
+        .to_string();
+
+        let txn = self.db.begin_write()?;
+        txn.open_table(KSNS)?.insert(
+            (notice.record.i.as_str(), notice.src.as_str()),
+            value.as_str(),
+        )?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Accepted key state notices
+    pub fn ksns(&self) -> Result<Vec<KeyStateNotice>> {
+        let txn = self.db.begin_read()?;
+        let mut notices = Vec::new();
+        for entry in txn.open_table(KSNS)?.iter()? {
+            let (key, value) = entry?;
+            let (pre, src) = key.value();
+            let stored: Value = serde_json::from_str(value.value())?;
+            let field = |label: &str| {
+                stored[label].as_str().map(str::to_string).ok_or_else(|| {
+                    SignifyError::InvalidFormat(format!(
+                        "Missing {} of key state notice of {} about {}",
+                        label, src, pre
+                    ))
+                })
+            };
+            notices.push(KeyStateNotice {
+                record: KeyStateRecord::from_value(&stored["a"])?,
+                src: src.to_string(),
+                dt: field("dt")?,
+                said: field("d")?,
+                est_sn: stored["sn"].as_u64(),
+            });
+        }
+        Ok(notices)
+    }
+
+    /// Prefixes with stored events
+    pub fn prefixes(&self) -> Result<Vec<String>> {
+        let txn = self.db.begin_read()?;
+        let mut prefixes = BTreeSet::new();
+        for entry in txn.open_table(KELS)?.iter()? {
+            prefixes.insert(entry?.0.value().0.to_string());
+        }
+        Ok(prefixes.into_iter().collect())
+    }
+
+    /// SAIDs indexed by `(prefix, ordinal)` with ordinal in `range`
+    fn indexed(
+        &self,
+        table: TableDefinition<(&str, u64), &str>,
+        pre: &str,
+        range: impl RangeBounds<u64>,
+    ) -> Result<Vec<(u64, String)>> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(0) => return Ok(Vec::new()),
+            Bound::Excluded(&n) => n - 1,
+            Bound::Unbounded => u64::MAX,
+        };
+        if start > end {
+            return Ok(Vec::new());
+        }
+
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(table)?;
+        let entries = table.range((pre, start)..=(pre, end))?;
+        entries
+            .map(|entry| {
+                let (key, said) = entry?;
+                Ok((key.value().1, said.value().to_string()))
+            })
+            .collect()
+    }
+
+    fn stored(&self, pre: &str, said: &str) -> Result<KelEvent> {
+        self.get_event(pre, said)?
+            .ok_or_else(|| SignifyError::NotFound(format!("Event {} of {}", said, pre)))
+    }
+}
+
+/// Event followed by an attached material group (`-V`) of its controller
+/// signatures, witness signatures, delegator seal source couple, receipt
+/// couples and first seen replay couple
+fn cloned(event: &KelEvent, fnu: u64, dt: &str) -> Result<Vec<u8>> {
+    if event.sigers.is_empty() {
+        return Err(SignifyError::InvalidState(format!(
+            "Missing signatures of event {}",
+            event.serder.said_field().unwrap_or_default()
+        )));
+    }
+
+    let mut atc = counted(CounterCodex::CONTROLLER_IDX_SIGS, event.sigers.len())?;
+    for siger in &event.sigers {
+        atc.push_str(&siger.qb64());
+    }
+
+    if !event.wigers.is_empty() {
+        atc.push_str(&counted(
+            CounterCodex::WITNESS_IDX_SIGS,
+            event.wigers.len(),
+        )?);
+        for wiger in &event.wigers {
+            atc.push_str(&wiger.qb64());
+        }
+    }
+
+    if let Some(source) = &event.source {
+        atc.push_str(&counted(CounterCodex::SEAL_SOURCE_COUPLES, 1)?);
+        atc.push_str(Seqner::from_snh(&source.s)?.qb64());
+        atc.push_str(Diger::from_qb64(&source.d)?.qb64());
+    }
+
+    if !event.trans_receipts.is_empty() {
+        atc.push_str(&counted(
+            CounterCodex::TRANS_RCT,
+            event.trans_receipts.len(),
+        )?);
+        for rct in &event.trans_receipts {
+            atc.push_str(rct.prefixer.qb64());
+            atc.push_str(rct.seqner.qb64());
+            atc.push_str(rct.diger.qb64());
+            atc.push_str(&rct.siger.qb64());
+        }
+    }
+
+    if !event.cigars.is_empty() {
+        atc.push_str(&counted(CounterCodex::NON_TRANS_RCT, event.cigars.len())?);
+        for cigar in &event.cigars {
+            let verfer = cigar.verfer().ok_or_else(|| {
+                SignifyError::InvalidState("Missing verfer for receipt couple".to_string())
+            })?;
+            atc.push_str(verfer.qb64());
+            atc.push_str(cigar.qb64());
+        }
+    }
+
+    atc.push_str(&counted(CounterCodex::FIRST_SEEN_RPY, 1)?);
+    atc.push_str(Seqner::new(fnu as u128)?.qb64());
+    atc.push_str(Dater::new(dt)?.qb64());
+
+    let count = atc.len() / 4;
+    let code = if count < 64 * 64 {
+        CounterCodex::ATTACHED_MATERIAL_QUADLETS
+    } else {
+        CounterCodex::BIG_ATTACHED_MATERIAL_QUADLETS
+    };
+
+    let mut msg = event.serder.raw().as_bytes().to_vec();
+    msg.extend(Counter::new(code, count as u32)?.qb64().as_bytes());
+    msg.extend(atc.as_bytes());
+    Ok(msg)
+}
+
+fn put_signatures(txn: &WriteTransaction, pre: &str, said: &str, sigers: &[Siger]) -> Result<()> {
+    let mut sigs = txn.open_multimap_table(SIGS)?;
+    for siger in sigers {
+        sigs.insert((pre, said), siger.qb64().as_str())?;
+    }
+    Ok(())
+}
+
+fn put_receipts(
+    txn: &WriteTransaction,
+    pre: &str,
+    said: &str,
+    wigers: &[Siger],
+    cigars: &[Cigar],
+    trans_receipts: &[TransReceipt],
+) -> Result<()> {
+    let mut wigs = txn.open_multimap_table(WIGS)?;
+    for wiger in wigers {
+        wigs.insert((pre, said), wiger.qb64().as_str())?;
+    }
+    let mut rcts = txn.open_multimap_table(RCTS)?;
+    for cigar in cigars {
+        let verfer = cigar.verfer().ok_or_else(|| {
+            SignifyError::InvalidArgument("Missing verfer for receipt couple".to_string())
+        })?;
+        let couple = format!("{}.{}", verfer.qb64(), cigar.qb64());
+        rcts.insert((pre, said), couple.as_str())?;
+    }
+    let mut vrcs = txn.open_multimap_table(VRCS)?;
+    for rct in trans_receipts {
+        let quadruple = format!(
+            "{}.{}.{}.{}",
+            rct.prefixer.qb64(),
+            rct.seqner.qb64(),
+            rct.diger.qb64(),
+            rct.siger.qb64()
+        );
+        vrcs.insert((pre, said), quadruple.as_str())?;
+    }
+    Ok(())
+}
+
+fn event_key(serder: &Serder) -> Result<(&str, u64, &str)> {
+    let pre = serder
+        .pre()
+        .ok_or_else(|| SignifyError::InvalidEvent("Missing prefix".to_string()))?;
+    let sn = serder
+        .sn()
+        .ok_or_else(|| SignifyError::InvalidEvent(format!("Missing sn for {}", pre)))?;
+    let said = serder
+        .said_field()
+        .ok_or_else(|| SignifyError::InvalidEvent(format!("Missing SAID for {}", pre)))?;
+    Ok((pre, sn, said))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cesr_parser::parse_stream;
+    use crate::core::codes::matter_codes;
+    use crate::core::eventing::{interact, rotate, RotateArgs};
+    use crate::core::indexer::IndexerCodex;
+    use crate::core::kevery::{Kevery, Processed};
+    use crate::core::signer::Signer;
+    use crate::core::test_support::{dig, event, icp, signer};
+    use std::slice::from_ref;
+
+    #[test]
+    fn test_baser() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keri.redb");
+        let (s0, s1, s2) = (signer(true), signer(true), signer(true));
+
+        let icp = icp(from_ref(&s0), from_ref(&s1), &[], None, None);
+        let pre = icp.pre().unwrap().to_string();
+        let ixn = interact(&pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        let rot = rotate(RotateArgs {
+            pre: pre.clone(),
+            keys: vec![s1.verfer().qb64().to_string()],
+            dig: icp.said_field().unwrap().to_string(),
+            sn: 1,
+            ndigs: vec![dig(&s2)],
+            ..Default::default()
+        })
+        .unwrap();
+        let (icp, ixn, rot) = (event(icp, &s0), event(ixn, &s0), event(rot, &s1));
+
+        {
+            let baser = Baser::open(&path).unwrap();
+            assert_eq!(baser.log_event(&icp).unwrap(), 0);
+            assert_eq!(baser.log_event(&ixn).unwrap(), 1);
+            assert_eq!(baser.log_event(&icp).unwrap(), 0);
+
+            let witness = Signer::new_random(matter_codes::ED25519_SEED, false).unwrap();
+            let sig = witness.sign(icp.serder.raw().as_bytes()).unwrap();
+            let bare = Cigar::new(&sig, matter_codes::ED25519_SIG, None).unwrap();
+            let cigar = Cigar::new(
+                &sig,
+                matter_codes::ED25519_SIG,
+                Some(witness.verfer().clone()),
+            )
+            .unwrap();
+            let said = icp.serder.said_field().unwrap();
+            baser.add_receipts(&pre, said, &[], &[cigar], &[]).unwrap();
+            assert!(baser.add_receipts(&pre, said, &[], &[bare], &[]).is_err());
+
+            // Recovery rotation supersedes the interaction event
+            assert_eq!(baser.log_event(&rot).unwrap(), 2);
+        }
+
+        // Events survive reopening
+        let baser = Baser::open(&path).unwrap();
+        assert_eq!(baser.prefixes().unwrap(), std::slice::from_ref(&pre));
+
+        let kel = baser.kel(&pre, ..).unwrap();
+        let saids: Vec<&str> = kel.iter().filter_map(|e| e.serder.said_field()).collect();
+        assert_eq!(
+            saids,
+            [
+                icp.serder.said_field().unwrap(),
+                rot.serder.said_field().unwrap()
+            ]
+        );
+        assert_eq!(kel[0].sigers[0].qb64(), icp.sigers[0].qb64());
+        assert_eq!(kel[0].cigars.len(), 1);
+        assert!(kel[0].cigars[0]
+            .verify(icp.serder.raw().as_bytes())
+            .unwrap());
+        assert_eq!(baser.kel(&pre, 1..).unwrap().len(), 1);
+        assert!(baser.kel(&pre, ..0).unwrap().is_empty());
+
+        let fel = baser.fel(&pre, ..).unwrap();
+        let fns: Vec<u64> = fel.iter().map(|f| f.fnu).collect();
+        assert_eq!(fns, [0, 1, 2]);
+        assert_eq!(fel[1].event.serder.raw(), ixn.serder.raw());
+        assert!(!fel[1].dt.is_empty());
+        assert_eq!(baser.fel(&pre, 1..=1).unwrap().len(), 1);
+
+        let (fnu, dt) = baser
+            .first_seen(&pre, rot.serder.said_field().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(fnu, 2);
+        assert_eq!(dt, fel[2].dt);
+        assert!(baser.get_event(&pre, "EUnknown").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_baser_last_sn() {
+        let dir = tempfile::tempdir().unwrap();
+        let (s0, s1) = (signer(true), signer(true));
+        let icp = icp(from_ref(&s0), from_ref(&s1), &[], None, None);
+        let pre = icp.pre().unwrap().to_string();
+        let last = interact(&pre, icp.said_field().unwrap(), u64::MAX, None, None).unwrap();
+
+        // Logging at the highest sn leaves no later events to supersede
+        let baser = Baser::open(dir.path().join("keri.redb")).unwrap();
+        baser.log_event(&event(icp, &s0)).unwrap();
+        assert_eq!(baser.log_event(&event(last, &s0)).unwrap(), 1);
+        assert_eq!(baser.kel(&pre, ..).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_baser_kli_export() {
+        // KEL exported by keripy with first-seen datetimes fixed at
+        // 2021-01-01, as recorded in the keriox tests
+        let export = r#"{"v":"KERI10JSON00012b_","t":"icp","d":"EA_SbBUZYwqLVlAAn14d6QUBQCSReJlZ755JqTgmRhXH","i":"EA_SbBUZYwqLVlAAn14d6QUBQCSReJlZ755JqTgmRhXH","s":"0","kt":"1","k":["DKiNnDmdOkcBjcAqL2FFhMZnSlPfNyGrJlCjJmX5b1nU"],"nt":"1","n":["EMP7Lg6BtehOYZt2RwOqXLNfMUiUllejAp8G_5EiANXR"],"bt":"0","b":[],"c":[],"a":[]}-VAn-AABAAArkDBeflIAo4kBsKnc754XHJvdLnf04iq-noTFEJkbv2MeIGZtx6lIfJPmRSEmFMUkFW4otRrMeBGQ0-nlhHEE-EAB0AAAAAAAAAAAAAAAAAAAAAAA1AAG2021-01-01T00c00c00d000000p00c00{"v":"KERI10JSON000160_","t":"rot","d":"EHZks1BQ_ieuzASY7VoZNIOgIfnlE-SZJzO3OP_Wf3zM","i":"EA_SbBUZYwqLVlAAn14d6QUBQCSReJlZ755JqTgmRhXH","s":"1","p":"EA_SbBUZYwqLVlAAn14d6QUBQCSReJlZ755JqTgmRhXH","kt":"1","k":["DMm-PHnlVVw-yQGqxxQFH3ynIGBrwkOCll9NJsszS4M1"],"nt":"1","n":["EGDpG5Ca3-vx-0O_rCXo44CG9VfjvDM8kXZlXt5TRGqq"],"bt":"0","br":[],"ba":[],"a":[]}-VAn-AABAAB49lUrFy86023zwry5pLz3_stNBPLU2Zoj2HO02W-J-fXvA9EL7BOpuVjEdhPHz1KbRWOKljI8yY3PZR3PyiMG-EAB0AAAAAAAAAAAAAAAAAAAAAAB1AAG2021-01-01T00c00c00d000000p00c00{"v":"KERI10JSON000160_","t":"rot","d":"ECg9CiC6qW-Y8DF-TByP0x4tG_OvPkAtKSZuZU8ZiXYT","i":"EA_SbBUZYwqLVlAAn14d6QUBQCSReJlZ755JqTgmRhXH","s":"2","p":"EHZks1BQ_ieuzASY7VoZNIOgIfnlE-SZJzO3OP_Wf3zM","kt":"1","k":["DMjdd0iohdRbFaFUeQuK_9eSSS1AcQVwZpJXg-QGFqZX"],"nt":"1","n":["ECDBhT8ht1Z2WFeC6C_7sCAPkduj3DDjEz2cxI_RSo-I"],"bt":"0","br":[],"ba":[],"a":[]}-VAn-AABAAAtK2_idK0_YfBrswOvbjBFWtjTZ5XRRU42HC7eoph_gi67BCeTaMBUBKyx5LZYnAG3GzOl5Xj-CXkvzSlwJ10K-EAB0AAAAAAAAAAAAAAAAAAAAAAC1AAG2021-01-01T00c00c00d000000p00c00{"v":"KERI10JSON000160_","t":"rot","d":"EHnhq9u8zdNJB38yaY3r7G73LrnJsPakgSjJFk6vSxUs","i":"EA_SbBUZYwqLVlAAn14d6QUBQCSReJlZ755JqTgmRhXH","s":"3","p":"ECg9CiC6qW-Y8DF-TByP0x4tG_OvPkAtKSZuZU8ZiXYT","kt":"1","k":["DK3AM_4Jg07liB5_5jkA3kiv2iSEYsOSDMzw-4oMxA29"],"nt":"1","n":["EGdk-oXzuVUatJYeIuai9wlUJ0ulVUTrb9w0LPPuuyB0"],"bt":"0","br":[],"ba":[],"a":[]}-VAn-AABAAAMxebsLh1V2NIHJl0diSC242MSg5TNSbtgjZPuf34adjV9rs6B73pWyt-TmRWMIY-me9-pg3eN0p4wQsyBWIEC-EAB0AAAAAAAAAAAAAAAAAAAAAAD1AAG2021-01-01T00c00c00d000000p00c00"#;
+        let pre = "EA_SbBUZYwqLVlAAn14d6QUBQCSReJlZ755JqTgmRhXH";
+
+        let dir = tempfile::tempdir().unwrap();
+        let baser = Baser::open(dir.path().join("keri.redb")).unwrap();
+        let mut kevery = Kevery::with_baser(baser.clone()).unwrap();
+        for message in parse_stream(export.as_bytes()).unwrap() {
+            let processed = kevery.process_message(&message).unwrap();
+            assert_eq!(processed, Processed::Accepted);
+        }
+
+        // The replay matches the export byte for byte but for the datetimes
+        // the events were first seen here
+        let mut expected = export.to_string();
+        for fe in baser.fel(pre, ..).unwrap() {
+            let dater = Dater::new(&fe.dt).unwrap();
+            expected = expected.replacen("1AAG2021-01-01T00c00c00d000000p00c00", dater.qb64(), 1);
+        }
+        let stream = baser.replay(pre, ..).unwrap();
+        assert_eq!(String::from_utf8(stream).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_baser_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let (s0, s1, s2) = (signer(true), signer(true), signer(true));
+        let (witness, watcher) = (signer(false), signer(false));
+
+        let icp = icp(from_ref(&s0), from_ref(&s1), from_ref(&witness), None, None);
+        let pre = icp.pre().unwrap().to_string();
+        let ixn = interact(&pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        let rot = rotate(RotateArgs {
+            pre: pre.clone(),
+            keys: vec![s1.verfer().qb64().to_string()],
+            dig: icp.said_field().unwrap().to_string(),
+            sn: 1,
+            ndigs: vec![dig(&s2)],
+            toad: Some(1),
+            wits: vec![witness.verfer().qb64().to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut icp = event(icp, &s0);
+        let sig = witness.sign(icp.serder.raw().as_bytes()).unwrap();
+        icp.wigers = vec![Siger::new(&sig, IndexerCodex::ED25519_SIG, 0, None, None).unwrap()];
+        let sig = watcher.sign(icp.serder.raw().as_bytes()).unwrap();
+        icp.cigars = vec![Cigar::new(
+            &sig,
+            matter_codes::ED25519_SIG,
+            Some(watcher.verfer().clone()),
+        )
+        .unwrap()];
+        let witnessed = |serder: Serder, signer: &Signer| {
+            let mut event = event(serder, signer);
+            let sig = witness.sign(event.serder.raw().as_bytes()).unwrap();
+            event.wigers =
+                vec![Siger::new(&sig, IndexerCodex::ED25519_SIG, 0, None, None).unwrap()];
+            event
+        };
+        let (ixn, rot) = (witnessed(ixn, &s0), witnessed(rot, &s1));
+
+        let baser = Baser::open(dir.path().join("keri.redb")).unwrap();
+        for event in [&icp, &ixn, &rot] {
+            baser.log_event(event).unwrap();
+        }
+
+        // Attachments laid out as keripy clones events
+        let stream = baser.replay(&pre, ..1).unwrap();
+        let dt = baser
+            .first_seen(&pre, icp.serder.said_field().unwrap())
+            .unwrap()
+            .unwrap()
+            .1;
+        let atc = [
+            "-AAB".to_string(),
+            icp.sigers[0].qb64(),
+            "-BAB".to_string(),
+            icp.wigers[0].qb64(),
+            "-CAB".to_string(),
+            watcher.verfer().qb64().to_string(),
+            icp.cigars[0].qb64().to_string(),
+            "-EAB".to_string(),
+            Seqner::new(0).unwrap().qb64().to_string(),
+            Dater::new(&dt).unwrap().qb64().to_string(),
+        ]
+        .concat();
+        let counter = Counter::new(
+            CounterCodex::ATTACHED_MATERIAL_QUADLETS,
+            atc.len() as u32 / 4,
+        )
+        .unwrap();
+        let expected = [icp.serder.raw(), &counter.qb64(), &atc].concat();
+        assert_eq!(String::from_utf8(stream).unwrap(), expected);
+
+        // The KEL replays without the superseded interaction event, the FEL
+        // with it
+        let messages = parse_stream(&baser.replay(&pre, ..).unwrap()).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].serder.raw(), rot.serder.raw());
+        assert_eq!(
+            parse_stream(&baser.replay(&pre, 1..).unwrap())
+                .unwrap()
+                .len(),
+            1
+        );
+        let fel = parse_stream(&baser.replay_fel(&pre, ..).unwrap()).unwrap();
+        assert_eq!(fel.len(), 3);
+        assert_eq!(fel[1].serder.raw(), ixn.serder.raw());
+        assert_eq!(fel[2].attachments.first_seen[0].0.sn(), 2);
+
+        let first = &messages[0].attachments;
+        assert_eq!(first.sigers[0].qb64(), icp.sigers[0].qb64());
+        assert_eq!(first.wigers[0].qb64(), icp.wigers[0].qb64());
+        assert_eq!(first.receipts[0].cigar.qb64(), icp.cigars[0].qb64());
+        assert_eq!(first.first_seen[0].1.dts(), dt);
+
+        // Another processor accepts the replayed KEL
+        let mut kevery = Kevery::new();
+        for message in &messages {
+            assert_eq!(
+                kevery.process_message(message).unwrap(),
+                Processed::Accepted
+            );
+        }
+        assert_eq!(
+            kevery.kever(&pre).unwrap().said(),
+            rot.serder.said_field().unwrap()
+        );
+        assert_eq!(kevery.kel(&pre)[0].cigars.len(), 1);
+    }
+}
@@ -0,0 +1,153 @@
+/// Bexter - Variable length Base64 text strings as CESR primitives
+///
+/// Bexter carries strings drawn from the Base64 URL-safe alphabet compactly:
+/// the text is decoded into raw bytes so the qb64 form is the text itself,
+/// prefixed with a StrB64 code and left padded to align on quadlets.
+use crate::core::codes::{matter_codes, sizage};
+use crate::core::matter::Matter;
+use crate::error::{Result, SignifyError};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+/// Variable length Base64 text primitive
+#[derive(Debug, Clone)]
+pub struct Bexter {
+    matter: Matter,
+}
+
+impl Bexter {
+    /// Create Bexter from Base64 URL-safe text
+    pub fn new(bext: &str) -> Result<Self> {
+        if !bext
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Err(SignifyError::InvalidInput(format!(
+                "Invalid Base64 text: {}",
+                bext
+            )));
+        }
+        let matter = Matter::from_raw(&Self::rawify(bext)?, matter_codes::STR_B64_L0)?;
+        Ok(Self { matter })
+    }
+
+    /// Create Bexter from qb64
+    pub fn from_qb64(qb64: &str) -> Result<Self> {
+        Self::from_matter(Matter::from_qb64(qb64)?)
+    }
+
+    /// Create Bexter from qb2
+    pub fn from_qb2(qb2: &[u8]) -> Result<Self> {
+        Self::from_matter(Matter::from_qb2(qb2)?)
+    }
+
+    /// Wrap existing Matter, validating it carries a StrB64 code
+    pub fn from_matter(matter: Matter) -> Result<Self> {
+        if !matches!(
+            matter.code(),
+            matter_codes::STR_B64_L0
+                | matter_codes::STR_B64_L1
+                | matter_codes::STR_B64_L2
+                | matter_codes::STR_B64_BIG_L0
+                | matter_codes::STR_B64_BIG_L1
+                | matter_codes::STR_B64_BIG_L2
+        ) {
+            return Err(SignifyError::InvalidCode(format!(
+                "Invalid code {} for Bexter",
+                matter.code()
+            )));
+        }
+        Ok(Self { matter })
+    }
+
+    /// Convert Base64 text to raw bytes, dropping the zero wad and lead bytes
+    fn rawify(bext: &str) -> Result<Vec<u8>> {
+        let ts = bext.len() % 4;
+        let ws = (4 - ts) % 4;
+        let ls = (3 - ts) % 3;
+        let base = format!("{}{}", "A".repeat(ws), bext);
+        let raw = URL_SAFE_NO_PAD.decode(base)?;
+        Ok(raw[ls..].to_vec())
+    }
+
+    /// Get the Base64 text
+    ///
+    /// Text whose length is a multiple of 4 and that starts with 'A' loses
+    /// that leading 'A', as the zero char is indistinguishable from padding.
+    pub fn bext(&self) -> String {
+        let ls = sizage(self.matter.code()).map(|sz| sz.ls).unwrap_or(0);
+        let mut padded = vec![0u8; ls];
+        padded.extend_from_slice(self.matter.raw());
+        let bext = URL_SAFE_NO_PAD.encode(padded);
+        let ws = if ls == 0 {
+            usize::from(bext.starts_with('A'))
+        } else {
+            (ls + 1) % 4
+        };
+        bext[ws..].to_string()
+    }
+
+    /// Get qb64 encoding
+    pub fn qb64(&self) -> &str {
+        self.matter.qb64()
+    }
+
+    /// Get qb2 encoding
+    pub fn qb2(&self) -> &[u8] {
+        self.matter.qb2()
+    }
+
+    /// Get code
+    pub fn code(&self) -> &str {
+        self.matter.code()
+    }
+
+    /// Get underlying Matter
+    pub fn matter(&self) -> &Matter {
+        &self.matter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bexter_roundtrip() {
+        for bext in [
+            "",
+            "-",
+            "-A",
+            "-A-",
+            "-A-B",
+            "-a-b-c",
+            "ABCDEFGHIJ_0123456789",
+        ] {
+            let bexter = Bexter::new(bext).unwrap();
+            assert_eq!(bexter.bext(), bext);
+
+            let bexter2 = Bexter::from_qb64(bexter.qb64()).unwrap();
+            assert_eq!(bexter2.bext(), bext);
+
+            let bexter3 = Bexter::from_qb2(bexter.qb2()).unwrap();
+            assert_eq!(bexter3.bext(), bext);
+        }
+    }
+
+    #[test]
+    fn test_bexter_qb64_carries_text() {
+        let bexter = Bexter::new("-A-B").unwrap();
+        assert_eq!(bexter.code(), matter_codes::STR_B64_L0);
+        assert_eq!(bexter.qb64(), "4AAB-A-B");
+
+        let bexter = Bexter::new("-a").unwrap();
+        assert_eq!(bexter.code(), matter_codes::STR_B64_L1);
+        assert_eq!(bexter.qb64(), "5AABAA-a");
+    }
+
+    #[test]
+    fn test_bexter_invalid() {
+        assert!(Bexter::new("not base64!").is_err());
+        let texter = Matter::from_raw(b"abc", matter_codes::BYTES_L0).unwrap();
+        assert!(Bexter::from_qb64(texter.qb64()).is_err());
+    }
+}
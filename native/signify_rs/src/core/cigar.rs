@@ -6,6 +6,7 @@ use crate::core::{Matter, Verfer};
 use crate::error::Result;
 
 /// Non-indexed signature with optional verifier
+#[derive(Debug, Clone)]
 pub struct Cigar {
     matter: Matter,
     verfer: Option<Verfer>,
@@ -24,11 +25,22 @@ impl Cigar {
         Ok(Self { matter, verfer })
     }
 
+    /// Create Cigar from qb2 bytes
+    pub fn from_qb2(qb2: &[u8], verfer: Option<Verfer>) -> Result<Self> {
+        let matter = Matter::from_qb2(qb2)?;
+        Ok(Self { matter, verfer })
+    }
+
     /// Get qb64 encoding
     pub fn qb64(&self) -> &str {
         self.matter.qb64()
     }
 
+    /// Get qb2 encoding
+    pub fn qb2(&self) -> &[u8] {
+        self.matter.qb2()
+    }
+
     /// Get code
     pub fn code(&self) -> &str {
         self.matter.code()
@@ -28,6 +28,7 @@ pub mod matter_codes {
     pub const SHA3_256: &str = "H"; // SHA3 256 bit digest
     pub const SHA2_256: &str = "I"; // SHA2 256 bit digest
     pub const ECDSA_256K1_SEED: &str = "J"; // ECDSA secp256k1 seed
+    pub const ED448_SEED: &str = "K"; // Ed448 448 bit seed
     pub const X25519_PRIVATE: &str = "O"; // X25519 private key
     pub const X25519_CIPHER_SEED: &str = "P"; // X25519 cipher of seed
     pub const ECDSA_256R1_SEED: &str = "Q"; // ECDSA secp256r1 seed
@@ -46,6 +47,10 @@ pub mod matter_codes {
 
     pub const ECDSA_256K1N: &str = "1AAA"; // ECDSA secp256k1 non-transferable
     pub const ECDSA_256K1: &str = "1AAB"; // ECDSA secp256k1 transferable
+    pub const ED448N: &str = "1AAC"; // Ed448 non-transferable key
+    pub const ED448: &str = "1AAD"; // Ed448 transferable key
+    pub const ED448_SIG: &str = "1AAE"; // Ed448 signature
+    pub const DATE_TIME: &str = "1AAG"; // Base64 custom encoded ISO-8601 datetime
     pub const X25519_CIPHER_SALT: &str = "1AAH"; // X25519 cipher of salt
     pub const ECDSA_256R1N: &str = "1AAI"; // ECDSA secp256r1 non-transferable
     pub const ECDSA_256R1: &str = "1AAJ"; // ECDSA secp256r1 transferable
@@ -56,6 +61,44 @@ pub mod matter_codes {
     pub const STR_B64_BIG_L0: &str = "7AAA"; // String Base64 Big Lead 0
     pub const STR_B64_BIG_L1: &str = "8AAA"; // String Base64 Big Lead 1
     pub const STR_B64_BIG_L2: &str = "9AAA"; // String Base64 Big Lead 2
+    pub const BYTES_L0: &str = "4B"; // Byte String Lead 0
+    pub const BYTES_L1: &str = "5B"; // Byte String Lead 1
+    pub const BYTES_L2: &str = "6B"; // Byte String Lead 2
+    pub const BYTES_BIG_L0: &str = "7AAB"; // Byte String Big Lead 0
+    pub const BYTES_BIG_L1: &str = "8AAB"; // Byte String Big Lead 1
+    pub const BYTES_BIG_L2: &str = "9AAB"; // Byte String Big Lead 2
+}
+
+/// Selector characters of small variable size codes, indexed by lead size
+pub const SMALL_VRZ_DEX: [char; 3] = ['4', '5', '6'];
+
+/// Selector characters of large variable size codes, indexed by lead size
+pub const LARGE_VRZ_DEX: [char; 3] = ['7', '8', '9'];
+
+/// Check if code belongs to a variable size family (small or large)
+pub fn is_variable(code: &str) -> bool {
+    code.chars()
+        .next()
+        .map(|c| SMALL_VRZ_DEX.contains(&c) || LARGE_VRZ_DEX.contains(&c))
+        .unwrap_or(false)
+}
+
+/// Digest codes usable for self-addressing identifiers and SAIDs
+pub const DIGEST_CODES: [&str; 9] = [
+    matter_codes::BLAKE3_256,
+    matter_codes::BLAKE2B_256,
+    matter_codes::BLAKE2S_256,
+    matter_codes::SHA3_256,
+    matter_codes::SHA2_256,
+    matter_codes::BLAKE3_512,
+    matter_codes::BLAKE2B_512,
+    matter_codes::SHA3_512,
+    matter_codes::SHA2_512,
+];
+
+/// Check if code is a digest (self-addressing) code
+pub fn is_digest(code: &str) -> bool {
+    DIGEST_CODES.contains(&code)
 }
 
 /// Indexer code definitions
@@ -64,24 +107,38 @@ pub mod indexer_codes {
     pub const ED25519_CRT_SIG: &str = "B"; // Ed25519 current only indexed signature
     pub const ECDSA_256K1_SIG: &str = "C"; // ECDSA secp256k1 indexed signature
     pub const ECDSA_256K1_CRT_SIG: &str = "D"; // ECDSA secp256k1 current only
-    pub const ED25519_BIG_SIG: &str = "0A"; // Ed25519 big indexed signature
-    pub const ED25519_BIG_CRT_SIG: &str = "0B"; // Ed25519 big current only
-    pub const ECDSA_256R1_SIG: &str = "0C"; // ECDSA secp256r1 indexed signature
-    pub const ECDSA_256R1_CRT_SIG: &str = "0D"; // ECDSA secp256r1 current only
+    pub const ECDSA_256R1_SIG: &str = "E"; // ECDSA secp256r1 indexed signature
+    pub const ECDSA_256R1_CRT_SIG: &str = "F"; // ECDSA secp256r1 current only
+    pub const ED448_SIG: &str = "0A"; // Ed448 indexed signature
+    pub const ED448_CRT_SIG: &str = "0B"; // Ed448 current only
+    pub const ED25519_BIG_SIG: &str = "2A"; // Ed25519 big indexed signature
+    pub const ED25519_BIG_CRT_SIG: &str = "2B"; // Ed25519 big current only
+    pub const ECDSA_256K1_BIG_SIG: &str = "2C"; // ECDSA secp256k1 big indexed signature
+    pub const ECDSA_256K1_BIG_CRT_SIG: &str = "2D"; // ECDSA secp256k1 big current only
+    pub const ECDSA_256R1_BIG_SIG: &str = "2E"; // ECDSA secp256r1 big indexed signature
+    pub const ECDSA_256R1_BIG_CRT_SIG: &str = "2F"; // ECDSA secp256r1 big current only
+    pub const ED448_BIG_SIG: &str = "3A"; // Ed448 big indexed signature
+    pub const ED448_BIG_CRT_SIG: &str = "3B"; // Ed448 big current only
 }
 
-/// Counter code definitions
+/// CESR 1.0 counter code definitions
+///
+/// Counter codes are versioned and live in `core::counter`. The `-G` to `-J`
+/// names here predate the CESR 1.0 table and keep their old values.
+#[deprecated(note = "use CounterCodex or CounterCodexV2 from core::counter")]
 pub mod counter_codes {
-    pub const CONTROLLER_IDX_SIGS: &str = "-A"; // Controller indexed signatures
-    pub const WITNESS_IDX_SIGS: &str = "-B"; // Witness indexed signatures
-    pub const NON_TRANS_RCT: &str = "-C"; // Non-transferable receipt
-    pub const TRANS_RCT: &str = "-D"; // Transferable receipt
-    pub const FIRST_SEEN_RPY: &str = "-E"; // First seen reply
-    pub const TRANS_IDX_SIG_GROUPS: &str = "-F"; // Transferable indexed sig groups
-    pub const ESCROW_RPY: &str = "-G"; // Escrowed reply
-    pub const LOCATION_SEAL: &str = "-H"; // Location seal
-    pub const ANCHOR_SEAL: &str = "-I"; // Anchor seal
-    pub const SOURCE_SEAL: &str = "-J"; // Source seal
+    use crate::core::counter::CounterCodex;
+
+    pub const CONTROLLER_IDX_SIGS: &str = CounterCodex::CONTROLLER_IDX_SIGS;
+    pub const WITNESS_IDX_SIGS: &str = CounterCodex::WITNESS_IDX_SIGS;
+    pub const NON_TRANS_RCT: &str = CounterCodex::NON_TRANS_RCT;
+    pub const TRANS_RCT: &str = CounterCodex::TRANS_RCT;
+    pub const FIRST_SEEN_RPY: &str = CounterCodex::FIRST_SEEN_RPY;
+    pub const TRANS_IDX_SIG_GROUPS: &str = CounterCodex::TRANS_IDX_SIG_GROUPS;
+    pub const ESCROW_RPY: &str = "-G"; // CounterCodex::SEAL_SOURCE_COUPLES
+    pub const LOCATION_SEAL: &str = "-H"; // CounterCodex::TRANS_LAST_IDX_SIG_GROUPS
+    pub const ANCHOR_SEAL: &str = "-I"; // CounterCodex::SEAL_SOURCE_TRIPLES
+    pub const SOURCE_SEAL: &str = "-J"; // CounterCodex::SAD_PATH_SIG
 }
 
 /// Size table for CESR codes
@@ -134,7 +191,7 @@ pub static SIZES: Lazy<HashMap<&'static str, Sizage>> = Lazy::new(|| {
     m.insert("1AAB", Sizage::new(4, 0, Some(48), 0));
     m.insert("1AAC", Sizage::new(4, 0, Some(80), 0));
     m.insert("1AAD", Sizage::new(4, 0, Some(80), 0));
-    m.insert("1AAE", Sizage::new(4, 0, Some(56), 0));
+    m.insert("1AAE", Sizage::new(4, 0, Some(156), 0));
     m.insert("1AAF", Sizage::new(4, 0, Some(8), 0));
     m.insert("1AAG", Sizage::new(4, 0, Some(36), 0));
     m.insert("1AAH", Sizage::new(4, 0, Some(100), 0));
@@ -148,6 +205,12 @@ pub static SIZES: Lazy<HashMap<&'static str, Sizage>> = Lazy::new(|| {
     m.insert("7AAA", Sizage::new(4, 4, None, 0));
     m.insert("8AAA", Sizage::new(4, 4, None, 1));
     m.insert("9AAA", Sizage::new(4, 4, None, 2));
+    m.insert("4B", Sizage::new(2, 2, None, 0));
+    m.insert("5B", Sizage::new(2, 2, None, 1));
+    m.insert("6B", Sizage::new(2, 2, None, 2));
+    m.insert("7AAB", Sizage::new(4, 4, None, 0));
+    m.insert("8AAB", Sizage::new(4, 4, None, 1));
+    m.insert("9AAB", Sizage::new(4, 4, None, 2));
 
     m
 });
@@ -215,9 +278,9 @@ pub fn raw_size(code: &str) -> crate::error::Result<usize> {
     let sz = sizage(code)?;
     if let Some(fs) = sz.fs {
         // Fixed size: calculate raw from qb64 size
-        // qb64_size = hs + ceil((raw_size + ls) * 4 / 3)
+        // qb64_size = hs + ss + ceil((raw_size + ls) * 4 / 3)
         // Solve for raw_size:
-        let qb64_data_size = fs - sz.hs;
+        let qb64_data_size = fs - (sz.hs + sz.ss);
         let raw_with_pad = (qb64_data_size * 3) / 4;
         Ok(raw_with_pad - sz.ls)
     } else {
@@ -247,5 +310,34 @@ mod tests {
         assert_eq!(raw_size("A").unwrap(), 32); // Ed25519 seed
         assert_eq!(raw_size("0B").unwrap(), 64); // Ed25519 signature
         assert_eq!(raw_size("0A").unwrap(), 16); // Salt_128
+        assert!(raw_size(matter_codes::STR_B64_L0).is_err());
+    }
+
+    #[test]
+    fn test_variable_sizes() {
+        assert!(is_variable(matter_codes::STR_B64_L1));
+        assert!(is_variable(matter_codes::BYTES_BIG_L2));
+        assert!(!is_variable(matter_codes::ED25519));
+
+        for (ls, code) in [
+            matter_codes::BYTES_L0,
+            matter_codes::BYTES_L1,
+            matter_codes::BYTES_L2,
+        ]
+        .iter()
+        .enumerate()
+        {
+            let sz = sizage(code).unwrap();
+            assert_eq!((sz.hs, sz.ss, sz.fs, sz.ls), (2, 2, None, ls));
+        }
+        assert_eq!(extract_code("7AABAAAB").unwrap(), "7AAB");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_counter_codes() {
+        assert_eq!(counter_codes::CONTROLLER_IDX_SIGS, "-A");
+        assert_eq!(counter_codes::TRANS_IDX_SIG_GROUPS, "-F");
+        assert_eq!(counter_codes::SOURCE_SEAL, "-J");
     }
 }
@@ -2,11 +2,13 @@
 ///
 /// Counter provides count encoding for grouped attachments in KERI events.
 /// Used to specify how many items follow in a particular attachment group.
+use crate::core::utils::{b2_to_b64, Version, VRSN_1_0};
 use crate::error::{Result, SignifyError};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
-/// Counter codes for different attachment types
+/// Counter codes for CESR 1.0 attachment groups
 pub struct CounterCodex;
 
 impl CounterCodex {
@@ -22,14 +24,93 @@ impl CounterCodex {
     pub const SAD_PATH_SIG: &'static str = "-J"; // SAD path signature
     pub const SAD_PATH_SIG_GROUP: &'static str = "-K"; // SAD path signature group
     pub const PATHED_MATERIAL_QUADLETS: &'static str = "-L"; // Pathed material quadlets
+    pub const BIG_PATHED_MATERIAL_QUADLETS: &'static str = "-0L"; // Big pathed quadlets
     pub const ATTACHED_MATERIAL_QUADLETS: &'static str = "-V"; // Attached material quadlets
     pub const BIG_ATTACHED_MATERIAL_QUADLETS: &'static str = "-0V"; // Big attached quadlets
     pub const KERI_PROTOCOL_STACK: &'static str = "--AAA"; // KERI protocol stack version
+    pub const KERI_ACDC_GENUS_VERSION: &'static str = "-_AAA"; // KERI/ACDC genus version
 
-    /// Check if code is valid
+    /// Check if code is valid in CESR 1.0
     pub fn is_valid(code: &str) -> bool {
         COUNTER_SIZES.contains_key(code)
     }
+
+    /// Check if code is valid for the given CESR protocol version
+    pub fn is_valid_for(code: &str, version: &Version) -> bool {
+        counter_sizes(version)
+            .map(|sizes| sizes.contains_key(code))
+            .unwrap_or(false)
+    }
+}
+
+/// Counter codes for CESR 2.0 attachment groups
+///
+/// CESR 2.0 reassigns the two character codes, so a code such as "-A" means
+/// a controller signature group in 1.0 but a generic group in 2.0. Every
+/// small code has a big "-0x" counterpart with a five character count.
+pub struct CounterCodexV2;
+
+impl CounterCodexV2 {
+    pub const GENERIC_GROUP: &'static str = "-A"; // Generic group
+    pub const BIG_GENERIC_GROUP: &'static str = "-0A";
+    pub const BODY_WITH_ATTACHMENT_GROUP: &'static str = "-B"; // Message body plus attachments
+    pub const BIG_BODY_WITH_ATTACHMENT_GROUP: &'static str = "-0B";
+    pub const ATTACHMENT_GROUP: &'static str = "-C"; // Message attachments only
+    pub const BIG_ATTACHMENT_GROUP: &'static str = "-0C";
+    pub const DATAGRAM_SEGMENT_GROUP: &'static str = "-D"; // Datagram segment
+    pub const BIG_DATAGRAM_SEGMENT_GROUP: &'static str = "-0D";
+    pub const ESSR_WRAPPER_GROUP: &'static str = "-E"; // ESSR wrapper
+    pub const BIG_ESSR_WRAPPER_GROUP: &'static str = "-0E";
+    pub const FIX_BODY_GROUP: &'static str = "-F"; // Fixed field message body
+    pub const BIG_FIX_BODY_GROUP: &'static str = "-0F";
+    pub const MAP_BODY_GROUP: &'static str = "-G"; // Field map message body
+    pub const BIG_MAP_BODY_GROUP: &'static str = "-0G";
+    pub const NON_NATIVE_BODY_GROUP: &'static str = "-H"; // Non-native body in a Texter
+    pub const BIG_NON_NATIVE_BODY_GROUP: &'static str = "-0H";
+    pub const GENERIC_MAP_GROUP: &'static str = "-I"; // Generic field map
+    pub const BIG_GENERIC_MAP_GROUP: &'static str = "-0I";
+    pub const GENERIC_LIST_GROUP: &'static str = "-J"; // Generic list
+    pub const BIG_GENERIC_LIST_GROUP: &'static str = "-0J";
+    pub const CONTROLLER_IDX_SIGS: &'static str = "-K"; // Controller indexed signatures
+    pub const BIG_CONTROLLER_IDX_SIGS: &'static str = "-0K";
+    pub const WITNESS_IDX_SIGS: &'static str = "-L"; // Witness indexed signatures
+    pub const BIG_WITNESS_IDX_SIGS: &'static str = "-0L";
+    pub const NON_TRANS_RCT: &'static str = "-M"; // Non-transferable receipt couples
+    pub const BIG_NON_TRANS_RCT: &'static str = "-0M";
+    pub const TRANS_RCT: &'static str = "-N"; // Transferable receipt quadruples
+    pub const BIG_TRANS_RCT: &'static str = "-0N";
+    pub const FIRST_SEEN_RPY: &'static str = "-O"; // First seen replay couples
+    pub const BIG_FIRST_SEEN_RPY: &'static str = "-0O";
+    pub const TRANS_IDX_SIG_GROUPS: &'static str = "-P"; // Transferable indexed sig groups
+    pub const BIG_TRANS_IDX_SIG_GROUPS: &'static str = "-0P";
+    pub const TRANS_LAST_IDX_SIG_GROUPS: &'static str = "-Q"; // Trans last indexed sig groups
+    pub const BIG_TRANS_LAST_IDX_SIG_GROUPS: &'static str = "-0Q";
+    pub const SEAL_SOURCE_COUPLES: &'static str = "-R"; // Seal source couples
+    pub const BIG_SEAL_SOURCE_COUPLES: &'static str = "-0R";
+    pub const SEAL_SOURCE_TRIPLES: &'static str = "-S"; // Seal source triples
+    pub const BIG_SEAL_SOURCE_TRIPLES: &'static str = "-0S";
+    pub const PATHED_MATERIAL_COUPLES: &'static str = "-T"; // Pathed material couples
+    pub const BIG_PATHED_MATERIAL_COUPLES: &'static str = "-0T";
+    pub const SAD_PATH_SIG_GROUPS: &'static str = "-U"; // SAD path signature groups
+    pub const BIG_SAD_PATH_SIG_GROUPS: &'static str = "-0U";
+    pub const ROOT_SAD_PATH_SIG_GROUPS: &'static str = "-V"; // Root SAD path sig groups
+    pub const BIG_ROOT_SAD_PATH_SIG_GROUPS: &'static str = "-0V";
+    pub const DIGEST_SEAL_SINGLES: &'static str = "-W"; // Digest seals
+    pub const BIG_DIGEST_SEAL_SINGLES: &'static str = "-0W";
+    pub const MERKLE_ROOT_SEAL_SINGLES: &'static str = "-X"; // Merkle root seals
+    pub const BIG_MERKLE_ROOT_SEAL_SINGLES: &'static str = "-0X";
+    pub const BACKER_REGISTRAR_SEAL_COUPLES: &'static str = "-Y"; // Backer registrar seals
+    pub const BIG_BACKER_REGISTRAR_SEAL_COUPLES: &'static str = "-0Y";
+    pub const SEAL_SOURCE_LAST_SINGLES: &'static str = "-Z"; // Seal source last singles
+    pub const BIG_SEAL_SOURCE_LAST_SINGLES: &'static str = "-0Z";
+    pub const ESSR_PAYLOAD_GROUP: &'static str = "-a"; // ESSR payload group
+    pub const BIG_ESSR_PAYLOAD_GROUP: &'static str = "-0a";
+    pub const KERI_ACDC_GENUS_VERSION: &'static str = "-_AAA"; // KERI/ACDC genus version
+
+    /// Check if code is valid in CESR 2.0
+    pub fn is_valid(code: &str) -> bool {
+        COUNTER_SIZES_2_0.contains_key(code)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -39,160 +120,115 @@ pub struct CounterSizage {
     pub fs: usize, // Full size (total length)
 }
 
-/// Size table for counter codes
+/// Small counters: 2 char code + 2 char count
+const SMALL: CounterSizage = CounterSizage {
+    hs: 2,
+    ss: 2,
+    fs: 4,
+};
+
+/// Big counters: 3 char code + 5 char count
+const BIG: CounterSizage = CounterSizage {
+    hs: 3,
+    ss: 5,
+    fs: 8,
+};
+
+/// Genus version counters: 5 char code + 3 char version
+const GENUS: CounterSizage = CounterSizage {
+    hs: 5,
+    ss: 3,
+    fs: 8,
+};
+
+/// Size table for CESR 1.0 counter codes
 static COUNTER_SIZES: Lazy<HashMap<&'static str, CounterSizage>> = Lazy::new(|| {
     let mut m = HashMap::new();
 
-    // Standard 2-char codes with 2-char count (fs=4)
-    m.insert(
-        "-A",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-B",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-C",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-D",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-E",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-F",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-G",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-H",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-I",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-J",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-K",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-L",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-    m.insert(
-        "-V",
-        CounterSizage {
-            hs: 2,
-            ss: 2,
-            fs: 4,
-        },
-    );
-
-    // Big counter (3-char code + 5-char count = 8)
-    m.insert(
-        "-0V",
-        CounterSizage {
-            hs: 3,
-            ss: 5,
-            fs: 8,
-        },
-    );
-
-    // Protocol stack version (5-char code + 3-char version = 8)
-    m.insert(
-        "--AAA",
-        CounterSizage {
-            hs: 5,
-            ss: 3,
-            fs: 8,
-        },
-    );
+    for code in [
+        "-A", "-B", "-C", "-D", "-E", "-F", "-G", "-H", "-I", "-J", "-K", "-L", "-V",
+    ] {
+        m.insert(code, SMALL);
+    }
+    for code in ["-0L", "-0V"] {
+        m.insert(code, BIG);
+    }
+    m.insert("--AAA", GENUS);
+    m.insert("-_AAA", GENUS);
 
     m
 });
 
-fn counter_sizage(code: &str) -> Option<&CounterSizage> {
-    COUNTER_SIZES.get(code)
+/// Size table for CESR 2.0 counter codes
+static COUNTER_SIZES_2_0: Lazy<HashMap<&'static str, CounterSizage>> = Lazy::new(|| {
+    let mut m = HashMap::new();
+
+    for code in [
+        "-A", "-B", "-C", "-D", "-E", "-F", "-G", "-H", "-I", "-J", "-K", "-L", "-M", "-N", "-O",
+        "-P", "-Q", "-R", "-S", "-T", "-U", "-V", "-W", "-X", "-Y", "-Z", "-a",
+    ] {
+        m.insert(code, SMALL);
+    }
+    for code in [
+        "-0A", "-0B", "-0C", "-0D", "-0E", "-0F", "-0G", "-0H", "-0I", "-0J", "-0K", "-0L", "-0M",
+        "-0N", "-0O", "-0P", "-0Q", "-0R", "-0S", "-0T", "-0U", "-0V", "-0W", "-0X", "-0Y", "-0Z",
+        "-0a",
+    ] {
+        m.insert(code, BIG);
+    }
+    m.insert("-_AAA", GENUS);
+
+    m
+});
+
+/// Select the counter size table for a CESR protocol version
+pub fn counter_sizes(version: &Version) -> Result<&'static HashMap<&'static str, CounterSizage>> {
+    match version.major {
+        1 => Ok(&COUNTER_SIZES),
+        2 => Ok(&COUNTER_SIZES_2_0),
+        _ => Err(SignifyError::InvalidArgument(format!(
+            "Unsupported CESR version {}.{}",
+            version.major, version.minor
+        ))),
+    }
+}
+
+/// Hard size of a counter code from its leading chars
+pub fn hard_size(lead: &str) -> usize {
+    if lead.starts_with("--") || lead.starts_with("-_") {
+        5
+    } else if lead.starts_with("-0") {
+        3
+    } else {
+        2
+    }
 }
 
 /// Counter for attachment groups
+///
+/// The code table is selected by the CESR protocol version the counter
+/// belongs to, 1.0 unless given otherwise.
+#[derive(Debug, Clone)]
 pub struct Counter {
     code: String,
     count: u32,
+    version: Version,
 }
 
 impl Counter {
-    /// Create new Counter with code and count
+    /// Create new CESR 1.0 Counter with code and count
     pub fn new(code: &str, count: u32) -> Result<Self> {
-        if !CounterCodex::is_valid(code) {
-            return Err(SignifyError::InvalidCode(format!(
-                "Invalid counter code: {}",
-                code
-            )));
-        }
+        Self::new_with_version(code, count, VRSN_1_0)
+    }
 
-        let sizage = counter_sizage(code).unwrap();
+    /// Create new Counter with code and count from the given version's table
+    pub fn new_with_version(code: &str, count: u32, version: Version) -> Result<Self> {
+        let sizage = counter_sizes(&version)?.get(code).ok_or_else(|| {
+            SignifyError::InvalidCode(format!(
+                "Invalid counter code {} for CESR {}.{}",
+                code, version.major, version.minor
+            ))
+        })?;
 
         // Validate count range
         let max_count = 64_u32.pow(sizage.ss as u32) - 1;
@@ -206,55 +242,76 @@ impl Counter {
         Ok(Self {
             code: code.to_string(),
             count,
+            version,
+        })
+    }
+
+    /// Create a genus version counter announcing the protocol version of
+    /// the stream that follows
+    ///
+    /// The version is encoded as one char major and two chars minor.
+    pub fn genus_version(version: Version) -> Result<Self> {
+        let count = ((version.major as u32) << 12) | version.minor as u32;
+        Self::new_with_version(CounterCodex::KERI_ACDC_GENUS_VERSION, count, version)
+    }
+
+    /// Protocol version announced by a genus version counter
+    pub fn gvrsn(&self) -> Result<Version> {
+        if self.code != CounterCodex::KERI_ACDC_GENUS_VERSION {
+            return Err(SignifyError::InvalidCode(format!(
+                "Counter {} is not a genus version counter",
+                self.code
+            )));
+        }
+
+        let part = |value: u32| {
+            u8::try_from(value).map_err(|_| {
+                SignifyError::InvalidCesr(format!(
+                    "Unsupported genus version {}.{}",
+                    self.count >> 12,
+                    self.count & 0xfff
+                ))
+            })
+        };
+        Ok(Version {
+            major: part(self.count >> 12)?,
+            minor: part(self.count & 0xfff)?,
         })
     }
 
-    /// Create Counter from qb64 string
+    /// Create CESR 1.0 Counter from qb64 string
     pub fn from_qb64(qb64: &str) -> Result<Self> {
+        Self::from_qb64_with_version(qb64, VRSN_1_0)
+    }
+
+    /// Create Counter from qb64 string using the given version's table
+    ///
+    /// Only the counter's full size is read so any trailing stream content
+    /// is ignored.
+    pub fn from_qb64_with_version(qb64: &str, version: Version) -> Result<Self> {
         if qb64.is_empty() {
             return Err(SignifyError::InvalidFormat("Empty qb64 string".to_string()));
         }
 
         // Extract code - counters start with '-'
-        let code = if qb64.starts_with("--") {
-            // 5-char code
-            if qb64.len() < 5 {
-                return Err(SignifyError::InvalidFormat(
-                    "qb64 too short for -- code".to_string(),
-                ));
-            }
-            &qb64[0..5]
-        } else if qb64.starts_with("-0") {
-            // 3-char code
-            if qb64.len() < 3 {
-                return Err(SignifyError::InvalidFormat(
-                    "qb64 too short for -0 code".to_string(),
-                ));
-            }
-            &qb64[0..3]
-        } else if qb64.starts_with('-') {
-            // 2-char code
-            if qb64.len() < 2 {
-                return Err(SignifyError::InvalidFormat(
-                    "qb64 too short for - code".to_string(),
-                ));
-            }
-            &qb64[0..2]
-        } else {
+        if !qb64.starts_with('-') {
             return Err(SignifyError::InvalidCode(format!(
                 "Invalid counter code prefix in: {}",
                 qb64
             )));
-        };
-
-        if !CounterCodex::is_valid(code) {
-            return Err(SignifyError::InvalidCode(format!(
-                "Unknown counter code: {}",
-                code
-            )));
         }
 
-        let sizage = counter_sizage(code).unwrap();
+        let hs = hard_size(qb64);
+        let code = qb64.get(0..hs).ok_or_else(|| {
+            SignifyError::InvalidFormat(format!("qb64 too short for {} char code", hs))
+        })?;
+
+        let sizage = counter_sizes(&version)?.get(code).ok_or_else(|| {
+            SignifyError::InvalidCode(format!(
+                "Unknown counter code {} for CESR {}.{}",
+                code, version.major, version.minor
+            ))
+        })?;
 
         if qb64.len() < sizage.fs {
             return Err(SignifyError::InvalidFormat(format!(
@@ -271,16 +328,59 @@ impl Counter {
         Ok(Self {
             code: code.to_string(),
             count,
+            version,
         })
     }
 
+    /// Create CESR 1.0 Counter from qb2 bytes
+    pub fn from_qb2(qb2: &[u8]) -> Result<Self> {
+        Self::from_qb2_with_version(qb2, VRSN_1_0)
+    }
+
+    /// Create Counter from qb2 bytes using the given version's table
+    ///
+    /// Like `from_qb64_with_version`, only the counter's full size is read so
+    /// any trailing stream content is ignored.
+    pub fn from_qb2_with_version(qb2: &[u8], version: Version) -> Result<Self> {
+        if qb2.is_empty() {
+            return Err(SignifyError::InvalidFormat("Empty qb2 bytes".to_string()));
+        }
+
+        // First two sextets select the code length
+        let hs = hard_size(&b2_to_b64(qb2, 2));
+        let code = b2_to_b64(qb2, hs);
+
+        let sizage = counter_sizes(&version)?.get(code.as_str()).ok_or_else(|| {
+            SignifyError::InvalidCode(format!("Unknown counter code in qb2: {}", code))
+        })?;
+
+        // Counters are whole quadlets, so 3 bytes per 4 chars
+        let bfs = sizage.fs * 3 / 4;
+        if qb2.len() < bfs {
+            return Err(SignifyError::InvalidFormat(format!(
+                "qb2 too short: {} < {}",
+                qb2.len(),
+                bfs
+            )));
+        }
+
+        Self::from_qb64_with_version(&URL_SAFE_NO_PAD.encode(&qb2[..bfs]), version)
+    }
+
     /// Get qb64 encoding
     pub fn qb64(&self) -> String {
-        let sizage = counter_sizage(&self.code).unwrap();
+        let sizage = &counter_sizes(&self.version).unwrap()[self.code.as_str()];
         let count_b64 = int_to_b64(self.count as usize, sizage.ss);
         format!("{}{}", self.code, count_b64)
     }
 
+    /// Get qb2 encoding
+    pub fn qb2(&self) -> Vec<u8> {
+        URL_SAFE_NO_PAD
+            .decode(self.qb64())
+            .expect("qb64 of a Counter is always valid base64")
+    }
+
     /// Get code
     pub fn code(&self) -> &str {
         &self.code
@@ -290,6 +390,11 @@ impl Counter {
     pub fn count(&self) -> u32 {
         self.count
     }
+
+    /// Get the CESR protocol version whose table the code belongs to
+    pub fn version(&self) -> Version {
+        self.version
+    }
 }
 
 /// Convert base64url string to integer
@@ -335,6 +440,7 @@ fn int_to_b64(mut n: usize, len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::utils::VRSN_2_0;
 
     #[test]
     fn test_counter_basic() {
@@ -389,6 +495,118 @@ mod tests {
         assert_eq!(qb64.len(), 8);
     }
 
+    #[test]
+    fn test_counter_qb2_all_codes() {
+        for (version, sizes) in [(VRSN_1_0, &*COUNTER_SIZES), (VRSN_2_0, &*COUNTER_SIZES_2_0)] {
+            for (code, sizage) in sizes.iter() {
+                let count = 64_u32.pow(sizage.ss as u32) - 1;
+                for count in [0, 1, count] {
+                    let counter = Counter::new_with_version(code, count, version).unwrap();
+
+                    let qb64 = counter.qb64();
+                    let qb2 = counter.qb2();
+                    assert_eq!(qb64.len(), sizage.fs, "code {}", code);
+                    assert_eq!(qb2.len(), sizage.fs * 3 / 4, "code {}", code);
+                    assert_eq!(qb2, URL_SAFE_NO_PAD.decode(&qb64).unwrap(), "code {}", code);
+
+                    let from_qb2 = Counter::from_qb2_with_version(&qb2, version).unwrap();
+                    assert_eq!(from_qb2.code(), *code);
+                    assert_eq!(from_qb2.count(), count);
+                    assert_eq!(from_qb2.qb64(), qb64);
+
+                    let from_qb64 = Counter::from_qb64_with_version(&qb64, version).unwrap();
+                    assert_eq!(from_qb64.qb2(), qb2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_counter_versioned_tables() {
+        // Same code means different groups per version
+        assert_eq!(
+            CounterCodex::SEAL_SOURCE_COUPLES,
+            CounterCodexV2::MAP_BODY_GROUP
+        );
+        assert_eq!(
+            CounterCodex::CONTROLLER_IDX_SIGS,
+            CounterCodexV2::GENERIC_GROUP
+        );
+
+        // Codes only in 2.0 are rejected by the 1.0 table and vice versa
+        assert!(Counter::new(CounterCodexV2::TRANS_RCT, 1).is_err()); // "-N" unknown in 1.0
+        assert!(!CounterCodex::is_valid(CounterCodexV2::TRANS_RCT));
+        assert!(CounterCodexV2::is_valid(
+            CounterCodexV2::BIG_CONTROLLER_IDX_SIGS
+        ));
+        assert!(!CounterCodex::is_valid(
+            CounterCodexV2::BIG_CONTROLLER_IDX_SIGS
+        ));
+        assert!(!CounterCodexV2::is_valid(CounterCodex::KERI_PROTOCOL_STACK));
+        assert!(CounterCodex::is_valid_for("-0K", &VRSN_2_0));
+        assert!(!CounterCodex::is_valid_for("-0K", &VRSN_1_0));
+
+        // Big 2.0 counters parse with a 5 char count
+        let qb64 = "-0KAAAAB";
+        let counter = Counter::from_qb64_with_version(qb64, VRSN_2_0).unwrap();
+        assert_eq!(counter.code(), CounterCodexV2::BIG_CONTROLLER_IDX_SIGS);
+        assert_eq!(counter.count(), 1);
+        assert_eq!(counter.version(), VRSN_2_0);
+        assert!(Counter::from_qb64(qb64).is_err());
+
+        // Unsupported versions have no table
+        let v3 = Version { major: 3, minor: 0 };
+        assert!(Counter::new_with_version("-A", 1, v3).is_err());
+    }
+
+    #[test]
+    fn test_counter_genus_version() {
+        let counter = Counter::genus_version(VRSN_2_0).unwrap();
+        assert_eq!(counter.qb64(), "-_AAACAA");
+        assert_eq!(counter.gvrsn().unwrap(), VRSN_2_0);
+
+        let counter = Counter::genus_version(VRSN_1_0).unwrap();
+        assert_eq!(counter.qb64(), "-_AAABAA");
+
+        // Genus version counters parse under either table
+        for version in [VRSN_1_0, VRSN_2_0] {
+            let counter = Counter::from_qb64_with_version("-_AAACAA", version).unwrap();
+            assert_eq!(counter.gvrsn().unwrap(), VRSN_2_0);
+            let counter = Counter::from_qb2_with_version(&counter.qb2(), version).unwrap();
+            assert_eq!(counter.gvrsn().unwrap(), VRSN_2_0);
+        }
+
+        // Minor versions beyond a byte are rejected rather than truncated
+        let counter = Counter::from_qb64("-_AAACD_").unwrap();
+        assert_eq!(
+            counter.gvrsn().unwrap(),
+            Version {
+                major: 2,
+                minor: 255
+            }
+        );
+        let counter = Counter::from_qb64("-_AAACEA").unwrap();
+        assert!(matches!(counter.gvrsn(), Err(SignifyError::InvalidCesr(_))));
+
+        let counter = Counter::new(CounterCodex::CONTROLLER_IDX_SIGS, 1).unwrap();
+        assert!(counter.gvrsn().is_err());
+    }
+
+    #[test]
+    fn test_counter_qb2_stream_and_truncated() {
+        let counter = Counter::new(CounterCodex::CONTROLLER_IDX_SIGS, 3).unwrap();
+        assert_eq!(counter.qb2(), vec![0xf8, 0x00, 0x03]);
+
+        // Trailing stream content is ignored
+        let mut stream = counter.qb2();
+        stream.extend_from_slice(&[0xff; 10]);
+        let parsed = Counter::from_qb2(&stream).unwrap();
+        assert_eq!(parsed.qb64(), "-AAD");
+
+        assert!(Counter::from_qb2(&[0xf8, 0x00]).is_err());
+        assert!(Counter::from_qb2(&[]).is_err());
+    }
+
     #[test]
     fn test_counter_invalid_code() {
         let result = Counter::new("-Z", 5);
@@ -0,0 +1,150 @@
+/// Dater - ISO-8601 datetime encoding for KERI attachments
+///
+/// Dater provides fully qualified format for datetimes such as first seen
+/// timestamps when provided as attached cryptographic material. The datetime
+/// string itself is the Base64 text with ':', '.' and '+' replaced by 'c',
+/// 'd' and 'p' so that it is valid Base64.
+use crate::core::matter::Matter;
+use crate::core::matter_codes;
+use crate::error::{Result, SignifyError};
+
+/// Length of an ISO-8601 datetime with microseconds and UTC offset
+const DTS_LEN: usize = 32;
+
+/// Dater for datetime encoding
+///
+/// Uses Matter code "1AAG" for 32 char ISO-8601 datetimes such as
+/// `2020-08-22T17:50:09.988921+00:00`.
+#[derive(Debug, Clone)]
+pub struct Dater {
+    matter: Matter,
+}
+
+impl Dater {
+    /// Create new Dater from ISO-8601 datetime string
+    pub fn new(dts: &str) -> Result<Self> {
+        if dts.len() != DTS_LEN {
+            return Err(SignifyError::InvalidSize {
+                expected: DTS_LEN,
+                actual: dts.len(),
+            });
+        }
+
+        let text: String = dts
+            .chars()
+            .map(|c| match c {
+                ':' => 'c',
+                '.' => 'd',
+                '+' => 'p',
+                c => c,
+            })
+            .collect();
+
+        Self::from_qb64(&format!("{}{}", matter_codes::DATE_TIME, text))
+    }
+
+    /// Create Dater for the current time in UTC
+    pub fn now() -> Result<Self> {
+        Self::new(&crate::core::utils::now_iso8601())
+    }
+
+    /// Create Dater from qb64 string
+    pub fn from_qb64(qb64: &str) -> Result<Self> {
+        let matter = Matter::from_qb64(qb64).map_err(|e| {
+            SignifyError::InvalidFormat(format!("Invalid datetime {}: {}", qb64, e))
+        })?;
+        Self::from_matter(matter)
+    }
+
+    /// Create Dater from qb2 bytes
+    pub fn from_qb2(qb2: &[u8]) -> Result<Self> {
+        let matter = Matter::from_qb2(qb2)?;
+        Self::from_matter(matter)
+    }
+
+    fn from_matter(matter: Matter) -> Result<Self> {
+        // Validate code
+        if matter.code() != matter_codes::DATE_TIME {
+            return Err(SignifyError::InvalidCode(format!(
+                "Invalid code {} for Dater, expected {}",
+                matter.code(),
+                matter_codes::DATE_TIME
+            )));
+        }
+
+        Ok(Self { matter })
+    }
+
+    /// Get ISO-8601 datetime string
+    pub fn dts(&self) -> String {
+        self.matter.qb64()[matter_codes::DATE_TIME.len()..]
+            .chars()
+            .map(|c| match c {
+                'c' => ':',
+                'd' => '.',
+                'p' => '+',
+                c => c,
+            })
+            .collect()
+    }
+
+    /// Get qb64 encoding
+    pub fn qb64(&self) -> &str {
+        self.matter.qb64()
+    }
+
+    /// Get qb2 encoding
+    pub fn qb2(&self) -> &[u8] {
+        self.matter.qb2()
+    }
+
+    /// Get raw bytes
+    pub fn raw(&self) -> &[u8] {
+        self.matter.raw()
+    }
+
+    /// Get code
+    pub fn code(&self) -> &str {
+        self.matter.code()
+    }
+
+    /// Get underlying Matter
+    pub fn matter(&self) -> &Matter {
+        &self.matter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dater_known_value() {
+        // Vector from keripy
+        let dater = Dater::new("2020-08-22T17:50:09.988921+00:00").unwrap();
+        assert_eq!(dater.qb64(), "1AAG2020-08-22T17c50c09d988921p00c00");
+        assert_eq!(dater.code(), matter_codes::DATE_TIME);
+        assert_eq!(dater.raw().len(), 24);
+        assert_eq!(dater.dts(), "2020-08-22T17:50:09.988921+00:00");
+    }
+
+    #[test]
+    fn test_dater_roundtrip() {
+        let dater = Dater::now().unwrap();
+
+        let dater2 = Dater::from_qb64(dater.qb64()).unwrap();
+        assert_eq!(dater.dts(), dater2.dts());
+
+        let dater3 = Dater::from_qb2(dater.qb2()).unwrap();
+        assert_eq!(dater.dts(), dater3.dts());
+    }
+
+    #[test]
+    fn test_dater_invalid() {
+        assert!(Dater::new("2020-08-22").is_err());
+        assert!(Dater::new("2020-08-22T17:50:09.988921+00:00!").is_err());
+
+        let seqner = crate::core::Seqner::new(1).unwrap();
+        assert!(Dater::from_qb64(seqner.qb64()).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::core::{matter_codes, Matter, MatterOpts};
 /// Diger - Cryptographic digest operations with CESR encoding
 use crate::error::{Result, SignifyError};
+use blake2::{digest::consts::U32, Blake2b, Blake2b512, Blake2s256};
 use blake3;
 use sha2::{Digest as Sha2Digest, Sha256, Sha512};
 use sha3::{Sha3_256, Sha3_512};
@@ -8,6 +9,9 @@ use sha3::{Sha3_256, Sha3_512};
 #[allow(unused_imports)]
 use sha3::Digest as _;
 
+/// Blake2b with 256 bit output, matching keripy's `blake2b(digest_size=32)`
+type Blake2b256 = Blake2b<U32>;
+
 /// Diger handles cryptographic digests (hashes) with CESR encoding
 #[derive(Debug, Clone)]
 pub struct Diger {
@@ -27,6 +31,12 @@ impl Diger {
         Ok(Self { matter })
     }
 
+    /// Create Diger from qb2 bytes
+    pub fn from_qb2(qb2: &[u8]) -> Result<Self> {
+        let matter = Matter::from_qb2(qb2)?;
+        Ok(Self { matter })
+    }
+
     /// Create Diger by computing digest of serialization
     ///
     /// # Arguments
@@ -54,17 +64,34 @@ impl Diger {
     }
 
     /// Compute raw digest bytes for given algorithm
-    fn compute_digest(code: &str, ser: &[u8]) -> Result<Vec<u8>> {
+    pub fn compute_digest(code: &str, ser: &[u8]) -> Result<Vec<u8>> {
         match code {
             matter_codes::BLAKE3_256 => {
                 let hash = blake3::hash(ser);
                 Ok(hash.as_bytes().to_vec())
             }
             matter_codes::BLAKE3_512 => {
+                // Blake3 extendable output, as keripy does with length=64
                 let mut hasher = blake3::Hasher::new();
                 hasher.update(ser);
-                let hash = hasher.finalize();
-                Ok(hash.as_bytes()[..64].to_vec())
+                let mut out = vec![0u8; 64];
+                hasher.finalize_xof().fill(&mut out);
+                Ok(out)
+            }
+            matter_codes::BLAKE2B_256 => {
+                let mut hasher = Blake2b256::new();
+                hasher.update(ser);
+                Ok(hasher.finalize().to_vec())
+            }
+            matter_codes::BLAKE2B_512 => {
+                let mut hasher = Blake2b512::new();
+                hasher.update(ser);
+                Ok(hasher.finalize().to_vec())
+            }
+            matter_codes::BLAKE2S_256 => {
+                let mut hasher = Blake2s256::new();
+                hasher.update(ser);
+                Ok(hasher.finalize().to_vec())
             }
             matter_codes::SHA2_256 => {
                 let mut hasher = Sha256::new();
@@ -182,6 +209,42 @@ mod tests {
         assert!(diger.verify(data).unwrap());
     }
 
+    #[test]
+    fn test_diger_all_digest_codes() {
+        let data = b"abcdefghijklmnopqrstuvwxyz0123456789";
+        for code in crate::core::codes::DIGEST_CODES {
+            let diger = Diger::new(code, data).unwrap();
+            let size = crate::core::codes::raw_size(code).unwrap();
+
+            assert_eq!(diger.code(), code);
+            assert_eq!(diger.raw().len(), size);
+            assert_eq!(diger.qb64().len(), if size == 32 { 44 } else { 88 });
+            assert!(diger.verify(data).unwrap());
+            assert!(!diger.verify(b"other data").unwrap());
+        }
+    }
+
+    #[test]
+    fn test_diger_blake2_vectors() {
+        // Known answers for the empty input
+        let diger = Diger::new(matter_codes::BLAKE2S_256, b"").unwrap();
+        assert_eq!(
+            hex::encode(diger.raw()),
+            "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9"
+        );
+
+        let diger = Diger::new(matter_codes::BLAKE2B_256, b"").unwrap();
+        assert_eq!(
+            hex::encode(diger.raw()),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+
+        // Blake3-512 extends the Blake3-256 output
+        let b256 = Diger::new(matter_codes::BLAKE3_256, b"").unwrap();
+        let b512 = Diger::new(matter_codes::BLAKE3_512, b"").unwrap();
+        assert_eq!(&b512.raw()[..32], b256.raw());
+    }
+
     #[test]
     fn test_diger_from_qb64() {
         let data = b"original data";
@@ -0,0 +1,481 @@
+//! Peer-to-peer exchange (exn) messages
+//!
+//! Exchange messages carry IPEX and multisig coordination between
+//! controllers. Other messages, such as events and credentials, travel in the
+//! `e` embeds section with their attachments moved to pathed material groups
+//! after the exchange message, and signatures over parts of an embed travel
+//! as SAD path signature groups rooted at the embed.
+
+use crate::cesr_parser::SadPathSig;
+use crate::core::codes::matter_codes;
+use crate::core::counter::{Counter, CounterCodex};
+use crate::core::eventing::ILK_EXN;
+use crate::core::pather::Pather;
+use crate::core::saider::Saider;
+use crate::core::serder::Serder;
+use crate::core::utils::{now_iso8601, versify, Protocols, Serials, VRSN_1_0};
+use crate::error::{Result, SignifyError};
+use serde_json::{json, Map, Value};
+
+/// Message embedded in an exchange message under `label`
+#[derive(Debug, Clone)]
+pub struct Embed {
+    /// Label of the embed in the `e` section
+    pub label: String,
+    /// Embedded message
+    pub serder: Serder,
+    /// CESR qb64 attachments of the embedded message, e.g. signatures
+    pub atc: Option<String>,
+    /// SAD path signatures with paths relative to the embedded message
+    pub sigs: Vec<SadPathSig>,
+}
+
+impl Embed {
+    /// Create an embed
+    pub fn new(label: &str, serder: Serder, atc: Option<&str>) -> Self {
+        Self {
+            label: label.to_string(),
+            serder,
+            atc: atc.map(str::to_string),
+            sigs: Vec::new(),
+        }
+    }
+
+    /// Attach SAD path signatures over the embed, e.g. an empty path for the
+    /// whole embedded message
+    pub fn with_sigs(mut self, sigs: Vec<SadPathSig>) -> Self {
+        self.sigs = sigs;
+        self
+    }
+}
+
+/// Create an exchange message
+///
+/// Returns the message and its attachments: the pathed material groups
+/// (`-L`) carrying the attachments of the embeds, each located by its SAD
+/// path `-e-<label>`, then a SAD path signature group (`-K`) rooted at
+/// `-e-<label>` for each embed with signatures. Embeds and the blocks nested
+/// in them are saidified when their `d` field is empty.
+///
+/// # Arguments
+/// * `route` - Exchange route, e.g. `/ipex/grant`
+/// * `payload` - Message attributes
+/// * `sender` - Sender identifier prefix
+/// * `recipient` - Recipient identifier prefix
+/// * `stamp` - ISO-8601 timestamp (default now)
+/// * `dig` - SAID of the prior exchange message in the conversation
+/// * `modifiers` - Query modifiers
+/// * `embeds` - Embedded messages and their attachments
+#[allow(clippy::too_many_arguments)]
+pub fn exchange(
+    route: &str,
+    payload: Option<Map<String, Value>>,
+    sender: &str,
+    recipient: Option<&str>,
+    stamp: Option<&str>,
+    dig: Option<&str>,
+    modifiers: Option<Value>,
+    embeds: Option<Vec<Embed>>,
+) -> Result<(Serder, String)> {
+    let vs = versify(Protocols::KERI, Some(VRSN_1_0), Some(Serials::JSON), 0);
+
+    let mut e = Map::new();
+    let mut end = String::new();
+    let mut sigs = String::new();
+    for embed in embeds.unwrap_or_default() {
+        if e.contains_key(&embed.label) || embed.label == "d" {
+            return Err(SignifyError::InvalidArgument(format!(
+                "Invalid embed label {}",
+                embed.label
+            )));
+        }
+        e.insert(embed.label.clone(), saidified(&embed.serder)?);
+
+        let root = Pather::new(&["e", &embed.label])?;
+        if !embed.sigs.is_empty() {
+            sigs.push_str(&root_sad_path_sigs(&root, &embed.sigs)?);
+        }
+
+        let Some(atc) = embed.atc.filter(|atc| !atc.is_empty()) else {
+            continue;
+        };
+        let pathed = format!("{}{}", root.qb64(), atc);
+        end.push_str(&pathed_counter(&pathed)?.qb64());
+        end.push_str(&pathed);
+    }
+    end.push_str(&sigs);
+
+    let mut e = Value::Object(e);
+    if !e.as_object().is_some_and(Map::is_empty) {
+        e["d"] = json!("");
+        Saider::saidify_with_code(&mut e, matter_codes::BLAKE3_256, "d")?;
+    }
+
+    let mut attrs = Map::new();
+    if let Some(recipient) = recipient {
+        attrs.insert("i".to_string(), json!(recipient));
+    }
+    attrs.extend(payload.unwrap_or_default());
+
+    let mut sad = json!({
+        "v": vs,
+        "t": ILK_EXN,
+        "d": "",
+        "i": sender,
+        "rp": recipient.unwrap_or(""),
+        "p": dig.unwrap_or(""),
+        "dt": stamp.map(str::to_string).unwrap_or_else(now_iso8601),
+        "r": route,
+        "q": modifiers.unwrap_or_else(|| json!({})),
+        "a": attrs,
+        "e": e,
+    });
+
+    Saider::saidify_with_code(&mut sad, matter_codes::BLAKE3_256, "d")?;
+    Ok((Serder::new(sad, None, None)?, end))
+}
+
+/// Embedded message body, saidified first when its SAID is still empty
+///
+/// Nested blocks with an empty `d`, such as the attribute, edge and rule
+/// sections of an ACDC, are saidified innermost first so the SAID of each
+/// block covers the SAIDs of the blocks inside it.
+fn saidified(serder: &Serder) -> Result<Value> {
+    let mut sad = serder.sad().clone();
+    if let Some(fields) = sad.as_object_mut() {
+        for (_, value) in fields.iter_mut().filter(|(label, _)| *label != "d") {
+            saidify_blocks(value)?;
+        }
+    }
+    if serder.said_field() == Some("") {
+        Saider::saidify_with_code(&mut sad, serder.code(), "d")?;
+        sad = Serder::new(sad, Some(serder.kind()), Some(serder.code()))?
+            .sad()
+            .clone();
+    }
+    Ok(sad)
+}
+
+/// Saidify the blocks in `value` whose `d` field is empty, innermost first
+fn saidify_blocks(value: &mut Value) -> Result<()> {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                saidify_blocks(item)?;
+            }
+        }
+        Value::Object(fields) => {
+            for item in fields.values_mut() {
+                saidify_blocks(item)?;
+            }
+            if fields.get("d").and_then(Value::as_str) == Some("") {
+                Saider::saidify_with_code(value, matter_codes::BLAKE3_256, "d")?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// SAD path signature group (`-K`) of `sigs` under the `root` path
+///
+/// Each signature is one `-J` entry of its path and a `-F` transferable or
+/// `-C` non-transferable signature group.
+fn root_sad_path_sigs(root: &Pather, sigs: &[SadPathSig]) -> Result<String> {
+    let mut entries = Vec::new();
+    for sig in sigs {
+        let path: Vec<&str> = sig.path.iter().map(String::as_str).collect();
+        let subpath = Pather::new(&path)?;
+
+        if !sig.trans_sig_groups.is_empty() {
+            let mut entry = subpath.qb64().to_string();
+            let count = sig.trans_sig_groups.len() as u32;
+            entry.push_str(&Counter::new(CounterCodex::TRANS_IDX_SIG_GROUPS, count)?.qb64());
+            for group in &sig.trans_sig_groups {
+                entry.push_str(group.prefixer.qb64());
+                entry.push_str(group.seqner.qb64());
+                entry.push_str(group.diger.qb64());
+                let count = group.sigers.len() as u32;
+                entry.push_str(&Counter::new(CounterCodex::CONTROLLER_IDX_SIGS, count)?.qb64());
+                for siger in &group.sigers {
+                    entry.push_str(&siger.qb64());
+                }
+            }
+            entries.push(entry);
+        }
+
+        if !sig.receipts.is_empty() {
+            let mut entry = subpath.qb64().to_string();
+            let count = sig.receipts.len() as u32;
+            entry.push_str(&Counter::new(CounterCodex::NON_TRANS_RCT, count)?.qb64());
+            for receipt in &sig.receipts {
+                entry.push_str(receipt.prefixer.qb64());
+                entry.push_str(receipt.cigar.qb64());
+            }
+            entries.push(entry);
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(SignifyError::InvalidArgument(format!(
+            "SAD path signatures under {} carry no signatures",
+            root.qb64()
+        )));
+    }
+
+    Ok(format!(
+        "{}{}{}{}",
+        Counter::new(CounterCodex::SAD_PATH_SIG_GROUP, 1)?.qb64(),
+        root.qb64(),
+        Counter::new(CounterCodex::SAD_PATH_SIG, entries.len() as u32)?.qb64(),
+        entries.concat()
+    ))
+}
+
+/// Pathed material counter sized for `pathed` quadlets
+fn pathed_counter(pathed: &str) -> Result<Counter> {
+    if !pathed.len().is_multiple_of(4) {
+        return Err(SignifyError::InvalidArgument(format!(
+            "Pathed material of {} chars is not quadlet aligned",
+            pathed.len()
+        )));
+    }
+    let count = (pathed.len() / 4) as u32;
+    if count < 64 * 64 {
+        Counter::new(CounterCodex::PATHED_MATERIAL_QUADLETS, count)
+    } else {
+        Counter::new(CounterCodex::BIG_PATHED_MATERIAL_QUADLETS, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cesr_parser::{parse_stream, NonTransReceipt, TransIdxSigGroup};
+    use crate::core::eventing::interact;
+    use crate::core::{Cigar, Diger, IndexerCodex, Prefixer, Seqner, Siger, Signer};
+
+    const STAMP: &str = "2021-01-01T00:00:00.000000+00:00";
+
+    #[test]
+    fn test_exchange() {
+        let mut payload = Map::new();
+        payload.insert("m".to_string(), json!("hello"));
+
+        let (exn, end) = exchange(
+            "/ipex/admit",
+            Some(payload),
+            "ESender",
+            Some("ERecipient"),
+            Some(STAMP),
+            Some("EPriorExn"),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let sad = exn.sad();
+        let fields: Vec<&String> = sad.as_object().unwrap().keys().collect();
+        assert_eq!(
+            fields,
+            ["v", "t", "d", "i", "rp", "p", "dt", "r", "q", "a", "e"]
+        );
+        assert_eq!(sad["t"], "exn");
+        assert_eq!(sad["rp"], "ERecipient");
+        assert_eq!(sad["p"], "EPriorExn");
+        assert_eq!(sad["dt"], STAMP);
+        assert_eq!(
+            serde_json::to_string(&sad["a"]).unwrap(),
+            r#"{"i":"ERecipient","m":"hello"}"#
+        );
+        assert_eq!(sad["e"], json!({}));
+        assert_eq!(exn.said(None).unwrap(), exn.said_field().unwrap());
+        assert!(end.is_empty());
+    }
+
+    #[test]
+    fn test_exchange_embeds() {
+        let ixn = interact("EPre", "EPrior", 1, None, None).unwrap();
+        let atc = "-AAB".to_string() + &"A".repeat(88);
+
+        let (exn, end) = exchange(
+            "/multisig/ixn",
+            None,
+            "ESender",
+            None,
+            Some(STAMP),
+            None,
+            None,
+            Some(vec![Embed::new("ixn", ixn.clone(), Some(&atc))]),
+        )
+        .unwrap();
+
+        let e = &exn.sad()["e"];
+        assert_eq!(&e["ixn"], ixn.sad());
+        let keys: Vec<&String> = e.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["ixn", "d"]);
+        assert_eq!(
+            Saider::saidify_with_code(&mut e.clone(), matter_codes::BLAKE3_256, "d")
+                .unwrap()
+                .qb64(),
+            e["d"]
+        );
+        assert_eq!(exn.sad()["rp"], "");
+        assert_eq!(exn.sad()["a"], json!({}));
+
+        // One pathed group of the path plus the attachment quadlets
+        let pather = Pather::new(&["e", "ixn"]).unwrap();
+        let count = (pather.qb64().len() + atc.len()) / 4;
+        let counter = Counter::new(CounterCodex::PATHED_MATERIAL_QUADLETS, count as u32).unwrap();
+        assert_eq!(end, format!("{}{}{}", counter.qb64(), pather.qb64(), atc));
+        assert!(end.starts_with("-LAa5AACAA-e-ixn-AAB"));
+    }
+
+    #[test]
+    fn test_exchange_saidifies_embeds() {
+        let sad = json!({
+            "v": "ACDC10JSON000000_",
+            "d": "",
+            "i": "EIssuer",
+            "a": {"d": "", "i": "EHolder"},
+            "r": [{"d": "", "l": "rule"}],
+        });
+        let acdc = Serder::new(sad, None, None).unwrap();
+
+        let (exn, end) = exchange(
+            "/ipex/grant",
+            None,
+            "ESender",
+            None,
+            Some(STAMP),
+            None,
+            None,
+            Some(vec![Embed::new("acdc", acdc, None)]),
+        )
+        .unwrap();
+
+        let embedded = Serder::new(exn.sad()["e"]["acdc"].clone(), None, None).unwrap();
+        assert_ne!(embedded.said_field(), Some(""));
+        assert_eq!(embedded.said(None).unwrap(), embedded.said_field().unwrap());
+        assert!(end.is_empty());
+
+        // Nested blocks get their SAIDs before the embed SAID covers them
+        for block in [&embedded.sad()["a"], &embedded.sad()["r"][0]] {
+            let said = block["d"].as_str().unwrap();
+            assert!(said.starts_with('E'));
+            let saider =
+                Saider::saidify_with_code(&mut block.clone(), matter_codes::BLAKE3_256, "d")
+                    .unwrap();
+            assert_eq!(saider.qb64(), said);
+        }
+
+        let dup = vec![
+            Embed::new("acdc", embedded.clone(), None),
+            Embed::new("acdc", embedded, None),
+        ];
+        assert!(exchange(
+            "/ipex/grant",
+            None,
+            "ESender",
+            None,
+            None,
+            None,
+            None,
+            Some(dup)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_exchange_sad_path_sigs_round_trip() {
+        let sender = Signer::from_seed(&[1; 32], matter_codes::ED25519_SEED, true).unwrap();
+        let controller = Signer::from_seed(&[2; 32], matter_codes::ED25519_SEED, true).unwrap();
+        let witness = Signer::from_seed(&[3; 32], matter_codes::ED25519_SEED, false).unwrap();
+        let pre = controller.verfer().qb64();
+
+        let ixn = interact(pre, "EPrior", 1, None, None).unwrap();
+        let siger = |signer: &Signer, ser: &[u8]| {
+            let sig = signer.sign(ser).unwrap();
+            Siger::new(&sig, IndexerCodex::ED25519_SIG, 0, None, None).unwrap()
+        };
+        let ser = ixn.raw().as_bytes();
+        let atc = format!("-AAB{}", siger(&controller, ser).qb64());
+        let sigs = vec![SadPathSig {
+            path: vec![],
+            trans_sig_groups: vec![TransIdxSigGroup {
+                prefixer: Prefixer::from_qb64(pre).unwrap(),
+                seqner: Seqner::new(0).unwrap(),
+                diger: Diger::new(matter_codes::BLAKE3_256, b"icp").unwrap(),
+                sigers: vec![siger(&controller, ser)],
+            }],
+            receipts: vec![NonTransReceipt {
+                prefixer: Prefixer::from_qb64(witness.verfer().qb64()).unwrap(),
+                cigar: Cigar::new(&witness.sign(ser).unwrap(), matter_codes::ED25519_SIG, None)
+                    .unwrap(),
+            }],
+        }];
+
+        let (exn, end) = exchange(
+            "/multisig/ixn",
+            None,
+            sender.verfer().qb64(),
+            None,
+            Some(STAMP),
+            None,
+            None,
+            Some(vec![Embed::new("ixn", ixn, Some(&atc)).with_sigs(sigs)]),
+        )
+        .unwrap();
+        assert!(end.contains("-KAB5AACAA-e-ixn-JAC"));
+
+        let stream = format!(
+            "{}-AAB{}{}",
+            exn.raw(),
+            siger(&sender, exn.raw().as_bytes()).qb64(),
+            end
+        );
+        let messages = parse_stream(stream.as_bytes()).unwrap();
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        let atts = &message.attachments;
+        assert_eq!(message.serder.said_field(), exn.said_field());
+
+        let verify = |siger: &Siger, signer: &Signer, ser: &[u8]| {
+            assert!(signer.verfer().verify(siger.raw(), ser).unwrap());
+        };
+        verify(&atts.sigers[0], &sender, message.serder.raw().as_bytes());
+
+        // Each signed path resolves to the embedded event in the parsed body
+        let resolve = |path: &[String]| {
+            let value = path
+                .iter()
+                .fold(message.serder.sad(), |value, label| &value[label]);
+            serde_json::to_vec(value).unwrap()
+        };
+
+        assert_eq!(atts.pathed.len(), 1);
+        let pathed = &atts.pathed[0];
+        assert_eq!(pathed.path, ["e", "ixn"]);
+        verify(
+            &pathed.attachments.sigers[0],
+            &controller,
+            &resolve(&pathed.path),
+        );
+
+        assert_eq!(atts.sad_path_sigs.len(), 2);
+        let trans = &atts.sad_path_sigs[0];
+        assert_eq!(trans.path, ["e", "ixn"]);
+        assert!(trans.receipts.is_empty());
+        let group = &trans.trans_sig_groups[0];
+        assert_eq!(group.prefixer.qb64(), pre);
+        assert_eq!(group.seqner.sn(), 0);
+        verify(&group.sigers[0], &controller, &resolve(&trans.path));
+
+        let nontrans = &atts.sad_path_sigs[1];
+        assert_eq!(nontrans.path, ["e", "ixn"]);
+        assert!(nontrans.trans_sig_groups.is_empty());
+        let receipt = &nontrans.receipts[0];
+        assert_eq!(receipt.prefixer.qb64(), witness.verfer().qb64());
+        assert!(receipt.cigar.verify(&resolve(&nontrans.path)).unwrap());
+    }
+}
@@ -5,12 +5,14 @@
 /// multi-signature scenarios.
 ///
 /// Index encoding:
-/// - Small codes (1 char): support index 0-63 (6 bits)
-/// - Big codes (2 char): support index 0-16383 (14 bits + 2 bits for ondex)
+/// - Small codes (1 char): support index 0-63 (6 bits), ondex equals index
+/// - Ed448 codes (2 char): support index and ondex 0-63 each
+/// - Big codes (2 char): support index and ondex 0-4095 each (0-262143 for Ed448)
 ///
 /// Current vs Both:
 /// - Current (Crt): signature appears only in current event
 /// - Both (Bth): signature appears in both current and prior events
+use crate::core::utils::b2_to_b64;
 use crate::error::{Result, SignifyError};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 
@@ -21,7 +23,8 @@ use std::collections::HashMap;
 #[derive(Debug, Clone)]
 pub struct IndexerSizage {
     pub hs: usize, // Hard size (code length)
-    pub ss: usize, // Soft size (index length)
+    pub ss: usize, // Soft size (index + ondex length)
+    pub os: usize, // Other size (ondex length)
     pub fs: usize, // Full size (total qb64 length)
 }
 
@@ -29,13 +32,14 @@ pub struct IndexerSizage {
 static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(|| {
     let mut m = HashMap::new();
 
-    // Small indexed signatures (1 char code + 1 char index)
+    // Small indexed signatures (1 char code + 1 char index, ondex implied)
     // 64-byte signature = 86 base64 chars + 1 code + 1 index = 88 total
     m.insert(
         "A",
         IndexerSizage {
             hs: 1,
             ss: 1,
+            os: 0,
             fs: 88,
         },
     );
@@ -44,6 +48,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 1,
             ss: 1,
+            os: 0,
             fs: 88,
         },
     );
@@ -52,6 +57,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 1,
             ss: 1,
+            os: 0,
             fs: 88,
         },
     );
@@ -60,6 +66,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 1,
             ss: 1,
+            os: 0,
             fs: 88,
         },
     );
@@ -68,6 +75,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 1,
             ss: 1,
+            os: 0,
             fs: 88,
         },
     );
@@ -76,16 +84,18 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 1,
             ss: 1,
+            os: 0,
             fs: 88,
         },
     );
 
-    // Medium indexed signatures (2 char code + 2 char index)
+    // Medium indexed signatures (2 char code + 1 char index + 1 char ondex)
     m.insert(
         "0A",
         IndexerSizage {
             hs: 2,
             ss: 2,
+            os: 1,
             fs: 156,
         },
     );
@@ -94,16 +104,18 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 2,
             ss: 2,
+            os: 1,
             fs: 156,
         },
     );
 
-    // Big indexed signatures (2 char code + 4 char dual index)
+    // Big indexed signatures (2 char code + 2 char index + 2 char ondex)
     m.insert(
         "2A",
         IndexerSizage {
             hs: 2,
             ss: 4,
+            os: 2,
             fs: 92,
         },
     );
@@ -112,6 +124,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 2,
             ss: 4,
+            os: 2,
             fs: 92,
         },
     );
@@ -120,6 +133,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 2,
             ss: 4,
+            os: 2,
             fs: 92,
         },
     );
@@ -128,6 +142,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 2,
             ss: 4,
+            os: 2,
             fs: 92,
         },
     );
@@ -136,6 +151,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 2,
             ss: 4,
+            os: 2,
             fs: 92,
         },
     );
@@ -144,6 +160,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 2,
             ss: 4,
+            os: 2,
             fs: 92,
         },
     );
@@ -152,6 +169,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 2,
             ss: 6,
+            os: 3,
             fs: 160,
         },
     );
@@ -160,6 +178,7 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
         IndexerSizage {
             hs: 2,
             ss: 6,
+            os: 3,
             fs: 160,
         },
     );
@@ -167,7 +186,8 @@ static INDEXER_SIZES: Lazy<HashMap<&'static str, IndexerSizage>> = Lazy::new(||
     m
 });
 
-fn indexer_sizage(code: &str) -> Option<&IndexerSizage> {
+/// Get size info for an indexer code
+pub fn indexer_sizage(code: &str) -> Option<&IndexerSizage> {
     INDEXER_SIZES.get(code)
 }
 
@@ -236,6 +256,7 @@ impl IndexerCodex {
 }
 
 /// Indexed signature primitive
+#[derive(Debug, Clone)]
 pub struct Indexer {
     raw: Vec<u8>, // raw signature bytes
     code: String, // CESR code
@@ -245,6 +266,9 @@ pub struct Indexer {
 
 impl Indexer {
     /// Create new Indexer from raw signature and indices
+    ///
+    /// Codes without an encoded ondex (small and current only codes) imply
+    /// ondex equals index, so a differing ondex is rejected for those codes.
     pub fn new(raw: &[u8], code: &str, index: u32, ondex: Option<u32>) -> Result<Self> {
         if !IndexerCodex::is_valid(code) {
             return Err(SignifyError::InvalidCode(format!(
@@ -254,25 +278,24 @@ impl Indexer {
         }
 
         // Get size info for this indexer code
-        let _sizage = indexer_sizage(code)
+        let sizage = indexer_sizage(code)
             .ok_or_else(|| SignifyError::InvalidCode(format!("Unknown indexer code: {}", code)))?;
 
-        // Validate raw signature size (should be 64 bytes for Ed25519, 114 for Ed448, etc.)
-        // For now, we accept any size since different signature algorithms have different sizes
-        // The qb64 encoding will handle the size properly
+        // Raw signature must exactly fill the code's full size
+        let rs = raw_size(sizage);
+        if raw.len() != rs {
+            return Err(SignifyError::InvalidSize {
+                expected: rs,
+                actual: raw.len(),
+            });
+        }
 
-        // Validate index range based on code
-        if IndexerCodex::is_big(code) {
-            if index > 16383 {
-                return Err(SignifyError::InvalidIndex(format!(
-                    "Index {} exceeds maximum 16383 for big codes",
-                    index
-                )));
-            }
-        } else if index > 63 {
+        // Validate index range based on the index chars of the soft size
+        let max_index = (1u64 << (6 * (sizage.ss - sizage.os))) - 1;
+        if index as u64 > max_index {
             return Err(SignifyError::InvalidIndex(format!(
-                "Index {} exceeds maximum 63 for small codes",
-                index
+                "Index {} exceeds maximum {} for code {}",
+                index, max_index, code
             )));
         }
 
@@ -280,18 +303,21 @@ impl Indexer {
         let ondex = ondex.unwrap_or(index);
 
         // Validate ondex
-        if IndexerCodex::is_big(code) {
-            if ondex > 16383 {
+        if sizage.os == 0 || IndexerCodex::is_current_only(code) {
+            if ondex != index {
                 return Err(SignifyError::InvalidIndex(format!(
-                    "Ondex {} exceeds maximum 16383 for big codes",
-                    ondex
+                    "Ondex {} must equal index {} for code {}",
+                    ondex, index, code
+                )));
+            }
+        } else {
+            let max_ondex = (1u64 << (6 * sizage.os)) - 1;
+            if ondex as u64 > max_ondex {
+                return Err(SignifyError::InvalidIndex(format!(
+                    "Ondex {} exceeds maximum {} for code {}",
+                    ondex, max_ondex, code
                 )));
             }
-        } else if ondex > 63 {
-            return Err(SignifyError::InvalidIndex(format!(
-                "Ondex {} exceeds maximum 63 for small codes",
-                ondex
-            )));
         }
 
         Ok(Self {
@@ -303,15 +329,32 @@ impl Indexer {
     }
 
     /// Create Indexer from qb64 string
+    ///
+    /// Parses exactly the code's full size from the front of `qb64`, so any
+    /// trailing stream content is ignored.
     pub fn from_qb64(qb64: &str) -> Result<Self> {
-        // Extract code
-        let code = if qb64.len() >= 2 && qb64.starts_with(|c: char| c.is_ascii_digit()) {
-            &qb64[0..2]
-        } else if !qb64.is_empty() {
-            &qb64[0..1]
-        } else {
+        if qb64.is_empty() {
             return Err(SignifyError::InvalidFormat("Empty qb64 string".to_string()));
+        }
+
+        // Only Base64 chars are checked before slicing, so no slice can
+        // split a multi-byte char
+        let b64 = |b: &u8| b.is_ascii_alphanumeric() || *b == b'-' || *b == b'_';
+        let hs = if qb64.starts_with(|c: char| c.is_ascii_digit()) {
+            2
+        } else {
+            1
         };
+        if !qb64.as_bytes().iter().take(hs).all(b64) {
+            return Err(SignifyError::InvalidCesr(
+                "Non-Base64 chars in indexer code".to_string(),
+            ));
+        }
+
+        // Extract code
+        let code = qb64.get(0..hs).ok_or_else(|| {
+            SignifyError::InvalidFormat(format!("qb64 string too short: {} < {}", qb64.len(), hs))
+        })?;
 
         if !IndexerCodex::is_valid(code) {
             return Err(SignifyError::InvalidCode(format!(
@@ -325,7 +368,7 @@ impl Indexer {
             .ok_or_else(|| SignifyError::InvalidCode(format!("Unknown indexer code: {}", code)))?;
 
         let hs = sizage.hs;
-        let ss = sizage.ss;
+        let cs = sizage.hs + sizage.ss;
         let fs = sizage.fs;
 
         if qb64.len() < fs {
@@ -336,57 +379,131 @@ impl Indexer {
             )));
         }
 
-        // Extract index from qb64
-        // For small codes: 1 char code + 1 char index (6 bits)
-        // For big codes: 2 char code + 4 chars index (2 + 2)
-        let (index, ondex) = if IndexerCodex::is_big(code) {
-            // Big codes use 2 chars for code + 4 chars for indices
-            let index_str = &qb64[hs..hs + 2];
-            let ondex_str = &qb64[hs + 2..hs + 4];
-            let index = b64_to_int(index_str)? as u32;
-            let ondex = b64_to_int(ondex_str)? as u32;
-            (index, ondex)
+        if !qb64.as_bytes()[..fs].iter().all(b64) {
+            return Err(SignifyError::InvalidCesr(format!(
+                "Non-Base64 chars in qb64 for code {}",
+                code
+            )));
+        }
+
+        // Soft part holds index chars followed by ondex chars
+        let ms = sizage.ss - sizage.os;
+        let index = b64_to_int(&qb64[hs..hs + ms])? as u32;
+        let ondex = if sizage.os == 0 {
+            index
         } else {
-            // Small codes use 1 char for index (6 bits)
-            let index_char = &qb64[hs..hs + 1];
-            let index = b64_to_int(index_char)? as u32;
-            (index, index) // ondex = index for small codes
+            let ondex = b64_to_int(&qb64[hs + ms..cs])? as u32;
+            if IndexerCodex::is_current_only(code) {
+                if ondex != 0 {
+                    return Err(SignifyError::InvalidIndex(format!(
+                        "Non-zero ondex {} for current only code {}",
+                        ondex, code
+                    )));
+                }
+                index
+            } else {
+                ondex
+            }
         };
 
-        // Decode signature bytes from qb64
-        let sig_part = &qb64[hs + ss..];
-        let raw = URL_SAFE_NO_PAD
-            .decode(sig_part)
+        // Signature is encoded with ps zero bytes prepended, where the pad
+        // size always equals cs mod 4 so that code and pad share a quadlet
+        let ps = cs % 4;
+        let base = format!("{}{}", "A".repeat(ps), &qb64[cs..fs]);
+        let paw = URL_SAFE_NO_PAD
+            .decode(base.as_bytes())
             .map_err(|e| SignifyError::InvalidCesr(format!("Failed to decode signature: {}", e)))?;
 
+        if paw[..ps].iter().any(|&b| b != 0) {
+            return Err(SignifyError::InvalidCesr(format!(
+                "Non-zero pad bits in qb64 for code {}",
+                code
+            )));
+        }
+
         Ok(Self {
-            raw,
+            raw: paw[ps..].to_vec(),
             code: code.to_string(),
             index,
             ondex,
         })
     }
 
+    /// Create Indexer from qb2 bytes
+    ///
+    /// Parses exactly the code's full size from the front of `qb2`, so any
+    /// trailing stream content is ignored.
+    pub fn from_qb2(qb2: &[u8]) -> Result<Self> {
+        if qb2.is_empty() {
+            return Err(SignifyError::InvalidFormat("Empty qb2 bytes".to_string()));
+        }
+
+        // First sextet selects between 1 and 2 char hard codes
+        let first = b2_to_b64(qb2, 1);
+        let hs = if first.starts_with(|c: char| c.is_ascii_digit()) {
+            2
+        } else {
+            1
+        };
+        let code = b2_to_b64(qb2, hs);
+        if code.len() < hs {
+            return Err(SignifyError::InvalidFormat(
+                "qb2 bytes too short for code".to_string(),
+            ));
+        }
+
+        let sizage = indexer_sizage(&code).ok_or_else(|| {
+            SignifyError::InvalidCode(format!("Invalid indexer code in qb2: {}", code))
+        })?;
+
+        // Full size is always a multiple of 4 chars, so 3 bytes per quadlet
+        let bfs = sizage.fs * 3 / 4;
+        if qb2.len() < bfs {
+            return Err(SignifyError::InvalidFormat(format!(
+                "qb2 bytes too short: {} < {}",
+                qb2.len(),
+                bfs
+            )));
+        }
+
+        Self::from_qb64(&URL_SAFE_NO_PAD.encode(&qb2[..bfs]))
+    }
+
     /// Get qb64 encoding
     pub fn qb64(&self) -> String {
         let sizage = indexer_sizage(&self.code).unwrap();
-        let hs = sizage.hs;
-        let ss = sizage.ss;
+        let cs = sizage.hs + sizage.ss;
+        let ms = sizage.ss - sizage.os;
 
-        // Encode signature bytes to base64
-        let sig_b64 = URL_SAFE_NO_PAD.encode(&self.raw);
-
-        // Build qb64 string: code + index(es) + signature
-        if IndexerCodex::is_big(&self.code) {
-            // Big codes: 2 char code + 4 chars dual index + signature
-            let index_b64 = int_to_b64(self.index as usize, 2);
-            let ondex_b64 = int_to_b64(self.ondex as usize, 2);
-            format!("{}{}{}{}", self.code, index_b64, ondex_b64, sig_b64)
+        // Current only codes carry a zero ondex when it has chars in the soft part
+        let ondex = if IndexerCodex::is_current_only(&self.code) {
+            0
         } else {
-            // Small codes: 1 char code + 1 char index + signature
-            let index_b64 = int_to_b64(self.index as usize, 1);
-            format!("{}{}{}", self.code, index_b64, sig_b64)
-        }
+            self.ondex as usize
+        };
+        let both = format!(
+            "{}{}{}",
+            self.code,
+            int_to_b64(self.index as usize, ms),
+            int_to_b64(ondex, sizage.os)
+        );
+
+        // Prepend pad bytes so the signature aligns on 24 bit boundaries,
+        // then drop the pad chars which the code replaces
+        let ps = cs % 4;
+        let mut padded = vec![0u8; ps];
+        padded.extend_from_slice(&self.raw);
+        let sig_b64 = URL_SAFE_NO_PAD.encode(&padded);
+
+        format!("{}{}", both, &sig_b64[ps..])
+    }
+
+    /// Get qb2 encoding
+    pub fn qb2(&self) -> Vec<u8> {
+        // Full size is a multiple of 4 chars so qb64 decodes without padding
+        URL_SAFE_NO_PAD
+            .decode(self.qb64())
+            .expect("qb64 of an Indexer is always valid base64")
     }
 
     /// Get signature index
@@ -410,6 +527,11 @@ impl Indexer {
     }
 }
 
+/// Raw signature size in bytes implied by a code's full size
+fn raw_size(sizage: &IndexerSizage) -> usize {
+    (sizage.fs - (sizage.hs + sizage.ss)) * 3 / 4
+}
+
 /// Convert base64url character(s) to integer
 fn b64_to_int(s: &str) -> Result<usize> {
     let bytes = s.as_bytes();
@@ -500,6 +622,113 @@ mod tests {
         assert_eq!(indexer.raw(), indexer2.raw());
     }
 
+    #[test]
+    fn test_indexer_known_vector() {
+        // Ed25519 indexed signature vector from keripy
+        let sig = b"\x99\xd2<9$$0\x9fk\xfb\x18\xa0\x8c@r\x122.k\xb2\xc7\x1fp\x0e'm\x8f@\xaa\xa5\x8c\xc8n\x85\xc8!\xf6q\x91p\xa9\xec\xcf\x92\xaf)\xde\xca\xfc\x7f~\xd7o|\x17\x82\x1d\xd4<o\"\x81&\t";
+        let qsig64 = "AACZ0jw5JCQwn2v7GKCMQHISMi5rsscfcA4nbY9AqqWMyG6FyCH2cZFwqezPkq8p3sr8f37Xb3wXgh3UPG8igSYJ";
+
+        let indexer = Indexer::new(sig, IndexerCodex::ED25519_SIG, 0, None).unwrap();
+        assert_eq!(indexer.qb64(), qsig64);
+
+        let indexer = Indexer::from_qb64(qsig64).unwrap();
+        assert_eq!(indexer.raw(), sig);
+        assert_eq!(indexer.index(), 0);
+        assert_eq!(indexer.ondex(), 0);
+    }
+
+    #[test]
+    fn test_indexer_qb2_all_codes() {
+        for (code, sizage) in INDEXER_SIZES.iter() {
+            let raw: Vec<u8> = (0..raw_size(sizage)).map(|i| i as u8).collect();
+            let ondex = if sizage.os == 0 || IndexerCodex::is_current_only(code) {
+                None
+            } else {
+                Some(7)
+            };
+            let indexer = Indexer::new(&raw, code, 5, ondex).unwrap();
+
+            let qb64 = indexer.qb64();
+            let qb2 = indexer.qb2();
+            assert_eq!(qb64.len(), sizage.fs, "code {}", code);
+            assert_eq!(qb2.len(), sizage.fs * 3 / 4, "code {}", code);
+            assert_eq!(qb2, URL_SAFE_NO_PAD.decode(&qb64).unwrap(), "code {}", code);
+
+            let from_qb2 = Indexer::from_qb2(&qb2).unwrap();
+            assert_eq!(from_qb2.qb64(), qb64, "code {}", code);
+            assert_eq!(from_qb2.code(), *code);
+            assert_eq!(from_qb2.raw(), raw.as_slice());
+            assert_eq!(from_qb2.index(), 5);
+            assert_eq!(from_qb2.ondex(), ondex.unwrap_or(5));
+
+            let from_qb64 = Indexer::from_qb64(&qb64).unwrap();
+            assert_eq!(from_qb64.qb2(), qb2, "code {}", code);
+        }
+    }
+
+    #[test]
+    fn test_indexer_qb2_stream_and_truncated() {
+        let sig = vec![3u8; 64];
+        let indexer = Indexer::new(&sig, IndexerCodex::ED25519_BIG_SIG, 70, Some(71)).unwrap();
+
+        // Trailing stream content is ignored
+        let mut stream = indexer.qb2();
+        stream.extend_from_slice(b"trailing");
+        let parsed = Indexer::from_qb2(&stream).unwrap();
+        assert_eq!(parsed.qb64(), indexer.qb64());
+
+        // Truncated input fails
+        let qb2 = indexer.qb2();
+        assert!(Indexer::from_qb2(&qb2[..qb2.len() - 1]).is_err());
+        assert!(Indexer::from_qb2(&[]).is_err());
+    }
+
+    #[test]
+    fn test_indexer_invalid_raw_size_and_ondex() {
+        // Ed25519 signatures must be 64 bytes
+        assert!(Indexer::new(&[0u8; 63], IndexerCodex::ED25519_SIG, 0, None).is_err());
+
+        // Small and current only codes cannot carry a different ondex
+        let sig = vec![0u8; 64];
+        assert!(Indexer::new(&sig, IndexerCodex::ED25519_SIG, 1, Some(2)).is_err());
+        assert!(Indexer::new(&sig, IndexerCodex::ED25519_BIG_CRT_SIG, 1, Some(2)).is_err());
+
+        // Current only big codes encode a zero ondex
+        let indexer = Indexer::new(&sig, IndexerCodex::ED25519_BIG_CRT_SIG, 65, None).unwrap();
+        assert!(indexer.qb64().starts_with("2BBBAA"));
+    }
+
+    #[test]
+    fn test_indexer_non_base64() {
+        let qb64 = Indexer::new(&[0u8; 64], IndexerCodex::ED25519_SIG, 0, None)
+            .unwrap()
+            .qb64();
+
+        // Multi-byte chars anywhere in the code or material fail cleanly
+        for at in [0, 1, 2, 40, 86] {
+            let mut bad = qb64.clone();
+            bad.replace_range(at..at + 2, "é");
+            assert!(matches!(
+                Indexer::from_qb64(&bad),
+                Err(SignifyError::InvalidCesr(_))
+            ));
+        }
+        assert!(matches!(
+            Indexer::from_qb64("1é"),
+            Err(SignifyError::InvalidCesr(_))
+        ));
+        let mut bad = qb64.clone();
+        bad.replace_range(10..11, "=");
+        assert!(matches!(
+            Indexer::from_qb64(&bad),
+            Err(SignifyError::InvalidCesr(_))
+        ));
+
+        // Trailing stream content is still ignored
+        let parsed = Indexer::from_qb64(&format!("{}é", qb64)).unwrap();
+        assert_eq!(parsed.qb64(), qb64);
+    }
+
     #[test]
     fn test_b64_to_int() {
         assert_eq!(b64_to_int("A").unwrap(), 0);
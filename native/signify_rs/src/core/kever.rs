@@ -0,0 +1,714 @@
+//! Kever - Key state machine for a single identifier
+//!
+//! A Kever is created from a validated inception event and then updated with
+//! each following event of the KEL in order. Every event is checked for its
+//! SAID, sequence number, prior event digest and controller signatures, and
+//! establishment events for their thresholds, witness changes and the
+//! pre-rotation commitment made by the prior establishment event.
+
+use crate::core::diger::Diger;
+use crate::core::eventing::{
+    verify_threshold, TraitCodex, ILK_DIP, ILK_DRT, ILK_ICP, ILK_IXN, ILK_ROT,
+};
+use crate::core::indexer::IndexerCodex;
+use crate::core::prefixer::Prefixer;
+use crate::core::serder::Serder;
+use crate::core::siger::Siger;
+use crate::core::state::{KeyStateRecord, StateEstEvent};
+use crate::core::tholder::Tholder;
+use crate::core::verfer::Verfer;
+use crate::error::{Result, SignifyError};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Key state of an identifier as of its latest accepted event
+#[derive(Debug, Clone)]
+pub struct Kever {
+    prefixer: Prefixer,
+    serder: Serder,
+    sn: u64,
+    said: String,
+    tholder: Tholder,
+    verfers: Vec<Verfer>,
+    ntholder: Tholder,
+    ndigs: Vec<String>,
+    toad: usize,
+    wits: Vec<String>,
+    cnfg: Vec<String>,
+    delpre: Option<String>,
+    last_est: (u64, String),
+    cuts: Vec<String>,
+    adds: Vec<String>,
+}
+
+impl Kever {
+    /// Create key state from an inception (icp) or delegated inception (dip)
+    /// event and its controller signatures
+    ///
+    /// Returns the Kever and the verified signatures.
+    pub fn new(serder: &Serder, sigers: &[Siger]) -> Result<(Self, Vec<Siger>)> {
+        let ilk = serder.ilk().unwrap_or_default();
+        if ilk != ILK_ICP && ilk != ILK_DIP {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Invalid ilk {} for inception",
+                ilk
+            )));
+        }
+
+        let sn = event_sn(serder)?;
+        if sn != 0 {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Invalid sn {} for inception",
+                sn
+            )));
+        }
+        let said = verify_said(serder)?;
+
+        let pre = field_str(serder, "i")?;
+        let prefixer = Prefixer::from_qb64(pre)?;
+        if !prefixer.verify(serder, true)? {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Invalid prefix {} for inception event {}",
+                pre, said
+            )));
+        }
+
+        let (tholder, verfers) = signing_keys(serder)?;
+        let (ntholder, ndigs) = next_digests(serder)?;
+
+        let wits = field_list(serder, "b")?;
+        unique(&wits, "b")?;
+        let toad = witness_toad(serder, wits.len())?;
+        let cnfg = field_list(serder, "c")?;
+
+        let delpre = if ilk == ILK_DIP {
+            if !prefixer.derivation().is_digestive() {
+                return Err(SignifyError::InvalidEvent(format!(
+                    "Delegated prefix {} must be digestive",
+                    pre
+                )));
+            }
+            Some(field_str(serder, "di")?.to_string())
+        } else {
+            None
+        };
+
+        let verified = verify_threshold(serder.raw().as_bytes(), sigers, &verfers, &tholder)?;
+
+        let kever = Self {
+            prefixer,
+            serder: serder.clone(),
+            sn,
+            said: said.clone(),
+            tholder,
+            verfers,
+            ntholder,
+            ndigs,
+            toad,
+            wits,
+            cnfg,
+            delpre,
+            last_est: (sn, said),
+            cuts: Vec::new(),
+            adds: Vec::new(),
+        };
+
+        Ok((kever, verified))
+    }
+
+    /// Update key state with the next event of the KEL, a rotation (rot),
+    /// delegated rotation (drt) or interaction (ixn)
+    ///
+    /// Returns the verified signatures. The key state is unchanged on error,
+    /// which is `VerificationFailed` when the signatures do not satisfy the
+    /// current or prior next threshold.
+    pub fn update(&mut self, serder: &Serder, sigers: &[Siger]) -> Result<Vec<Siger>> {
+        if serder.pre() != Some(self.pre()) {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Event prefix {:?} does not match {}",
+                serder.pre(),
+                self.pre()
+            )));
+        }
+
+        let ilk = serder.ilk().unwrap_or_default();
+        let sn = event_sn(serder)?;
+        if sn != self.sn + 1 {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Out of order sn {} for {} at sn {}",
+                sn,
+                self.pre(),
+                self.sn
+            )));
+        }
+        let said = verify_said(serder)?;
+
+        let prior = field_str(serder, "p")?;
+        if prior != self.said {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Prior digest {} does not match {} at sn {}",
+                prior, self.said, self.sn
+            )));
+        }
+
+        if !self.transferable() {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Non-transferable {} cannot accept {} event",
+                self.pre(),
+                ilk
+            )));
+        }
+
+        match ilk {
+            ILK_ROT | ILK_DRT => self.rotate(serder, sigers, sn, said),
+            ILK_IXN => self.interact(serder, sigers, sn, said),
+            _ => Err(SignifyError::InvalidEvent(format!(
+                "Invalid ilk {} for update of {}",
+                ilk,
+                self.pre()
+            ))),
+        }
+    }
+
+    fn rotate(
+        &mut self,
+        serder: &Serder,
+        sigers: &[Siger],
+        sn: u64,
+        said: String,
+    ) -> Result<Vec<Siger>> {
+        let ilk = serder.ilk().unwrap_or_default();
+        if (ilk == ILK_DRT) != self.delpre.is_some() {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Invalid ilk {} for {} delegated={}",
+                ilk,
+                self.pre(),
+                self.delpre.is_some()
+            )));
+        }
+
+        let (tholder, verfers) = signing_keys(serder)?;
+        let (ntholder, ndigs) = next_digests(serder)?;
+
+        // Witness cuts must come from the current witnesses, adds must be new
+        let cuts = field_list(serder, "br")?;
+        let adds = field_list(serder, "ba")?;
+        let witset = unique(&self.wits, "b")?;
+        let cutset = unique(&cuts, "br")?;
+        let addset = unique(&adds, "ba")?;
+        if !cutset.is_subset(&witset) || !addset.is_disjoint(&witset) {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Invalid witness cuts {:?} or adds {:?} for {}",
+                cuts,
+                adds,
+                self.pre()
+            )));
+        }
+        let wits: Vec<String> = self
+            .wits
+            .iter()
+            .filter(|w| !cutset.contains(w))
+            .chain(adds.iter())
+            .cloned()
+            .collect();
+        let toad = witness_toad(serder, wits.len())?;
+
+        let ser = serder.raw().as_bytes();
+        let verified = verify_threshold(ser, sigers, &verfers, &tholder)?;
+        self.verify_prior_next(&verified, &verfers)?;
+
+        self.serder = serder.clone();
+        self.sn = sn;
+        self.said = said.clone();
+        self.tholder = tholder;
+        self.verfers = verfers;
+        self.ntholder = ntholder;
+        self.ndigs = ndigs;
+        self.toad = toad;
+        self.wits = wits;
+        self.last_est = (sn, said);
+        self.cuts = cuts;
+        self.adds = adds;
+
+        Ok(verified)
+    }
+
+    /// Check that the new signing keys reveal enough of the prior next key
+    /// digests to satisfy the prior next threshold
+    fn verify_prior_next(&self, verified: &[Siger], verfers: &[Verfer]) -> Result<()> {
+        let ondices: Vec<u32> = self
+            .committed(verified, verfers)?
+            .iter()
+            .map(Siger::ondex)
+            .collect();
+        if !self.ntholder.satisfy(&ondices) {
+            return Err(SignifyError::VerificationFailed);
+        }
+
+        Ok(())
+    }
+
+    fn interact(
+        &mut self,
+        serder: &Serder,
+        sigers: &[Siger],
+        sn: u64,
+        said: String,
+    ) -> Result<Vec<Siger>> {
+        if self.estab_only() {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Interaction event for establishment only {}",
+                self.pre()
+            )));
+        }
+
+        let ser = serder.raw().as_bytes();
+        let verified = verify_threshold(ser, sigers, &self.verfers, &self.tholder)?;
+
+        self.serder = serder.clone();
+        self.sn = sn;
+        self.said = said;
+
+        Ok(verified)
+    }
+
+    /// Identifier prefix
+    pub fn pre(&self) -> &str {
+        self.prefixer.qb64()
+    }
+
+    /// Identifier prefixer
+    pub fn prefixer(&self) -> &Prefixer {
+        &self.prefixer
+    }
+
+    /// Latest accepted event
+    pub fn serder(&self) -> &Serder {
+        &self.serder
+    }
+
+    /// Sequence number of the latest event
+    pub fn sn(&self) -> u64 {
+        self.sn
+    }
+
+    /// SAID of the latest event
+    pub fn said(&self) -> &str {
+        &self.said
+    }
+
+    /// Signatures among `verified` made with keys this key state committed
+    /// to in its next key digests
+    ///
+    /// Each signature's ondex locates the digest of its key, the key at its
+    /// index in `verfers`, in the next key digests. Signatures by keys only in
+    /// the current key list (current only codes) are never committed.
+    pub fn committed(&self, verified: &[Siger], verfers: &[Verfer]) -> Result<Vec<Siger>> {
+        let mut committed = Vec::new();
+        for siger in verified {
+            if IndexerCodex::is_current_only(siger.code()) {
+                continue;
+            }
+            let (Some(ndig), Some(verfer)) = (
+                self.ndigs.get(siger.ondex() as usize),
+                verfers.get(siger.index() as usize),
+            ) else {
+                continue;
+            };
+            if Diger::from_qb64(ndig)?.verify(verfer.qb64b())? {
+                committed.push(siger.clone());
+            }
+        }
+        Ok(committed)
+    }
+
+    /// Current signing threshold
+    pub fn tholder(&self) -> &Tholder {
+        &self.tholder
+    }
+
+    /// Current signing keys
+    pub fn verfers(&self) -> &[Verfer] {
+        &self.verfers
+    }
+
+    /// Current signing keys as qb64
+    pub fn keys(&self) -> Vec<String> {
+        self.verfers.iter().map(|v| v.qb64().to_string()).collect()
+    }
+
+    /// Next signing threshold
+    pub fn ntholder(&self) -> &Tholder {
+        &self.ntholder
+    }
+
+    /// Next key digests
+    pub fn ndigs(&self) -> &[String] {
+        &self.ndigs
+    }
+
+    /// Witness threshold
+    pub fn toad(&self) -> usize {
+        self.toad
+    }
+
+    /// Current witnesses
+    pub fn wits(&self) -> &[String] {
+        &self.wits
+    }
+
+    /// Configuration traits from inception
+    pub fn cnfg(&self) -> &[String] {
+        &self.cnfg
+    }
+
+    /// Delegator prefix of a delegated identifier
+    pub fn delpre(&self) -> Option<&str> {
+        self.delpre.as_deref()
+    }
+
+    /// Sequence number and SAID of the latest establishment event
+    pub fn last_est(&self) -> (u64, &str) {
+        (self.last_est.0, &self.last_est.1)
+    }
+
+    /// Witness cuts and adds of the latest establishment event
+    pub fn last_est_wits(&self) -> (&[String], &[String]) {
+        (&self.cuts, &self.adds)
+    }
+
+    /// Key state record with the first-seen ordinal and datetime of the
+    /// latest event
+    pub fn state(&self, fnu: u64, dt: &str) -> KeyStateRecord {
+        KeyStateRecord {
+            vn: vec![1, 0],
+            i: self.pre().to_string(),
+            s: format!("{:x}", self.sn),
+            p: field_str(&self.serder, "p").unwrap_or_default().to_string(),
+            d: self.said.clone(),
+            f: format!("{:x}", fnu),
+            dt: dt.to_string(),
+            et: self.serder.ilk().unwrap_or_default().to_string(),
+            kt: self.tholder.sith(),
+            k: self.keys(),
+            nt: self.ntholder.sith(),
+            n: self.ndigs.clone(),
+            bt: format!("{:x}", self.toad),
+            b: self.wits.clone(),
+            c: self.cnfg.clone(),
+            ee: StateEstEvent {
+                s: format!("{:x}", self.last_est.0),
+                d: self.last_est.1.clone(),
+                br: self.cuts.clone(),
+                ba: self.adds.clone(),
+            },
+            di: self.delpre.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Whether only establishment events are allowed (EO)
+    pub fn estab_only(&self) -> bool {
+        self.cnfg.iter().any(|c| c == TraitCodex::EST_ONLY)
+    }
+
+    /// Whether the identifier may not act as a delegator (DND)
+    pub fn do_not_delegate(&self) -> bool {
+        self.cnfg.iter().any(|c| c == TraitCodex::DO_NOT_DELEGATE)
+    }
+
+    /// Whether the identifier can still rotate keys
+    ///
+    /// An empty next key digest list abandons the identifier.
+    pub fn transferable(&self) -> bool {
+        !self.ndigs.is_empty()
+    }
+}
+
+/// Sequence number of an event
+fn event_sn(serder: &Serder) -> Result<u64> {
+    serder.sn().ok_or_else(|| {
+        SignifyError::InvalidEvent(format!("Missing or invalid sn in {}", serder.raw()))
+    })
+}
+
+/// Check the SAID of an event and return it
+fn verify_said(serder: &Serder) -> Result<String> {
+    let said = field_str(serder, "d")?;
+    if serder.said(None)? != said {
+        return Err(SignifyError::InvalidEvent(format!(
+            "Invalid SAID {} for event",
+            said
+        )));
+    }
+    Ok(said.to_string())
+}
+
+fn field_str<'a>(serder: &'a Serder, label: &str) -> Result<&'a str> {
+    serder
+        .sad()
+        .get(label)
+        .and_then(Value::as_str)
+        .ok_or_else(|| SignifyError::InvalidEvent(format!("Missing or invalid field {}", label)))
+}
+
+fn field_list(serder: &Serder, label: &str) -> Result<Vec<String>> {
+    serder
+        .sad()
+        .get(label)
+        .and_then(Value::as_array)
+        .and_then(|items| {
+            items
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect()
+        })
+        .ok_or_else(|| SignifyError::InvalidEvent(format!("Missing or invalid field {}", label)))
+}
+
+fn unique<'a>(items: &'a [String], label: &str) -> Result<HashSet<&'a String>> {
+    let mut set = HashSet::new();
+    for item in items {
+        if !set.insert(item) {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Duplicate {} in field {}",
+                item, label
+            )));
+        }
+    }
+    Ok(set)
+}
+
+/// Signing threshold and keys of an establishment event
+fn signing_keys(serder: &Serder) -> Result<(Tholder, Vec<Verfer>)> {
+    let keys = field_list(serder, "k")?;
+    if keys.is_empty() {
+        return Err(SignifyError::InvalidEvent(
+            "Empty signing key list".to_string(),
+        ));
+    }
+    let verfers = keys
+        .iter()
+        .map(|k| Verfer::from_qb64(k))
+        .collect::<Result<Vec<_>>>()?;
+
+    let tholder = Tholder::new(serder.sad().get("kt").unwrap_or(&Value::Null))?;
+    if tholder.num() == Some(0) || tholder.size() > verfers.len() {
+        return Err(SignifyError::InvalidEvent(format!(
+            "Invalid signing threshold {} for {} keys",
+            tholder.json(),
+            verfers.len()
+        )));
+    }
+
+    Ok((tholder, verfers))
+}
+
+/// Next threshold and key digests of an establishment event
+fn next_digests(serder: &Serder) -> Result<(Tholder, Vec<String>)> {
+    let ndigs = field_list(serder, "n")?;
+    for ndig in &ndigs {
+        Diger::from_qb64(ndig)?;
+    }
+
+    let ntholder = Tholder::new(serder.sad().get("nt").unwrap_or(&Value::Null))?;
+    if ntholder.size() > ndigs.len() {
+        return Err(SignifyError::InvalidEvent(format!(
+            "Invalid next threshold {} for {} next keys",
+            ntholder.json(),
+            ndigs.len()
+        )));
+    }
+
+    Ok((ntholder, ndigs))
+}
+
+/// Witness threshold of an establishment event for `nwits` witnesses
+fn witness_toad(serder: &Serder, nwits: usize) -> Result<usize> {
+    let bt = field_str(serder, "bt")?;
+    let toad = usize::from_str_radix(bt, 16)
+        .map_err(|_| SignifyError::InvalidEvent(format!("Invalid witness threshold {}", bt)))?;
+
+    let valid = if nwits == 0 {
+        toad == 0
+    } else {
+        (1..=nwits).contains(&toad)
+    };
+    if !valid {
+        return Err(SignifyError::InvalidEvent(format!(
+            "Invalid toad {} for {} witnesses",
+            toad, nwits
+        )));
+    }
+
+    Ok(toad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::codes::matter_codes;
+    use crate::core::eventing::{incept, interact, rotate, RotateArgs};
+    use crate::core::signer::Signer;
+    use crate::core::test_support::{digs, icp, keys, sign, signers};
+
+    #[test]
+    fn test_kever_kel() {
+        let (s0, s1, s2) = (signers(2, true), signers(2, true), signers(2, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+
+        let (mut kever, verified) = Kever::new(&icp, &sign(&icp, &s0)).unwrap();
+        assert_eq!(verified.len(), 2);
+        assert_eq!(kever.pre(), icp.pre().unwrap());
+        assert_eq!(kever.sn(), 0);
+        assert_eq!(kever.keys(), keys(&s0));
+        assert_eq!(kever.ndigs(), digs(&s1));
+        assert_eq!(kever.toad(), 0);
+        assert!(kever.wits().is_empty());
+
+        let pre = kever.pre().to_string();
+        let ixn = interact(&pre, kever.said(), 1, None, None).unwrap();
+        kever.update(&ixn, &sign(&ixn, &s0)).unwrap();
+        assert_eq!(kever.sn(), 1);
+        assert_eq!(kever.last_est().0, 0);
+
+        let rot = rotate(RotateArgs {
+            pre: pre.clone(),
+            keys: keys(&s1),
+            dig: kever.said().to_string(),
+            sn: 2,
+            ndigs: digs(&s2),
+            toad: Some(1),
+            adds: vec!["BWit".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        kever.update(&rot, &sign(&rot, &s1)).unwrap();
+        assert_eq!(kever.sn(), 2);
+        assert_eq!(kever.keys(), keys(&s1));
+        assert_eq!(kever.ndigs(), digs(&s2));
+        assert_eq!(kever.wits(), ["BWit"]);
+        assert_eq!(kever.toad(), 1);
+        assert_eq!(kever.last_est(), (2, rot.said_field().unwrap()));
+    }
+
+    #[test]
+    fn test_kever_basic_prefix() {
+        for code in [
+            matter_codes::ECDSA_256K1_SEED,
+            matter_codes::ECDSA_256R1_SEED,
+            matter_codes::ED448_SEED,
+        ] {
+            for transferable in [true, false] {
+                let current = [Signer::new_random(code, transferable).unwrap()];
+                let next = [Signer::new_random(code, true).unwrap()];
+                let ndigs = if transferable { digs(&next) } else { vec![] };
+                let icp = incept(
+                    keys(&current),
+                    None,
+                    ndigs,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    None,
+                )
+                .unwrap();
+                let pre = current[0].verfer().qb64();
+                assert_eq!(icp.pre(), Some(pre));
+
+                let siger = current[0]
+                    .siger(icp.raw().as_bytes(), 0, false, None)
+                    .unwrap();
+                let (mut kever, verified) = Kever::new(&icp, &[siger]).unwrap();
+                assert_eq!(verified.len(), 1);
+                assert_eq!(kever.pre(), pre);
+                let derivation = kever.prefixer().derivation();
+                assert!(!derivation.is_digestive());
+                assert_eq!(derivation.is_non_transferable(), !transferable);
+                if !transferable {
+                    continue;
+                }
+
+                let rot = rotate(RotateArgs {
+                    pre: pre.to_string(),
+                    keys: keys(&next),
+                    dig: kever.said().to_string(),
+                    sn: 1,
+                    ..Default::default()
+                })
+                .unwrap();
+                let siger = next[0].siger(rot.raw().as_bytes(), 0, false, None).unwrap();
+                kever.update(&rot, &[siger]).unwrap();
+                assert_eq!(kever.keys(), keys(&next));
+            }
+        }
+    }
+
+    #[test]
+    fn test_kever_invalid() {
+        let (s0, s1, s2) = (signers(1, true), signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+
+        // Signatures by keys other than the current keys
+        assert!(Kever::new(&icp, &sign(&icp, &s1)).is_err());
+        assert!(Kever::new(&icp, &[]).is_err());
+
+        let (mut kever, _) = Kever::new(&icp, &sign(&icp, &s0)).unwrap();
+        let pre = kever.pre().to_string();
+        let said = kever.said().to_string();
+
+        // Wrong sn and wrong prior digest
+        let ixn = interact(&pre, &said, 2, None, None).unwrap();
+        assert!(kever.update(&ixn, &sign(&ixn, &s0)).is_err());
+        let ixn = interact(&pre, "EBadPrior", 1, None, None).unwrap();
+        assert!(kever.update(&ixn, &sign(&ixn, &s0)).is_err());
+
+        // Tampered event fails its SAID
+        let ixn = interact(&pre, &said, 1, None, None).unwrap();
+        let mut sad = ixn.sad().clone();
+        sad["a"] = serde_json::json!([{"d": "EX"}]);
+        let tampered = Serder::new(sad, None, None).unwrap();
+        assert!(kever.update(&tampered, &sign(&tampered, &s0)).is_err());
+
+        // Rotation to keys not committed to by the prior next digests
+        let rot = |new: &[Signer]| {
+            rotate(RotateArgs {
+                pre: pre.clone(),
+                keys: keys(new),
+                dig: said.clone(),
+                sn: 1,
+                ndigs: digs(&s2),
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let bad = rot(&s2);
+        assert!(kever.update(&bad, &sign(&bad, &s2)).is_err());
+        assert_eq!(kever.sn(), 0);
+
+        let good = rot(&s1);
+        kever.update(&good, &sign(&good, &s1)).unwrap();
+        assert_eq!(kever.sn(), 1);
+    }
+
+    #[test]
+    fn test_kever_config_traits() {
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let cnfg = vec![
+            TraitCodex::EST_ONLY.to_string(),
+            TraitCodex::DO_NOT_DELEGATE.to_string(),
+        ];
+        let icp = icp(&s0, &s1, &[], Some(cnfg), None);
+        let (mut kever, _) = Kever::new(&icp, &sign(&icp, &s0)).unwrap();
+        assert!(kever.estab_only());
+        assert!(kever.do_not_delegate());
+
+        let ixn = interact(kever.pre(), kever.said(), 1, None, None).unwrap();
+        assert!(kever.update(&ixn, &sign(&ixn, &s0)).is_err());
+    }
+}
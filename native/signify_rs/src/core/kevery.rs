@@ -0,0 +1,2361 @@
+//! Kevery - Key event processor
+//!
+//! Kevery validates key events from parsed CESR messages into the key state
+//! of their identifiers. Events that cannot be accepted yet are escrowed:
+//! events ahead of the key state, events missing controller signatures or
+//! witness signatures, and delegated events whose delegator has not anchored
+//! them yet. Escrows are indexed by the prefix and sn of the event, so an
+//! accepted event retries only the escrowed events it may unblock: those of
+//! its identifier at its sn and the next one, and the delegated events of
+//! its identifier waiting for an anchor. Escrowed events are dropped once
+//! they are older than the escrow timeout, and the oldest first once more
+//! than the escrow limit are waiting.
+//!
+//! An event is fully witnessed once distinct witnesses of the key state it
+//! results in have receipted it up to the witness threshold `bt`, either
+//! with indexed witness signatures (`-B`) or with non-transferable receipt
+//! couples (`-C`) attached to the event or sent in a receipt (`rct`)
+//! message. Verified receipts are kept with the accepted event, receipt
+//! couples of witnesses as witness signatures indexed by the witness list as
+//! keripy does, and receipts for an event not seen yet wait in escrow for it.
+//! Transferable receipts of validators, attached as quadruples (`-D`) or sent
+//! as signature groups (`-F`) in a receipt message, are kept once they verify
+//! against the establishment event of the validator they name. Those of
+//! validators whose establishment event is not accepted yet are dropped.
+//!
+//! Delegated events are accepted once their delegator's KEL anchors them in
+//! an event seal, found through an attached seal source couple (`-G`) or by
+//! searching the delegator's KEL. A rotation may supersede the accepted
+//! event at its sn to recover from key compromise: any rotation supersedes
+//! interaction events after the latest establishment event, and a delegated
+//! rotation supersedes another one its delegator anchored earlier.
+//!
+//! With a `Baser` attached, accepted events with their signatures and
+//! receipts are logged to the database and the key state is rebuilt from it
+//! on startup, and so are likely duplicitous events and accepted key state
+//! notices. Escrows are kept in memory only: they wait for the escrow
+//! timeout at most, and the senders of escrowed events deliver them again
+//! after a restart.
+//!
+//! The verified key state after each accepted event is kept with the KEL, so
+//! checking an event or signatures against an earlier key state never
+//! replays the KEL.
+//!
+//! The key state record of an identifier carries the first-seen ordinal and
+//! datetime of its latest event. Key state notices (`/ksn` replies) from
+//! other sources are accepted when signed by their source and fresher than
+//! the notice accepted from it before.
+//!
+//! A validly signed event that conflicts with the accepted event at the same
+//! sn forks the KEL. Both versions are kept in the likely duplicitous event
+//! log and processing reports `SignifyError::DuplicitousEvent`.
+
+use crate::cesr_parser::{Attachments, Message, TransReceipt};
+use crate::core::bada::{fresher, parse_dt};
+use crate::core::basing::Baser;
+use crate::core::cigar::Cigar;
+use crate::core::codes::matter_codes;
+use crate::core::eventing::{
+    verify_sigs, verify_threshold, ILK_DIP, ILK_DRT, ILK_ICP, ILK_IXN, ILK_RCT, ILK_ROT, ILK_RPY,
+    ROUTE_KSN_REPLY,
+};
+use crate::core::indexer::IndexerCodex;
+use crate::core::kever::Kever;
+use crate::core::seal::{Seal, SealEvent, SealSource};
+use crate::core::serder::Serder;
+use crate::core::siger::Siger;
+use crate::core::state::KeyStateRecord;
+use crate::core::utils::now_iso8601;
+use crate::core::verfer::Verfer;
+use crate::error::{Result, SignifyError};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Default time an event may wait in escrow
+pub const DEFAULT_ESCROW_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// Default number of events that may wait in escrow
+pub const DEFAULT_ESCROW_LIMIT: usize = 10_000;
+
+/// Escrow an event waits in until it can be accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Escrow {
+    /// Prior events of the identifier are missing
+    OutOfOrder,
+    /// Controller signatures do not satisfy the signing thresholds yet
+    PartialSigs,
+    /// Witness signatures do not satisfy the witness threshold yet
+    PartialWitness,
+    /// Delegator has not anchored the delegated event yet
+    Delegation,
+    /// Receipted event has not been seen yet
+    UnverifiedReceipt,
+}
+
+/// Outcome of processing an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Processed {
+    /// Event was accepted into the key state, or receipts were added to an
+    /// accepted event
+    Accepted,
+    /// Event was accepted before
+    Duplicate,
+    /// Event waits in escrow
+    Escrowed(Escrow),
+}
+
+/// Accepted key event with its signatures
+#[derive(Debug, Clone)]
+pub struct KelEvent {
+    /// Key event
+    pub serder: Serder,
+    /// Controller indexed signatures
+    pub sigers: Vec<Siger>,
+    /// Witness indexed signatures
+    pub wigers: Vec<Siger>,
+    /// Non-transferable receipt couples, each cigar carrying the receiptor
+    /// verfer. Once accepted, only those of receiptors other than witnesses
+    pub cigars: Vec<Cigar>,
+    /// Transferable receipt quadruples of validators. Once accepted, only
+    /// those that verify against the validator's establishment event
+    pub trans_receipts: Vec<TransReceipt>,
+    /// Delegator's event anchoring a delegated event
+    pub source: Option<SealSource>,
+}
+
+/// Likely duplicitous event with the accepted event it conflicts with
+#[derive(Debug, Clone)]
+pub struct Duplicity {
+    /// Accepted event at the sn with its signatures
+    pub accepted: KelEvent,
+    /// Conflicting event with its signatures
+    pub duplicitous: KelEvent,
+    /// When the conflicting event was first seen
+    pub dt: String,
+}
+
+/// Signatures verified against the key state of an identifier
+#[derive(Debug, Clone)]
+pub struct AidVerification {
+    /// Signatures by current signing keys, satisfying the signing threshold
+    pub current: Vec<Siger>,
+    /// Current signatures whose keys the prior establishment event committed
+    /// to in its next key digests, located by ondex
+    pub prior_next: Vec<Siger>,
+}
+
+/// Key state notice accepted from a source
+#[derive(Debug, Clone)]
+pub struct KeyStateNotice {
+    /// Key state record of the notice
+    pub record: KeyStateRecord,
+    /// Identifier of the source that signed the notice
+    pub src: String,
+    /// Datetime of the reply
+    pub dt: String,
+    /// SAID of the reply
+    pub said: String,
+    /// Sn of the source's establishment event the signatures verified
+    /// against, `None` for non-transferable sources
+    pub est_sn: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+struct Escrowed {
+    escrow: Escrow,
+    event: KelEvent,
+    stamp: Instant,
+}
+
+/// Prefix and sn of an event or receipt, the index of escrowed events
+type EscrowKey = (String, u64);
+
+fn escrow_key(serder: &Serder) -> EscrowKey {
+    (
+        serder.pre().unwrap_or_default().to_string(),
+        serder.sn().unwrap_or_default(),
+    )
+}
+
+/// Escrowed events indexed by prefix and sn
+#[derive(Debug, Default)]
+struct Escrows {
+    events: BTreeMap<EscrowKey, Vec<Escrowed>>,
+    len: usize,
+}
+
+impl Escrows {
+    /// Escrow an event, dropping the oldest escrowed events beyond `limit`
+    fn insert(&mut self, escrowed: Escrowed, limit: usize) {
+        self.restore(escrowed);
+        while self.len > limit {
+            let oldest = self
+                .events
+                .iter()
+                .flat_map(|(key, events)| {
+                    events.iter().enumerate().map(move |(at, e)| (key, at, e))
+                })
+                .min_by_key(|(_, _, e)| e.stamp)
+                .map(|(key, at, _)| (key.clone(), at));
+            let Some((key, at)) = oldest else {
+                break;
+            };
+            let events = self.events.get_mut(&key).expect("escrowed events");
+            events.remove(at);
+            if events.is_empty() {
+                self.events.remove(&key);
+            }
+            self.len -= 1;
+        }
+    }
+
+    /// Put back an event taken from escrow
+    fn restore(&mut self, escrowed: Escrowed) {
+        let key = escrow_key(&escrowed.event.serder);
+        self.events.entry(key).or_default().push(escrowed);
+        self.len += 1;
+    }
+
+    /// Take the escrowed events at `key` matching `take`
+    fn remove(&mut self, key: &EscrowKey, take: impl Fn(&Escrowed) -> bool) -> Vec<Escrowed> {
+        let Some(events) = self.events.remove(key) else {
+            return Vec::new();
+        };
+        let (taken, kept): (Vec<_>, Vec<_>) = events.into_iter().partition(take);
+        self.len -= taken.len();
+        if !kept.is_empty() {
+            self.events.insert(key.clone(), kept);
+        }
+        taken
+    }
+
+    fn find(&self, key: &EscrowKey, find: impl Fn(&Escrowed) -> bool) -> Option<&Escrowed> {
+        self.events.get(key)?.iter().find(|e| find(e))
+    }
+
+    fn find_mut(
+        &mut self,
+        key: &EscrowKey,
+        find: impl Fn(&Escrowed) -> bool,
+    ) -> Option<&mut Escrowed> {
+        self.events.get_mut(key)?.iter_mut().find(|e| find(e))
+    }
+
+    fn retain(&mut self, keep: impl Fn(&Escrowed) -> bool) {
+        for events in self.events.values_mut() {
+            events.retain(&keep);
+        }
+        self.events.retain(|_, events| !events.is_empty());
+        self.len = self.events.values().map(Vec::len).sum();
+    }
+
+    fn keys(&self) -> Vec<EscrowKey> {
+        self.events.keys().cloned().collect()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Escrowed> {
+        self.events.values().flatten()
+    }
+}
+
+/// Key event processor holding the key state of every identifier it has
+/// seen events for
+#[derive(Debug)]
+pub struct Kevery {
+    kels: HashMap<String, Vec<KelEvent>>,
+    /// Key state after each accepted event, the last one the current state
+    states: HashMap<String, Vec<Kever>>,
+    escrows: Escrows,
+    ldes: HashMap<String, Vec<Duplicity>>,
+    fels: HashMap<String, Vec<(String, String)>>,
+    ksns: HashMap<(String, String), KeyStateNotice>,
+    timeout: Duration,
+    limit: usize,
+    db: Option<Baser>,
+}
+
+impl Default for Kevery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Kevery {
+    /// Create processor with the default escrow timeout and limit
+    pub fn new() -> Self {
+        Self {
+            kels: HashMap::new(),
+            states: HashMap::new(),
+            escrows: Escrows::default(),
+            ldes: HashMap::new(),
+            fels: HashMap::new(),
+            ksns: HashMap::new(),
+            timeout: DEFAULT_ESCROW_TIMEOUT,
+            limit: DEFAULT_ESCROW_LIMIT,
+            db: None,
+        }
+    }
+
+    /// Drop escrowed events older than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Keep at most `limit` events in escrow, dropping the oldest first
+    pub fn with_escrow_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Create processor logging accepted events to `db`, with the key state
+    /// of every identifier in it rebuilt by replaying its stored KEL
+    pub fn with_baser(db: Baser) -> Result<Self> {
+        let mut kevery = Self::new();
+        for pre in db.prefixes()? {
+            let kel = db.kel(&pre, ..)?;
+            let Some((first, rest)) = kel.split_first() else {
+                continue;
+            };
+            let (mut kever, _) = Kever::new(&first.serder, &first.sigers)?;
+            let mut states = vec![kever.clone()];
+            for event in rest {
+                kever.update(&event.serder, &event.sigers)?;
+                states.push(kever.clone());
+            }
+            let fel = db.fel(&pre, ..)?;
+            kevery.fels.insert(
+                pre.clone(),
+                fel.into_iter()
+                    .map(|seen| {
+                        (
+                            seen.event
+                                .serder
+                                .said_field()
+                                .unwrap_or_default()
+                                .to_string(),
+                            seen.dt,
+                        )
+                    })
+                    .collect(),
+            );
+            let ldes = db.duplicity(&pre)?;
+            if !ldes.is_empty() {
+                kevery.ldes.insert(pre.clone(), ldes);
+            }
+            kevery.states.insert(pre.clone(), states);
+            kevery.kels.insert(pre, kel);
+        }
+        for notice in db.ksns()? {
+            let key = (notice.record.i.clone(), notice.src.clone());
+            kevery.ksns.insert(key, notice);
+        }
+        kevery.db = Some(db);
+        Ok(kevery)
+    }
+
+    /// Process a key event or receipt message from the CESR parser
+    pub fn process_message(&mut self, message: &Message) -> Result<Processed> {
+        let cigars = message
+            .attachments
+            .receipts
+            .iter()
+            .map(|receipt| {
+                let mut cigar = receipt.cigar.clone();
+                if cigar.verfer().is_none() {
+                    cigar.set_verfer(Some(Verfer::from_qb64(receipt.prefixer.qb64())?));
+                }
+                Ok(cigar)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut trans_receipts = message.attachments.trans_receipts.clone();
+        if message.serder.ilk() == Some(ILK_RCT) {
+            for tsg in &message.attachments.trans_sig_groups {
+                trans_receipts.extend(tsg.sigers.iter().map(|siger| TransReceipt {
+                    prefixer: tsg.prefixer.clone(),
+                    seqner: tsg.seqner.clone(),
+                    diger: tsg.diger.clone(),
+                    siger: siger.clone(),
+                }));
+            }
+            return self.receive(
+                &message.serder,
+                &message.attachments.wigers,
+                &cigars,
+                &trans_receipts,
+            );
+        }
+
+        let source = message
+            .attachments
+            .seal_sources
+            .last()
+            .map(|(seqner, diger)| SealSource {
+                s: seqner.snh(),
+                d: diger.qb64().to_string(),
+            });
+        self.process(KelEvent {
+            serder: message.serder.clone(),
+            sigers: message.attachments.sigers.clone(),
+            wigers: message.attachments.wigers.clone(),
+            cigars,
+            trans_receipts,
+            source,
+        })
+    }
+
+    /// Process a key event with its controller and witness signatures and,
+    /// for delegated events, the delegator's anchoring event
+    ///
+    /// Signatures for an event already in escrow are merged with the escrowed
+    /// ones. Invalid events are rejected with an error.
+    pub fn process_event(
+        &mut self,
+        serder: &Serder,
+        sigers: &[Siger],
+        wigers: &[Siger],
+        source: Option<&SealSource>,
+    ) -> Result<Processed> {
+        self.process(KelEvent {
+            serder: serder.clone(),
+            sigers: sigers.to_vec(),
+            wigers: wigers.to_vec(),
+            cigars: Vec::new(),
+            trans_receipts: Vec::new(),
+            source: source.cloned(),
+        })
+    }
+
+    /// Process witness receipts of an event sent in a receipt message
+    ///
+    /// Receipts for an escrowed event are merged with it, receipts for an
+    /// accepted event are verified and kept with it, and receipts for an
+    /// event not seen yet wait in escrow for it.
+    pub fn process_receipt(
+        &mut self,
+        serder: &Serder,
+        wigers: &[Siger],
+        cigars: &[Cigar],
+    ) -> Result<Processed> {
+        self.receive(serder, wigers, cigars, &[])
+    }
+
+    /// Process witness and validator receipts of an event sent in a receipt
+    /// message
+    fn receive(
+        &mut self,
+        serder: &Serder,
+        wigers: &[Siger],
+        cigars: &[Cigar],
+        trans_receipts: &[TransReceipt],
+    ) -> Result<Processed> {
+        if serder.ilk() != Some(ILK_RCT) {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Invalid ilk {} for receipt",
+                serder.ilk().unwrap_or_default()
+            )));
+        }
+        let pre = serder
+            .pre()
+            .ok_or_else(|| SignifyError::InvalidEvent("Missing prefix".to_string()))?;
+        let sn = serder
+            .sn()
+            .ok_or_else(|| SignifyError::InvalidEvent(format!("Missing sn for {}", pre)))?;
+        let said = serder.said_field().unwrap_or_default();
+        let receipted =
+            |e: &Serder| e.pre() == Some(pre) && e.sn() == Some(sn) && e.said_field() == Some(said);
+        let key = (pre.to_string(), sn);
+
+        let receipt = KelEvent {
+            serder: serder.clone(),
+            sigers: Vec::new(),
+            wigers: wigers.to_vec(),
+            cigars: cigars.to_vec(),
+            trans_receipts: trans_receipts.to_vec(),
+            source: None,
+        };
+
+        if let Some(escrowed) = self.escrows.find(&key, |e| {
+            e.escrow != Escrow::UnverifiedReceipt && receipted(&e.event.serder)
+        }) {
+            return self.process(KelEvent {
+                serder: escrowed.event.serder.clone(),
+                ..receipt
+            });
+        }
+
+        if let Some(at) = self.kel(pre).iter().position(|e| receipted(&e.serder)) {
+            self.add_attachments(pre, at, &receipt)?;
+            return Ok(Processed::Accepted);
+        }
+
+        match self.escrows.find_mut(&key, |e| {
+            e.escrow == Escrow::UnverifiedReceipt && e.event.serder.raw() == serder.raw()
+        }) {
+            Some(escrowed) => {
+                merge(&mut escrowed.event.wigers, wigers);
+                merge_cigars(&mut escrowed.event.cigars, cigars);
+                merge_trans_receipts(&mut escrowed.event.trans_receipts, trans_receipts);
+            }
+            None => self.escrows.insert(
+                Escrowed {
+                    escrow: Escrow::UnverifiedReceipt,
+                    event: receipt,
+                    stamp: Instant::now(),
+                },
+                self.limit,
+            ),
+        }
+        Ok(Processed::Escrowed(Escrow::UnverifiedReceipt))
+    }
+
+    /// Process an event, merging it with the escrowed version of it and any
+    /// escrowed receipts for it
+    fn process(&mut self, incoming: KelEvent) -> Result<Processed> {
+        let serder = &incoming.serder;
+        let mut event = KelEvent {
+            serder: serder.clone(),
+            sigers: Vec::new(),
+            wigers: Vec::new(),
+            cigars: Vec::new(),
+            trans_receipts: Vec::new(),
+            source: None,
+        };
+        let mut stamp = Instant::now();
+
+        // Escrowed copies are only put back in place of the merged event, so
+        // a copy failing to process must not cost them
+        let key = escrow_key(serder);
+        let escrowed = self.escrows.remove(&key, |e| {
+            e.escrow != Escrow::UnverifiedReceipt && e.event.serder.raw() == serder.raw()
+        });
+        if let Some(escrowed) = escrowed.first() {
+            event = escrowed.event.clone();
+            stamp = escrowed.stamp;
+        }
+        let receipts = self.escrows.remove(&key, |e| {
+            e.escrow == Escrow::UnverifiedReceipt
+                && e.event.serder.said_field() == serder.said_field()
+        });
+        for escrowed in &receipts {
+            merge(&mut event.wigers, &escrowed.event.wigers);
+            merge_cigars(&mut event.cigars, &escrowed.event.cigars);
+            merge_trans_receipts(&mut event.trans_receipts, &escrowed.event.trans_receipts);
+        }
+        merge(&mut event.sigers, &incoming.sigers);
+        merge(&mut event.wigers, &incoming.wigers);
+        merge_cigars(&mut event.cigars, &incoming.cigars);
+        merge_trans_receipts(&mut event.trans_receipts, &incoming.trans_receipts);
+        if incoming.source.is_some() {
+            event.source = incoming.source;
+        }
+
+        let processed = match self.evaluate(&event) {
+            Ok(processed) => processed,
+            Err(err) => {
+                for taken in escrowed.into_iter().chain(receipts) {
+                    self.escrows.restore(taken);
+                }
+                return Err(err);
+            }
+        };
+        match processed {
+            Processed::Accepted => {
+                let unblocked = self.unblocked(&key);
+                self.retry(unblocked);
+            }
+            Processed::Escrowed(escrow) => self.escrows.insert(
+                Escrowed {
+                    escrow,
+                    event,
+                    stamp,
+                },
+                self.limit,
+            ),
+            Processed::Duplicate => {}
+        }
+
+        Ok(processed)
+    }
+
+    /// Retry every escrowed event, dropping those that timed out or turned
+    /// out invalid
+    pub fn process_escrows(&mut self) {
+        let timeout = self.timeout;
+        self.escrows.retain(|e| e.stamp.elapsed() < timeout);
+        let keys = self.escrows.keys();
+        self.retry(keys);
+    }
+
+    /// Retry the escrowed events at `keys` and, for each one accepted, those
+    /// it unblocks in turn
+    fn retry(&mut self, mut keys: Vec<EscrowKey>) {
+        while let Some(key) = keys.pop() {
+            let escrowed = self
+                .escrows
+                .remove(&key, |e| e.escrow != Escrow::UnverifiedReceipt);
+            for mut escrowed in escrowed {
+                if escrowed.stamp.elapsed() >= self.timeout {
+                    continue;
+                }
+                match self.evaluate(&escrowed.event) {
+                    Ok(Processed::Escrowed(escrow)) => {
+                        escrowed.escrow = escrow;
+                        self.escrows.restore(escrowed);
+                    }
+                    Ok(Processed::Accepted) => keys.extend(self.unblocked(&key)),
+                    Ok(Processed::Duplicate) | Err(_) => {}
+                }
+            }
+        }
+    }
+
+    /// Escrow keys of the events that acceptance of an event at `key` may
+    /// unblock: events of its identifier at its sn and the next one, and
+    /// delegated events of its identifier waiting for an anchor
+    fn unblocked(&self, key: &EscrowKey) -> Vec<EscrowKey> {
+        let (pre, sn) = key;
+        let mut keys = vec![key.clone()];
+        if let Some(next) = sn.checked_add(1) {
+            keys.push((pre.clone(), next));
+        }
+        for escrowed in self.escrows.iter() {
+            let serder = &escrowed.event.serder;
+            if escrowed.escrow == Escrow::Delegation && self.delegator(serder) == Some(pre.as_str())
+            {
+                keys.push(escrow_key(serder));
+            }
+        }
+        keys
+    }
+
+    /// Delegator of a delegated event, from the event for an inception and
+    /// from the key state of the delegate for a rotation
+    fn delegator<'a>(&'a self, serder: &'a Serder) -> Option<&'a str> {
+        match serder.ilk() {
+            Some(ILK_DIP) => serder.sad()["di"].as_str(),
+            _ => self.kever(serder.pre()?)?.delpre(),
+        }
+    }
+
+    /// Validate an event against the current key state and accept it when
+    /// nothing is missing
+    fn evaluate(&mut self, event: &KelEvent) -> Result<Processed> {
+        let serder = &event.serder;
+        let ilk = serder.ilk().unwrap_or_default();
+        if ![ILK_ICP, ILK_DIP, ILK_ROT, ILK_DRT, ILK_IXN].contains(&ilk) {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Invalid ilk {} for key event",
+                ilk
+            )));
+        }
+        let pre = serder
+            .pre()
+            .ok_or_else(|| SignifyError::InvalidEvent("Missing prefix".to_string()))?;
+        let sn = serder
+            .sn()
+            .ok_or_else(|| SignifyError::InvalidEvent(format!("Missing sn for {}", pre)))?;
+
+        let result = match self.kever(pre) {
+            None if sn > 0 => return Ok(Processed::Escrowed(Escrow::OutOfOrder)),
+            None => Kever::new(serder, &event.sigers),
+            Some(kever) if sn <= kever.sn() => return self.accepted(event, sn),
+            Some(kever) if sn > kever.sn() + 1 => {
+                return Ok(Processed::Escrowed(Escrow::OutOfOrder))
+            }
+            Some(kever) => {
+                let mut kever = kever.clone();
+                kever
+                    .update(serder, &event.sigers)
+                    .map(|verified| (kever, verified))
+            }
+        };
+        let (kever, verified) = match result {
+            Ok(result) => result,
+            Err(SignifyError::VerificationFailed) => {
+                return Ok(Processed::Escrowed(Escrow::PartialSigs))
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut event = receipted(&kever, event)?;
+        event.sigers = verified;
+        event.trans_receipts = self.endorsed(&event.serder, &event.trans_receipts)?;
+        if let Some(escrow) = self.missing(&kever, &event)? {
+            return Ok(Processed::Escrowed(escrow));
+        }
+
+        self.log_first_seen(&event)?;
+        self.states.entry(pre.to_string()).or_default().push(kever);
+        self.kels.entry(pre.to_string()).or_default().push(event);
+
+        Ok(Processed::Accepted)
+    }
+
+    /// Escrow for what a validly signed event with verified receipts still
+    /// lacks: its delegator's anchor or witness receipts
+    fn missing(&self, kever: &Kever, event: &KelEvent) -> Result<Option<Escrow>> {
+        let ilk = event.serder.ilk().unwrap_or_default();
+        if (ilk == ILK_DIP || ilk == ILK_DRT) && self.delegation(kever, event)?.is_none() {
+            return Ok(Some(Escrow::Delegation));
+        }
+
+        if !witnessed(kever, event) {
+            return Ok(Some(Escrow::PartialWitness));
+        }
+
+        Ok(None)
+    }
+
+    /// Position of the delegator's event anchoring a delegated event in the
+    /// delegator's KEL, if anchored yet
+    fn delegation(&self, kever: &Kever, event: &KelEvent) -> Result<Option<usize>> {
+        let delpre = kever.delpre().unwrap_or_default();
+        let Some(delegator) = self.kever(delpre) else {
+            return Ok(None);
+        };
+        if delegator.do_not_delegate() {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Delegator {} of {} does not allow delegation",
+                delpre,
+                kever.pre()
+            )));
+        }
+
+        let serder = &event.serder;
+        let seal = Seal::Event(SealEvent::new(
+            kever.pre(),
+            serder.sn().unwrap_or_default(),
+            serder.said_field().unwrap_or_default(),
+        ));
+        let anchors = |e: &KelEvent| e.serder.seals().contains(&seal);
+
+        let kel = self.kel(delpre);
+        let at = match &event.source {
+            Some(source) => kel.iter().position(|e| {
+                e.serder.said_field() == Some(source.d.as_str())
+                    && e.serder.sn().map(|sn| format!("{:x}", sn)).as_ref() == Some(&source.s)
+                    && anchors(e)
+            }),
+            None => kel.iter().position(anchors),
+        };
+
+        Ok(at)
+    }
+
+    /// Handle an event at or below the current sn, adding any new signatures
+    /// when it is the accepted event and logging it when it conflicts
+    fn accepted(&mut self, event: &KelEvent, sn: u64) -> Result<Processed> {
+        let serder = &event.serder;
+        let pre = serder.pre().unwrap_or_default().to_string();
+        let kel = self.kels.entry(pre.clone()).or_default();
+        let at = kel
+            .iter()
+            .rposition(|e| e.serder.sn() == Some(sn))
+            .ok_or_else(|| {
+                SignifyError::InvalidEvent(format!("Missing accepted event for {} at {}", pre, sn))
+            })?;
+
+        if kel[at].serder.raw() == serder.raw() {
+            self.add_attachments(&pre, at, event)?;
+            return Ok(Processed::Duplicate);
+        }
+
+        if let Some(processed) = self.supersede(event, at)? {
+            return Ok(processed);
+        }
+
+        // Only a conflicting event valid against the key state before it is
+        // evidence of duplicity, anything else is just invalid
+        match self.state_before(&pre, at) {
+            None => Kever::new(serder, &event.sigers).map(|_| ())?,
+            Some(kever) => kever.clone().update(serder, &event.sigers).map(|_| ())?,
+        }
+
+        let accepted = self.kels[&pre][at].clone();
+        let ldes = self.ldes.entry(pre.clone()).or_default();
+        let duplicity = match ldes
+            .iter_mut()
+            .position(|d| d.duplicitous.serder.raw() == serder.raw())
+        {
+            Some(at) => {
+                let duplicity = &mut ldes[at];
+                merge(&mut duplicity.duplicitous.sigers, &event.sigers);
+                merge(&mut duplicity.duplicitous.wigers, &event.wigers);
+                merge_cigars(&mut duplicity.duplicitous.cigars, &event.cigars);
+                duplicity
+            }
+            None => {
+                ldes.push(Duplicity {
+                    accepted,
+                    duplicitous: event.clone(),
+                    dt: now_iso8601(),
+                });
+                ldes.last_mut().expect("logged duplicity")
+            }
+        };
+        if let Some(db) = &self.db {
+            db.log_duplicity(duplicity)?;
+        }
+
+        Err(SignifyError::DuplicitousEvent {
+            pre,
+            sn,
+            said: serder.said_field().unwrap_or_default().to_string(),
+        })
+    }
+
+    /// Recover with a rotation replacing the accepted event at `at` and every
+    /// event after it, when the superseding rules allow
+    fn supersede(&mut self, event: &KelEvent, at: usize) -> Result<Option<Processed>> {
+        let serder = &event.serder;
+        let ilk = serder.ilk().unwrap_or_default();
+        if ilk != ILK_ROT && ilk != ILK_DRT {
+            return Ok(None);
+        }
+        let pre = serder.pre().unwrap_or_default().to_string();
+        let sn = serder.sn().unwrap_or_default();
+        let Some(mut kever) = self.state_before(&pre, at).cloned() else {
+            return Ok(None);
+        };
+        let Some(current) = self.kever(&pre) else {
+            return Ok(None);
+        };
+        let superseded = &self.kel(&pre)[at];
+
+        // Interaction events after the latest establishment event give way
+        // to any rotation, a delegated rotation to one anchored later
+        let allowed = match superseded.serder.ilk() {
+            Some(ILK_IXN) => current.last_est().0 < sn,
+            Some(ILK_DRT) if ilk == ILK_DRT && current.last_est().0 == sn => {
+                let Some(prior) = self.delegation(current, superseded)? else {
+                    return Ok(None);
+                };
+                match self.delegation(current, event)? {
+                    Some(later) => later > prior,
+                    None => return Ok(Some(Processed::Escrowed(Escrow::Delegation))),
+                }
+            }
+            _ => false,
+        };
+        if !allowed {
+            return Ok(None);
+        }
+
+        match kever.update(serder, &event.sigers) {
+            Ok(_) => {}
+            Err(SignifyError::VerificationFailed) => {
+                return Ok(Some(Processed::Escrowed(Escrow::PartialSigs)))
+            }
+            Err(e) => return Err(e),
+        }
+        let mut event = receipted(&kever, event)?;
+        event.trans_receipts = self.endorsed(&event.serder, &event.trans_receipts)?;
+        if let Some(escrow) = self.missing(&kever, &event)? {
+            return Ok(Some(Processed::Escrowed(escrow)));
+        }
+
+        self.log_first_seen(&event)?;
+        let kel = self.kels.entry(pre.clone()).or_default();
+        kel.truncate(at);
+        kel.push(event);
+        let states = self.states.entry(pre).or_default();
+        states.truncate(at);
+        states.push(kever);
+
+        Ok(Some(Processed::Accepted))
+    }
+
+    /// Verify signatures and receipts of the accepted event at `at` against
+    /// the key state it resulted in and keep the valid ones with it
+    fn add_attachments(&mut self, pre: &str, at: usize, more: &KelEvent) -> Result<()> {
+        let kever = self
+            .states
+            .get(pre)
+            .and_then(|states| states.get(at))
+            .ok_or_else(|| {
+                SignifyError::InvalidEvent(format!("Missing accepted event for {}", pre))
+            })?;
+        let serder = &self.kel(pre)[at].serder;
+        let trans_receipts = self.endorsed(serder, &more.trans_receipts)?;
+        let event = &mut self.kels.get_mut(pre).expect("accepted KEL")[at];
+        let ser = event.serder.raw().as_bytes();
+        let verified = KelEvent {
+            sigers: verify_sigs(ser, &more.sigers, kever.verfers())?,
+            trans_receipts,
+            ..receipted(
+                kever,
+                &KelEvent {
+                    serder: event.serder.clone(),
+                    ..more.clone()
+                },
+            )?
+        };
+        merge(&mut event.sigers, &verified.sigers);
+        merge(&mut event.wigers, &verified.wigers);
+        merge_cigars(&mut event.cigars, &verified.cigars);
+        merge_trans_receipts(&mut event.trans_receipts, &verified.trans_receipts);
+
+        if let Some(db) = &self.db {
+            let said = event.serder.said_field().unwrap_or_default();
+            db.add_signatures(pre, said, &verified.sigers)?;
+            db.add_receipts(
+                pre,
+                said,
+                &verified.wigers,
+                &verified.cigars,
+                &verified.trans_receipts,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Transferable receipt quadruples on an event whose validator signature
+    /// verifies against the establishment event of the validator's KEL the
+    /// quadruple names
+    fn endorsed(
+        &self,
+        serder: &Serder,
+        trans_receipts: &[TransReceipt],
+    ) -> Result<Vec<TransReceipt>> {
+        let ser = serder.raw().as_bytes();
+        let mut endorsed: Vec<TransReceipt> = Vec::new();
+        for rct in trans_receipts {
+            let validator = rct.prefixer.qb64();
+            let Ok(sn) = usize::try_from(rct.seqner.sn()) else {
+                continue;
+            };
+            let est = self.kel(validator).get(sn).filter(|e| {
+                e.serder.said_field() == Some(rct.diger.qb64())
+                    && matches!(e.serder.ilk(), Some(ILK_ICP | ILK_DIP | ILK_ROT | ILK_DRT))
+            });
+            let Some(kever) = est.and_then(|_| self.states.get(validator)?.get(sn)) else {
+                continue;
+            };
+            if !verify_sigs(ser, std::slice::from_ref(&rct.siger), kever.verfers())?.is_empty() {
+                merge_trans_receipts(&mut endorsed, std::slice::from_ref(rct));
+            }
+        }
+        Ok(endorsed)
+    }
+
+    /// Log an accepted event to the database and to the first-seen log of
+    /// its prefix unless it was seen before
+    fn log_first_seen(&mut self, event: &KelEvent) -> Result<()> {
+        let pre = event.serder.pre().unwrap_or_default();
+        let said = event.serder.said_field().unwrap_or_default();
+        let dt = match &self.db {
+            Some(db) => {
+                db.log_event(event)?;
+                db.first_seen(pre, said)?.map(|(_, dt)| dt)
+            }
+            None => None,
+        };
+        let fel = self.fels.entry(pre.to_string()).or_default();
+        if !fel.iter().any(|(seen, _)| seen == said) {
+            fel.push((said.to_string(), dt.unwrap_or_else(now_iso8601)));
+        }
+        Ok(())
+    }
+
+    /// Key state before the accepted event at `at`, after the events before it
+    fn state_before(&self, pre: &str, at: usize) -> Option<&Kever> {
+        self.states.get(pre)?.get(at.checked_sub(1)?)
+    }
+
+    /// Key state of an identifier
+    pub fn kever(&self, pre: &str) -> Option<&Kever> {
+        self.states.get(pre)?.last()
+    }
+
+    /// Key state record of an identifier at its latest accepted event
+    pub fn state(&self, pre: &str) -> Option<KeyStateRecord> {
+        let kever = self.kever(pre)?;
+        let (fnu, (_, dt)) = self
+            .fels
+            .get(pre)?
+            .iter()
+            .enumerate()
+            .rfind(|(_, (said, _))| said == kever.said())?;
+        Some(kever.state(fnu as u64, dt))
+    }
+
+    /// Accepted events of an identifier in order
+    pub fn kel(&self, pre: &str) -> &[KelEvent] {
+        self.kels.get(pre).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Events waiting in an escrow
+    pub fn escrowed(&self, escrow: Escrow) -> Vec<&Serder> {
+        self.escrows
+            .iter()
+            .filter(|e| e.escrow == escrow)
+            .map(|e| &e.event.serder)
+            .collect()
+    }
+
+    /// Verify signatures on `payload` against the key state of an identifier
+    /// at `sn`, or its latest key state
+    ///
+    /// Returns `VerificationFailed` when the signatures verifying against the
+    /// current signing keys do not satisfy the signing threshold.
+    pub fn verify_by_aid(
+        &self,
+        pre: &str,
+        sn: Option<u64>,
+        payload: &[u8],
+        sigers: &[Siger],
+    ) -> Result<AidVerification> {
+        let kel = self.kel(pre);
+        let at = match sn {
+            Some(sn) => kel.iter().rposition(|e| e.serder.sn() == Some(sn)),
+            None => kel.len().checked_sub(1),
+        };
+        let kever = at
+            .and_then(|at| self.state_before(pre, at + 1))
+            .ok_or_else(|| {
+                let sn = sn.map_or_else(|| "latest".to_string(), |sn| sn.to_string());
+                SignifyError::NotFound(format!("Key state of {} at sn {}", pre, sn))
+            })?;
+
+        let current = verify_threshold(payload, sigers, kever.verfers(), kever.tholder())?;
+
+        // Key state before the establishment event that set the current keys
+        let (est_sn, est_said) = kever.last_est();
+        let est = kel
+            .iter()
+            .position(|e| e.serder.sn() == Some(est_sn) && e.serder.said_field() == Some(est_said))
+            .unwrap_or_default();
+        let prior_next = match self.state_before(pre, est) {
+            Some(prior) => prior.committed(&current, kever.verfers())?,
+            None => Vec::new(),
+        };
+
+        Ok(AidVerification {
+            current,
+            prior_next,
+        })
+    }
+
+    /// Process a signed key state notice reply on `/ksn/<src>`
+    ///
+    /// The reply must be signed by its source `src`: a transferable source
+    /// with indexed signatures (`-F`) that verify against the establishment
+    /// event of its KEL they name, a non-transferable source with a receipt
+    /// couple (`-C`). A notice conflicting with the accepted event at its sn
+    /// is rejected. Notices of a source about an identifier follow the BADA
+    /// rules: a notice replaces the accepted one only when signed at a later
+    /// establishment event of the source or, at the same one, with a later
+    /// datetime. Returns whether the notice was accepted.
+    pub fn process_ksn(&mut self, message: &Message) -> Result<bool> {
+        let serder = &message.serder;
+        let sad = serder.sad();
+        let route = sad["r"].as_str().unwrap_or_default();
+        let src = route
+            .strip_prefix(ROUTE_KSN_REPLY)
+            .and_then(|src| src.strip_prefix('/'))
+            .filter(|src| serder.ilk() == Some(ILK_RPY) && !src.is_empty())
+            .ok_or_else(|| {
+                SignifyError::InvalidEvent(format!("Invalid route {} for key state notice", route))
+            })?;
+        let dt = sad["dt"].as_str().unwrap_or_default();
+        parse_dt(dt)?;
+        let record = KeyStateRecord::from_value(&sad["a"])?;
+        let est_sn = self.verify_reply(serder, src, &message.attachments)?;
+
+        let sn = record.sn()?;
+        if let Some(event) = self
+            .kel(&record.i)
+            .iter()
+            .find(|e| e.serder.sn() == Some(sn))
+        {
+            if event.serder.said_field() != Some(record.d.as_str()) {
+                return Err(SignifyError::InvalidEvent(format!(
+                    "Key state notice for {} conflicts with accepted event at sn {}",
+                    record.i, sn
+                )));
+            }
+        }
+
+        let key = (record.i.clone(), src.to_string());
+        if let Some(prior) = self.ksns.get(&key) {
+            if !fresher(est_sn, dt, prior.est_sn, &prior.dt)? {
+                return Ok(false);
+            }
+        }
+        let notice = KeyStateNotice {
+            record,
+            src: src.to_string(),
+            dt: dt.to_string(),
+            said: serder.said_field().unwrap_or_default().to_string(),
+            est_sn,
+        };
+        if let Some(db) = &self.db {
+            db.put_ksn(&notice)?;
+        }
+        self.ksns.insert(key, notice);
+        Ok(true)
+    }
+
+    /// Verify the signatures of the source `src` on a reply, returning the
+    /// sn of the establishment event they verified against for a
+    /// transferable source
+    pub(crate) fn verify_reply(
+        &self,
+        serder: &Serder,
+        src: &str,
+        attachments: &Attachments,
+    ) -> Result<Option<u64>> {
+        let ser = serder.raw().as_bytes();
+        if let Some(tsg) = attachments
+            .trans_sig_groups
+            .iter()
+            .find(|tsg| tsg.prefixer.qb64() == src)
+        {
+            let sn = u64::try_from(tsg.seqner.sn())
+                .map_err(|_| SignifyError::InvalidEvent(format!("Invalid sn for {}", src)))?;
+            let est = self.kel(src).iter().find(|e| {
+                e.serder.sn() == Some(sn) && e.serder.said_field() == Some(tsg.diger.qb64())
+            });
+            if !matches!(
+                est.and_then(|e| e.serder.ilk()),
+                Some(ILK_ICP | ILK_DIP | ILK_ROT | ILK_DRT)
+            ) {
+                return Err(SignifyError::NotFound(format!(
+                    "Establishment event of {} at sn {}",
+                    src, sn
+                )));
+            }
+            self.verify_by_aid(src, Some(sn), ser, &tsg.sigers)?;
+            return Ok(Some(sn));
+        }
+
+        if let Some(receipt) = attachments
+            .receipts
+            .iter()
+            .find(|receipt| receipt.prefixer.qb64() == src)
+        {
+            let verfer = Verfer::from_qb64(src)?;
+            if verfer.transferable() || !verfer.verify(receipt.cigar.raw(), ser).unwrap_or(false) {
+                return Err(SignifyError::VerificationFailed);
+            }
+            return Ok(None);
+        }
+
+        Err(SignifyError::InvalidEvent(format!(
+            "Missing signatures of {} for reply",
+            src
+        )))
+    }
+
+    /// Key state notice of `src` about an identifier
+    pub fn key_state_notice(&self, pre: &str, src: &str) -> Option<&KeyStateNotice> {
+        self.ksns.get(&(pre.to_string(), src.to_string()))
+    }
+
+    /// Likely duplicitous events logged for an identifier
+    pub fn duplicity(&self, pre: &str) -> &[Duplicity] {
+        self.ldes.get(pre).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Identifiers with likely duplicitous events
+    pub fn duplicitous(&self) -> Vec<&str> {
+        self.ldes.keys().map(String::as_str).collect()
+    }
+}
+
+/// Event with only the receipts that verify, receipt couples of witnesses
+/// of the key state it results in turned into witness signatures indexed by
+/// the witness list
+fn receipted(kever: &Kever, event: &KelEvent) -> Result<KelEvent> {
+    let ser = event.serder.raw().as_bytes();
+    let verfers = kever
+        .wits()
+        .iter()
+        .map(|wit| Verfer::from_qb64(wit))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut wigers = event.wigers.clone();
+    let mut cigars: Vec<Cigar> = Vec::new();
+    for cigar in &event.cigars {
+        let Some(verfer) = cigar.verfer() else {
+            continue;
+        };
+        if !cigar.verify(ser).unwrap_or(false) {
+            continue;
+        }
+        match kever.wits().iter().position(|w| w == verfer.qb64()) {
+            Some(index) if cigar.code() == matter_codes::ED25519_SIG => {
+                let code = if index < 64 {
+                    IndexerCodex::ED25519_SIG
+                } else {
+                    IndexerCodex::ED25519_BIG_SIG
+                };
+                let siger = Siger::new(cigar.raw(), code, index as u32, None, None)?;
+                wigers.push(siger);
+            }
+            Some(_) => {}
+            None => {
+                if !cigars
+                    .iter()
+                    .any(|c| c.verfer().map(Verfer::qb64) == Some(verfer.qb64()))
+                {
+                    cigars.push(cigar.clone());
+                }
+            }
+        }
+    }
+
+    Ok(KelEvent {
+        wigers: verify_sigs(ser, &wigers, &verfers)?,
+        cigars,
+        ..event.clone()
+    })
+}
+
+/// Whether distinct witnesses with verified signatures satisfy the witness
+/// threshold of the key state the event results in
+fn witnessed(kever: &Kever, event: &KelEvent) -> bool {
+    let receipted: HashSet<u32> = event.wigers.iter().map(Siger::index).collect();
+    receipted.len() >= kever.toad()
+}
+
+/// Add signatures not in `sigers` yet
+fn merge(sigers: &mut Vec<Siger>, more: &[Siger]) {
+    for siger in more {
+        if !sigers.iter().any(|s| s.qb64() == siger.qb64()) {
+            sigers.push(siger.clone());
+        }
+    }
+}
+
+/// Add transferable receipt quadruples not in `rcts` yet
+fn merge_trans_receipts(rcts: &mut Vec<TransReceipt>, more: &[TransReceipt]) {
+    for rct in more {
+        if !rcts.iter().any(|r| {
+            r.prefixer.qb64() == rct.prefixer.qb64()
+                && r.diger.qb64() == rct.diger.qb64()
+                && r.siger.qb64() == rct.siger.qb64()
+        }) {
+            rcts.push(rct.clone());
+        }
+    }
+}
+
+/// Add receipt couples not in `cigars` yet
+fn merge_cigars(cigars: &mut Vec<Cigar>, more: &[Cigar]) {
+    for cigar in more {
+        if !cigars.iter().any(|c| c.qb64() == cigar.qb64()) {
+            cigars.push(cigar.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cesr_parser::parse_stream;
+    use crate::core::diger::Diger;
+    use crate::core::eventing::{
+        incept, interact, key_state_notice, messagize, receipt, rotate, MessagizeOpts, RotateArgs,
+        TraitCodex,
+    };
+    use crate::core::prefixer::Prefixer;
+    use crate::core::seqner::Seqner;
+    use crate::core::signer::Signer;
+    use crate::core::test_support::{cigar, digs, icp, keys, siger, sign, signers};
+    use serde_json::json;
+
+    #[test]
+    fn test_kevery_out_of_order() {
+        let (s0, s1, s2) = (signers(1, true), signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let ixn = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        let rot = rotate(RotateArgs {
+            pre: pre.to_string(),
+            keys: keys(&s1),
+            dig: ixn.said_field().unwrap().to_string(),
+            sn: 2,
+            ndigs: digs(&s2),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut kevery = Kevery::new();
+        let ooo = Processed::Escrowed(Escrow::OutOfOrder);
+        assert_eq!(
+            kevery
+                .process_event(&rot, &sign(&rot, &s1), &[], None)
+                .unwrap(),
+            ooo
+        );
+        assert_eq!(
+            kevery
+                .process_event(&ixn, &sign(&ixn, &s0), &[], None)
+                .unwrap(),
+            ooo
+        );
+        assert_eq!(kevery.escrowed(Escrow::OutOfOrder).len(), 2);
+
+        // Inception unblocks the escrowed events
+        let processed = kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        assert_eq!(kevery.kever(pre).unwrap().sn(), 2);
+        assert_eq!(kevery.kel(pre).len(), 3);
+        assert!(kevery.escrowed(Escrow::OutOfOrder).is_empty());
+
+        let processed = kevery
+            .process_event(&ixn, &sign(&ixn, &s0), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Duplicate);
+    }
+
+    #[test]
+    fn test_kevery_partial_sigs_and_witnesses() {
+        let (s0, s1, wits) = (signers(3, true), signers(3, true), signers(1, false));
+        let icp = icp(&s0, &s1, &wits, None, None);
+        let pre = icp.pre().unwrap();
+        let sigers = sign(&icp, &s0);
+        let wigers = sign(&icp, &wits);
+
+        let mut kevery = Kevery::new();
+        let processed = kevery.process_event(&icp, &sigers[..1], &[], None).unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::PartialSigs));
+
+        // The other signatures arrive without the first
+        let processed = kevery.process_event(&icp, &sigers[1..], &[], None).unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::PartialWitness));
+        assert!(kevery.kever(pre).is_none());
+
+        let processed = kevery.process_event(&icp, &[], &wigers, None).unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        assert_eq!(kevery.kel(pre)[0].sigers.len(), 3);
+        assert_eq!(kevery.kel(pre)[0].wigers.len(), 1);
+    }
+
+    #[test]
+    fn test_kevery_failed_copy_keeps_escrow() {
+        let (s0, s1, wits) = (signers(3, true), signers(3, true), signers(1, false));
+        let icp = icp(&s0, &s1, &wits, None, None);
+        let pre = icp.pre().unwrap();
+        let rct = receipt(pre, 0, icp.said_field().unwrap()).unwrap();
+        let sigers = sign(&icp, &s0);
+
+        let mut kevery = Kevery::new();
+        kevery.process_event(&icp, &sigers[..1], &[], None).unwrap();
+
+        // Copies with attachments that do not verify leave the escrowed
+        // event and its signatures alone
+        let bogus = Siger::new(&[0u8; 114], IndexerCodex::ED448_SIG, 1, None, None).unwrap();
+        let processed = kevery.process_event(&icp, &[bogus], &[], None).unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::PartialSigs));
+        let bogus = Cigar::new(&[0u8; 114], matter_codes::ED448_SIG, None).unwrap();
+        let processed = kevery.process_receipt(&rct, &[], &[bogus]).unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::PartialSigs));
+        assert_eq!(kevery.escrowed(Escrow::PartialSigs).len(), 1);
+
+        let processed = kevery
+            .process_event(&icp, &sigers[1..2], &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::PartialWitness));
+        let processed = kevery
+            .process_event(&icp, &[], &sign(&icp, &wits), None)
+            .unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        assert_eq!(kevery.kel(pre)[0].sigers.len(), 2);
+        assert!(kevery.kel(pre)[0].cigars.is_empty());
+    }
+
+    #[test]
+    fn test_kevery_failed_copy_restores_escrow() {
+        let (d0, d1) = (signers(1, true), signers(1, true));
+        let delegator = incept(
+            keys(&d0),
+            None,
+            digs(&d1),
+            None,
+            None,
+            None,
+            Some(vec![TraitCodex::DO_NOT_DELEGATE.to_string()]),
+            None,
+            None,
+            None,
+            Some(matter_codes::BLAKE3_256),
+            false,
+            None,
+        )
+        .unwrap();
+        let (s0, s1) = (signers(3, true), signers(3, true));
+        let dip = icp(&s0, &s1, &[], None, Some(delegator.pre().unwrap()));
+        let rct = receipt(dip.pre().unwrap(), 0, dip.said_field().unwrap()).unwrap();
+        let sigers = sign(&dip, &s0);
+        let stranger = signers(1, false);
+
+        let mut kevery = Kevery::new();
+        kevery.process_event(&dip, &sigers[..1], &[], None).unwrap();
+        kevery
+            .process_receipt(&rct, &[], &[cigar(&dip, &stranger[0])])
+            .unwrap();
+        kevery
+            .process_event(&delegator, &sign(&delegator, &d0), &[], None)
+            .unwrap();
+
+        // Processing the merged copy fails, which puts back what was escrowed
+        assert!(kevery.process_event(&dip, &sigers[1..], &[], None).is_err());
+        let escrowed = kevery.escrowed(Escrow::PartialSigs);
+        assert_eq!(escrowed.len(), 1);
+        assert_eq!(escrowed[0].raw(), dip.raw());
+        let key = escrow_key(&dip);
+        let restored = kevery
+            .escrows
+            .find(&key, |e| e.escrow == Escrow::PartialSigs)
+            .unwrap();
+        assert_eq!(restored.event.sigers.len(), 1);
+        assert_eq!(restored.event.cigars.len(), 1);
+    }
+
+    #[test]
+    fn test_kevery_witness_receipts() {
+        let (s0, s1, wits) = (signers(1, true), signers(1, true), signers(3, false));
+        let icp = incept(
+            keys(&s0),
+            None,
+            digs(&s1),
+            None,
+            Some(2),
+            Some(keys(&wits)),
+            None,
+            None,
+            None,
+            None,
+            Some(matter_codes::BLAKE3_256),
+            false,
+            None,
+        )
+        .unwrap();
+        let pre = icp.pre().unwrap();
+        let rct = receipt(pre, 0, icp.said_field().unwrap()).unwrap();
+        let partial = Processed::Escrowed(Escrow::PartialWitness);
+
+        let mut kevery = Kevery::new();
+        let processed = kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        assert_eq!(processed, partial);
+
+        // Receipt couple of the first witness in a receipt message, with one
+        // from a non-witness that does not count
+        let stranger = signers(1, false);
+        let opts = MessagizeOpts {
+            cigars: vec![cigar(&icp, &wits[0]), cigar(&icp, &stranger[0])],
+            ..Default::default()
+        };
+        let msg = messagize(&rct, opts).unwrap();
+        let messages = parse_stream(&msg).unwrap();
+        assert_eq!(kevery.process_message(&messages[0]).unwrap(), partial);
+
+        // The same witness signing again still counts once
+        let wigers = sign(&icp, &wits);
+        let processed = kevery.process_receipt(&rct, &wigers[..1], &[]).unwrap();
+        assert_eq!(processed, partial);
+        assert!(kevery.kever(pre).is_none());
+
+        let processed = kevery.process_receipt(&rct, &wigers[1..2], &[]).unwrap();
+        assert_eq!(processed, Processed::Accepted);
+
+        // Witness receipt couples are kept as witness signatures
+        let event = &kevery.kel(pre)[0];
+        let indices: Vec<u32> = event.wigers.iter().map(Siger::index).collect();
+        assert_eq!(indices, [0, 1]);
+        assert_eq!(event.wigers[0].qb64(), wigers[0].qb64());
+        assert_eq!(event.cigars.len(), 1);
+        assert_eq!(event.cigars[0].verfer().unwrap().qb64(), keys(&stranger)[0]);
+
+        // Later receipts are verified and kept with the accepted event
+        let forged = cigar(&rct, &wits[2]);
+        let processed = kevery.process_receipt(&rct, &[], &[forged]).unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        assert_eq!(kevery.kel(pre)[0].wigers.len(), 2);
+        let processed = kevery
+            .process_receipt(&rct, &[], &[cigar(&icp, &wits[2])])
+            .unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        assert_eq!(kevery.kel(pre)[0].wigers.len(), 3);
+    }
+
+    #[test]
+    fn test_kevery_receipts_before_event() {
+        let (s0, s1, wits) = (signers(1, true), signers(1, true), signers(2, false));
+        let icp = icp(&s0, &s1, &wits, None, None);
+        let pre = icp.pre().unwrap();
+        let toad = icp.sad()["bt"].as_str().unwrap();
+        assert_eq!(usize::from_str_radix(toad, 16).unwrap(), 2);
+        let rct = receipt(pre, 0, icp.said_field().unwrap()).unwrap();
+
+        let mut kevery = Kevery::new();
+        let unverified = Processed::Escrowed(Escrow::UnverifiedReceipt);
+        let processed = kevery
+            .process_receipt(&rct, &[], &[cigar(&icp, &wits[0])])
+            .unwrap();
+        assert_eq!(processed, unverified);
+        let processed = kevery
+            .process_receipt(&rct, &[], &[cigar(&icp, &wits[1])])
+            .unwrap();
+        assert_eq!(processed, unverified);
+        assert_eq!(kevery.escrowed(Escrow::UnverifiedReceipt).len(), 1);
+
+        let processed = kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        assert_eq!(kevery.kel(pre)[0].wigers.len(), 2);
+        assert!(kevery.escrowed(Escrow::UnverifiedReceipt).is_empty());
+
+        let ixn = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        assert!(kevery.process_receipt(&ixn, &[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_kevery_trans_receipts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keri.redb");
+        let (s0, s1, v0, v1) = (
+            signers(1, true),
+            signers(1, true),
+            signers(1, true),
+            signers(1, true),
+        );
+        let vcp = icp(&v0, &v1, &[], None, None);
+        let validator = vcp.pre().unwrap();
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let ixn = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        let quadruple = |serder: &Serder, signer: &Signer| TransReceipt {
+            prefixer: Prefixer::from_qb64(validator).unwrap(),
+            seqner: Seqner::new(0).unwrap(),
+            diger: Diger::from_qb64(vcp.said_field().unwrap()).unwrap(),
+            siger: siger(serder, signer),
+        };
+
+        {
+            let mut kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+            kevery
+                .process_event(&vcp, &sign(&vcp, &v0), &[], None)
+                .unwrap();
+
+            // Quadruples attached to the event, one signed by a stranger
+            let msg = messagize(
+                &icp,
+                MessagizeOpts {
+                    sigers: sign(&icp, &s0),
+                    trans_receipts: vec![quadruple(&icp, &v0[0]), quadruple(&icp, &v1[0])],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            for message in parse_stream(&msg).unwrap() {
+                kevery.process_message(&message).unwrap();
+            }
+            let event = &kevery.kel(pre)[0];
+            assert_eq!(event.trans_receipts.len(), 1);
+            assert_eq!(event.trans_receipts[0].prefixer.qb64(), validator);
+
+            // Signature groups of the validator in a receipt message
+            kevery
+                .process_event(&ixn, &sign(&ixn, &s0), &[], None)
+                .unwrap();
+            let rct = receipt(pre, 1, ixn.said_field().unwrap()).unwrap();
+            let seal = SealEvent::new(validator, 0, vcp.said_field().unwrap());
+            let msg = messagize(
+                &rct,
+                MessagizeOpts {
+                    sigers: vec![siger(&ixn, &v0[0])],
+                    seal: Some(Seal::Event(seal)),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            for message in parse_stream(&msg).unwrap() {
+                kevery.process_message(&message).unwrap();
+            }
+            assert_eq!(kevery.kel(pre)[1].trans_receipts.len(), 1);
+        }
+
+        // Receipts survive a restart and replay as quadruples
+        let kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+        let kel = kevery.kel(pre);
+        assert_eq!(
+            kel[0].trans_receipts[0].siger.qb64(),
+            siger(&icp, &v0[0]).qb64()
+        );
+        assert_eq!(
+            kel[1].trans_receipts[0].siger.qb64(),
+            siger(&ixn, &v0[0]).qb64()
+        );
+        let stream = kevery.db.as_ref().unwrap().replay(pre, ..).unwrap();
+        let messages = parse_stream(&stream).unwrap();
+        assert_eq!(messages[0].attachments.trans_receipts.len(), 1);
+        assert_eq!(messages[1].attachments.trans_receipts.len(), 1);
+    }
+
+    #[test]
+    fn test_kevery_baser_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keri.redb");
+        let (s0, s1, s2, wits) = (
+            signers(1, true),
+            signers(1, true),
+            signers(1, true),
+            signers(1, false),
+        );
+        let icp = icp(&s0, &s1, &wits, None, None);
+        let pre = icp.pre().unwrap();
+        let rct = receipt(pre, 0, icp.said_field().unwrap()).unwrap();
+        let ixn = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        let rot = rotate(RotateArgs {
+            pre: pre.to_string(),
+            keys: keys(&s1),
+            dig: ixn.said_field().unwrap().to_string(),
+            sn: 2,
+            ndigs: digs(&s2),
+            toad: Some(1),
+            wits: keys(&wits),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let state = {
+            let mut kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+            kevery
+                .process_event(&icp, &sign(&icp, &s0), &sign(&icp, &wits), None)
+                .unwrap();
+            kevery
+                .process_receipt(&rct, &[], &[cigar(&icp, &wits[0])])
+                .unwrap();
+            for (event, signers) in [(&ixn, &s0), (&rot, &s1)] {
+                let wigers = sign(event, &wits);
+                let processed = kevery
+                    .process_event(event, &sign(event, signers), &wigers, None)
+                    .unwrap();
+                assert_eq!(processed, Processed::Accepted);
+            }
+            kevery.state(pre).unwrap()
+        };
+
+        let mut kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+        assert_eq!(kevery.state(pre).unwrap(), state);
+        assert_eq!(state.f, "2");
+        let kever = kevery.kever(pre).unwrap();
+        assert_eq!(kever.sn(), 2);
+        assert_eq!(kever.said(), rot.said_field().unwrap());
+        assert_eq!(kever.keys(), keys(&s1));
+        assert_eq!(kevery.kel(pre).len(), 3);
+        assert_eq!(kevery.kel(pre)[0].wigers.len(), 1);
+
+        let processed = kevery
+            .process_event(&ixn, &sign(&ixn, &s0), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Duplicate);
+        let next = interact(pre, rot.said_field().unwrap(), 3, None, None).unwrap();
+        let processed = kevery
+            .process_event(&next, &sign(&next, &s1), &sign(&next, &wits), None)
+            .unwrap();
+        assert_eq!(processed, Processed::Accepted);
+    }
+
+    #[test]
+    fn test_kevery_verify_by_aid() {
+        let (s0, s1, s2) = (signers(3, true), signers(3, true), signers(3, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let ixn = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        let rot = rotate(RotateArgs {
+            pre: pre.to_string(),
+            keys: keys(&s1),
+            dig: ixn.said_field().unwrap().to_string(),
+            sn: 2,
+            ndigs: digs(&s2),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut kevery = Kevery::new();
+        for (event, signers) in [(&icp, &s0), (&ixn, &s0), (&rot, &s1)] {
+            kevery
+                .process_event(event, &sign(event, signers), &[], None)
+                .unwrap();
+        }
+
+        let payload = b"was this signed by the AID?";
+        let sigs = |signers: &[Signer], code: &str| -> Vec<Siger> {
+            signers
+                .iter()
+                .enumerate()
+                .map(|(i, s)| Siger::new(&s.sign(payload).unwrap(), code, i as u32, None, None))
+                .collect::<Result<_>>()
+                .unwrap()
+        };
+
+        // Rotated keys are current and were committed to by the inception
+        let both = sigs(&s1, IndexerCodex::ED25519_SIG);
+        let verified = kevery.verify_by_aid(pre, None, payload, &both).unwrap();
+        assert_eq!(verified.current.len(), 3);
+        assert_eq!(verified.prior_next.len(), 3);
+
+        let current_only = sigs(&s1, IndexerCodex::ED25519_CRT_SIG);
+        let verified = kevery
+            .verify_by_aid(pre, Some(2), payload, &current_only[1..])
+            .unwrap();
+        assert_eq!(verified.current.len(), 2);
+        assert!(verified.prior_next.is_empty());
+
+        // Ondex locating another key's digest is not committed
+        let sig = s1[0].sign(payload).unwrap();
+        let swapped = Siger::new(&sig, IndexerCodex::ED25519_BIG_SIG, 0, Some(1), None).unwrap();
+        let verified = kevery
+            .verify_by_aid(pre, None, payload, &[swapped, both[1].clone()])
+            .unwrap();
+        let ondices: Vec<u32> = verified.prior_next.iter().map(Siger::ondex).collect();
+        assert_eq!(ondices, [1]);
+
+        assert!(matches!(
+            kevery.verify_by_aid(pre, None, payload, &both[..1]),
+            Err(SignifyError::VerificationFailed)
+        ));
+
+        // Key state at an earlier sn
+        let inception = sigs(&s0, IndexerCodex::ED25519_SIG);
+        let verified = kevery
+            .verify_by_aid(pre, Some(1), payload, &inception)
+            .unwrap();
+        assert_eq!(verified.current.len(), 3);
+        assert!(verified.prior_next.is_empty());
+        assert!(matches!(
+            kevery.verify_by_aid(pre, Some(1), payload, &both),
+            Err(SignifyError::VerificationFailed)
+        ));
+        assert!(matches!(
+            kevery.verify_by_aid(pre, Some(3), payload, &both),
+            Err(SignifyError::NotFound(_))
+        ));
+        assert!(kevery
+            .verify_by_aid("EUnknown", None, payload, &both)
+            .is_err());
+    }
+
+    #[test]
+    fn test_kevery_key_state() {
+        let (s0, s1, s2) = (signers(1, true), signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let ixn = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        let rot = rot(pre, &ixn, 2, &s1, &s2);
+
+        let mut kevery = Kevery::new();
+        assert!(kevery.state(pre).is_none());
+        kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        let state = kevery.state(pre).unwrap();
+        assert_eq!((state.s.as_str(), state.f.as_str()), ("0", "0"));
+        assert_eq!(state.et, "icp");
+        assert_eq!(state.p, "");
+
+        for (event, signers) in [(&ixn, &s0), (&rot, &s1)] {
+            kevery
+                .process_event(event, &sign(event, signers), &[], None)
+                .unwrap();
+        }
+        let state = kevery.state(pre).unwrap();
+        let fields: Vec<String> = state
+            .to_value()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "vn", "i", "s", "p", "d", "f", "dt", "et", "kt", "k", "nt", "n", "bt", "b", "c",
+                "ee", "di"
+            ]
+        );
+        assert_eq!(state.i, pre);
+        assert_eq!((state.s.as_str(), state.f.as_str()), ("2", "2"));
+        assert_eq!(state.p, ixn.said_field().unwrap());
+        assert_eq!(state.d, rot.said_field().unwrap());
+        assert_eq!(state.et, "rot");
+        assert_eq!(state.kt, json!("1"));
+        assert_eq!(state.k, keys(&s1));
+        assert_eq!(state.n, digs(&s2));
+        assert_eq!(state.bt, "0");
+        assert_eq!(state.ee.s, "2");
+        assert_eq!(state.ee.d, rot.said_field().unwrap());
+        assert!(state.dt.ends_with("+00:00"));
+        assert_eq!(state.di, "");
+    }
+
+    #[test]
+    fn test_kevery_ksn_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keri.redb");
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let watcher = signers(1, false).remove(0);
+        let wat = watcher.verfer().qb64().to_string();
+        let t0 = "2024-01-01T00:00:00.000000+00:00";
+        let t1 = "2024-01-02T00:00:00.000000+00:00";
+
+        let mut kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+        kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        let state = kevery.state(pre).unwrap();
+        let ksn = |stamp: &str| {
+            let rpy = key_state_notice(&state, &wat, Some(stamp)).unwrap();
+            let opts = MessagizeOpts {
+                cigars: vec![cigar(&rpy, &watcher)],
+                ..Default::default()
+            };
+            parse_stream(&messagize(&rpy, opts).unwrap())
+                .unwrap()
+                .remove(0)
+        };
+        assert!(kevery.process_ksn(&ksn(t1)).unwrap());
+        let said = kevery.key_state_notice(pre, &wat).unwrap().said.clone();
+        drop(kevery);
+
+        // The notice survives a restart and still outranks staler ones
+        let mut kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+        let notice = kevery.key_state_notice(pre, &wat).unwrap();
+        assert_eq!(notice.record, state);
+        assert_eq!(notice.dt, t1);
+        assert_eq!(notice.said, said);
+        assert_eq!(notice.est_sn, None);
+        assert!(!kevery.process_ksn(&ksn(t0)).unwrap());
+    }
+
+    #[test]
+    fn test_kevery_process_ksn() {
+        let (b0, b1, b2) = (signers(1, true), signers(1, true), signers(1, true));
+        let bicp = icp(&b0, &b1, &[], None, None);
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let mut kevery = Kevery::new();
+        kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        let state = kevery.state(pre).unwrap();
+
+        let ksn = |src: &str, stamp: &str, opts: &dyn Fn(&Serder) -> MessagizeOpts| {
+            let rpy = key_state_notice(&state, src, Some(stamp)).unwrap();
+            assert_eq!(rpy.sad()["r"], format!("/ksn/{}", src));
+            let msg = messagize(&rpy, opts(&rpy)).unwrap();
+            parse_stream(&msg).unwrap().remove(0)
+        };
+
+        // Non-transferable source signs with a receipt couple
+        let watcher = signers(1, false).remove(0);
+        let wat = watcher.verfer().qb64().to_string();
+        fn couple(signer: &Signer) -> impl Fn(&Serder) -> MessagizeOpts + '_ {
+            move |rpy| MessagizeOpts {
+                cigars: vec![cigar(rpy, signer)],
+                ..Default::default()
+            }
+        }
+        let t0 = "2024-01-01T00:00:00.000000+00:00";
+        let t1 = "2024-01-02T00:00:00.000000+00:00";
+        assert!(kevery
+            .process_ksn(&ksn(&wat, t1, &couple(&watcher)))
+            .unwrap());
+        let notice = kevery.key_state_notice(pre, &wat).unwrap();
+        assert_eq!(notice.record, state);
+        assert_eq!(notice.est_sn, None);
+
+        // Stale or replayed notices are ignored
+        assert!(!kevery
+            .process_ksn(&ksn(&wat, t1, &couple(&watcher)))
+            .unwrap());
+        assert!(!kevery
+            .process_ksn(&ksn(&wat, t0, &couple(&watcher)))
+            .unwrap());
+        assert_eq!(kevery.key_state_notice(pre, &wat).unwrap().dt, t1);
+
+        let other = signers(1, false).remove(0);
+        let forged = |rpy: &Serder| {
+            let sig = other.sign(rpy.raw().as_bytes()).unwrap();
+            MessagizeOpts {
+                cigars: vec![Cigar::new(
+                    &sig,
+                    matter_codes::ED25519_SIG,
+                    Some(watcher.verfer().clone()),
+                )
+                .unwrap()],
+                ..Default::default()
+            }
+        };
+        assert!(matches!(
+            kevery.process_ksn(&ksn(&wat, t1, &forged)),
+            Err(SignifyError::VerificationFailed)
+        ));
+        assert!(matches!(
+            kevery.process_ksn(&ksn(&wat, t1, &couple(&other))),
+            Err(SignifyError::InvalidEvent(_))
+        ));
+        let mut unsigned = ksn(&wat, t1, &couple(&watcher));
+        unsigned.attachments.receipts.clear();
+        assert!(matches!(
+            kevery.process_ksn(&unsigned),
+            Err(SignifyError::InvalidEvent(_))
+        ));
+
+        // Transferable source signs at an establishment event of its KEL
+        let src = bicp.pre().unwrap().to_string();
+        fn group<'a>(
+            signers: &'a [Signer],
+            est: &Serder,
+        ) -> impl Fn(&Serder) -> MessagizeOpts + 'a {
+            let seal: Seal = SealEvent::new(
+                est.pre().unwrap(),
+                est.sn().unwrap(),
+                est.said_field().unwrap(),
+            )
+            .into();
+            move |rpy| MessagizeOpts {
+                sigers: sign(rpy, signers),
+                seal: Some(seal.clone()),
+                ..Default::default()
+            }
+        }
+        assert!(matches!(
+            kevery.process_ksn(&ksn(&src, t0, &group(&b0, &bicp))),
+            Err(SignifyError::NotFound(_))
+        ));
+        kevery
+            .process_event(&bicp, &sign(&bicp, &b0), &[], None)
+            .unwrap();
+        assert!(kevery
+            .process_ksn(&ksn(&src, t1, &group(&b0, &bicp)))
+            .unwrap());
+        assert!(matches!(
+            kevery.process_ksn(&ksn(&src, t1, &group(&b1, &bicp))),
+            Err(SignifyError::VerificationFailed)
+        ));
+
+        // A later establishment event wins over a later datetime
+        let brot = rot(&src, &bicp, 1, &b1, &b2);
+        kevery
+            .process_event(&brot, &sign(&brot, &b1), &[], None)
+            .unwrap();
+        assert!(kevery
+            .process_ksn(&ksn(&src, t0, &group(&b1, &brot)))
+            .unwrap());
+        let t2 = "2024-01-03T00:00:00.000000+00:00";
+        assert!(!kevery
+            .process_ksn(&ksn(&src, t2, &group(&b0, &bicp)))
+            .unwrap());
+        assert_eq!(kevery.key_state_notice(pre, &src).unwrap().est_sn, Some(1));
+
+        // Notice conflicting with the accepted KEL
+        let mut forked = state.clone();
+        forked.d = digs(&s1).remove(0);
+        let rpy = key_state_notice(&forked, &wat, Some(t2)).unwrap();
+        let msg = messagize(&rpy, couple(&watcher)(&rpy)).unwrap();
+        assert!(matches!(
+            kevery.process_ksn(&parse_stream(&msg).unwrap()[0]),
+            Err(SignifyError::InvalidEvent(_))
+        ));
+    }
+
+    #[test]
+    fn test_kevery_delegation() {
+        let (d0, d1) = (signers(1, true), signers(1, true));
+        let delegator = icp(&d0, &d1, &[], None, None);
+        let delpre = delegator.pre().unwrap();
+
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let dip = icp(&s0, &s1, &[], None, Some(delpre));
+        let pre = dip.pre().unwrap();
+
+        let mut kevery = Kevery::new();
+        kevery
+            .process_event(&delegator, &sign(&delegator, &d0), &[], None)
+            .unwrap();
+        let processed = kevery
+            .process_event(&dip, &sign(&dip, &s0), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::Delegation));
+
+        // Delegator anchors the delegated inception
+        let seal = SealEvent::new(pre, 0, dip.said_field().unwrap());
+        let ixn = interact(
+            delpre,
+            delegator.said_field().unwrap(),
+            1,
+            Some(vec![seal.into()]),
+            None,
+        )
+        .unwrap();
+        let msg = messagize(
+            &ixn,
+            MessagizeOpts {
+                sigers: sign(&ixn, &d0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let message = &parse_stream(&msg).unwrap()[0];
+        assert_eq!(
+            kevery.process_message(message).unwrap(),
+            Processed::Accepted
+        );
+        assert_eq!(kevery.kever(pre).unwrap().delpre(), Some(delpre));
+    }
+
+    #[test]
+    fn test_kevery_escrow_timeout() {
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let ixn = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+
+        let mut kevery = Kevery::new().with_timeout(Duration::ZERO);
+        kevery
+            .process_event(&ixn, &sign(&ixn, &s0), &[], None)
+            .unwrap();
+        assert_eq!(kevery.escrowed(Escrow::OutOfOrder).len(), 1);
+
+        // Expired before the inception lands
+        kevery.process_escrows();
+        kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        assert!(kevery.escrowed(Escrow::OutOfOrder).is_empty());
+        assert_eq!(kevery.kever(pre).unwrap().sn(), 0);
+
+        // Signatures by other keys leave the event partially signed
+        let processed = kevery
+            .process_event(&ixn, &sign(&ixn, &s1), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::PartialSigs));
+    }
+
+    #[test]
+    fn test_kevery_escrow_limit() {
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let mut events = vec![icp.clone()];
+        for sn in 1..4 {
+            let prior = events.last().unwrap().said_field().unwrap();
+            events.push(interact(pre, prior, sn, None, None).unwrap());
+        }
+
+        let mut kevery = Kevery::new().with_escrow_limit(2);
+        for ixn in &events[1..] {
+            kevery
+                .process_event(ixn, &sign(ixn, &s0), &[], None)
+                .unwrap();
+        }
+
+        // The oldest escrowed event gave way, so the later ones stay blocked
+        let escrowed = kevery.escrowed(Escrow::OutOfOrder);
+        let sns: Vec<u64> = escrowed.iter().filter_map(|e| e.sn()).collect();
+        assert_eq!(sns, [2, 3]);
+        kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        assert_eq!(kevery.kever(pre).unwrap().sn(), 0);
+        assert_eq!(kevery.escrowed(Escrow::OutOfOrder).len(), 2);
+
+        // The missing event unblocks the rest in order
+        kevery
+            .process_event(&events[1], &sign(&events[1], &s0), &[], None)
+            .unwrap();
+        assert_eq!(kevery.kever(pre).unwrap().sn(), 3);
+        assert!(kevery.escrowed(Escrow::OutOfOrder).is_empty());
+    }
+
+    #[test]
+    fn test_kevery_duplicity() {
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let said = icp.said_field().unwrap();
+        let ixn = interact(pre, said, 1, None, None).unwrap();
+        let seal = SealEvent::new(pre, 0, said);
+        let fork = interact(pre, said, 1, Some(vec![seal.into()]), None).unwrap();
+
+        let mut kevery = Kevery::new();
+        kevery
+            .process_event(&icp, &sign(&icp, &s0), &[], None)
+            .unwrap();
+        kevery
+            .process_event(&ixn, &sign(&ixn, &s0), &[], None)
+            .unwrap();
+        assert!(kevery.duplicitous().is_empty());
+
+        // Unsigned conflicting events are not evidence
+        assert!(kevery
+            .process_event(&fork, &sign(&fork, &s1), &[], None)
+            .is_err());
+        assert!(kevery.duplicity(pre).is_empty());
+
+        let err = kevery.process_event(&fork, &sign(&fork, &s0), &[], None);
+        assert!(matches!(
+            err,
+            Err(SignifyError::DuplicitousEvent { sn: 1, .. })
+        ));
+        assert_eq!(kevery.duplicitous(), [pre]);
+        let duplicity = &kevery.duplicity(pre)[0];
+        assert_eq!(duplicity.accepted.serder.raw(), ixn.raw());
+        assert_eq!(duplicity.duplicitous.serder.raw(), fork.raw());
+        assert_eq!(duplicity.duplicitous.sigers.len(), 1);
+
+        // Seeing the fork again adds no new record and keeps the accepted KEL
+        assert!(kevery
+            .process_event(&fork, &sign(&fork, &s0), &[], None)
+            .is_err());
+        assert_eq!(kevery.duplicity(pre).len(), 1);
+        assert_eq!(kevery.kever(pre).unwrap().said(), ixn.said_field().unwrap());
+    }
+
+    #[test]
+    fn test_kevery_duplicity_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keri.redb");
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let said = icp.said_field().unwrap();
+        let ixn = interact(pre, said, 1, None, None).unwrap();
+        let seal = SealEvent::new(pre, 0, said);
+        let fork = interact(pre, said, 1, Some(vec![seal.into()]), None).unwrap();
+
+        let dt = {
+            let mut kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+            for event in [&icp, &ixn] {
+                kevery
+                    .process_event(event, &sign(event, &s0), &[], None)
+                    .unwrap();
+            }
+            assert!(kevery
+                .process_event(&fork, &sign(&fork, &s0), &[], None)
+                .is_err());
+            kevery.duplicity(pre)[0].dt.clone()
+        };
+
+        let mut kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+        assert_eq!(kevery.duplicitous(), [pre]);
+        let duplicity = &kevery.duplicity(pre)[0];
+        assert_eq!(duplicity.dt, dt);
+        assert_eq!(duplicity.accepted.serder.raw(), ixn.raw());
+        assert_eq!(duplicity.duplicitous.serder.raw(), fork.raw());
+        assert_eq!(duplicity.duplicitous.sigers.len(), 1);
+
+        // The restored record is merged into rather than logged again
+        assert!(kevery
+            .process_event(&fork, &sign(&fork, &s0), &[], None)
+            .is_err());
+        drop(kevery);
+        let kevery = Kevery::with_baser(Baser::open(&path).unwrap()).unwrap();
+        assert_eq!(kevery.duplicity(pre).len(), 1);
+        assert_eq!(kevery.kel(pre).len(), 2);
+    }
+
+    fn rot(pre: &str, prior: &Serder, sn: u64, new: &[Signer], next: &[Signer]) -> Serder {
+        let delegated = prior.ilk() == Some(ILK_DIP) || prior.ilk() == Some(ILK_DRT);
+        rotate(RotateArgs {
+            pre: pre.to_string(),
+            keys: keys(new),
+            dig: prior.said_field().unwrap().to_string(),
+            sn,
+            ndigs: digs(next),
+            delegated,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    /// Delegator interaction event anchoring the given events
+    fn anchor(kevery: &mut Kevery, signers: &[Signer], events: &[&Serder]) -> Serder {
+        let delpre = events[0].sad()["di"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| {
+                let pre = events[0].pre().unwrap();
+                kevery
+                    .kever(pre)
+                    .and_then(|k| k.delpre())
+                    .map(str::to_string)
+            })
+            .unwrap();
+        let kever = kevery.kever(&delpre).unwrap();
+        let seals = events
+            .iter()
+            .map(|e| {
+                SealEvent::new(e.pre().unwrap(), e.sn().unwrap(), e.said_field().unwrap()).into()
+            })
+            .collect();
+        let ixn = interact(&delpre, kever.said(), kever.sn() + 1, Some(seals), None).unwrap();
+        let processed = kevery
+            .process_event(&ixn, &sign(&ixn, signers), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        ixn
+    }
+
+    #[test]
+    fn test_kevery_delegation_source_seal() {
+        let (d0, d1) = (signers(1, true), signers(1, true));
+        let delegator = icp(&d0, &d1, &[], None, None);
+        let delpre = delegator.pre().unwrap();
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let dip = icp(&s0, &s1, &[], None, Some(delpre));
+        let pre = dip.pre().unwrap();
+
+        let mut kevery = Kevery::new();
+        kevery
+            .process_event(&delegator, &sign(&delegator, &d0), &[], None)
+            .unwrap();
+        let ixn = anchor(&mut kevery, &d0, &[&dip]);
+
+        // A source seal pointing elsewhere does not find the anchor
+        let wrong = SealSource::new(0, delegator.said_field().unwrap());
+        let processed = kevery
+            .process_event(&dip, &sign(&dip, &s0), &[], Some(&wrong))
+            .unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::Delegation));
+
+        let source = SealSource::new(1, ixn.said_field().unwrap());
+        let processed = kevery.process_event(&dip, &[], &[], Some(&source)).unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        assert_eq!(kevery.kever(pre).unwrap().delpre(), Some(delpre));
+    }
+
+    #[test]
+    fn test_kevery_delegation_dnd() {
+        let (d0, d1) = (signers(1, true), signers(1, true));
+        let delegator = incept(
+            keys(&d0),
+            None,
+            digs(&d1),
+            None,
+            None,
+            None,
+            Some(vec![TraitCodex::DO_NOT_DELEGATE.to_string()]),
+            None,
+            None,
+            None,
+            Some(matter_codes::BLAKE3_256),
+            false,
+            None,
+        )
+        .unwrap();
+        let delpre = delegator.pre().unwrap();
+        let (s0, s1) = (signers(1, true), signers(1, true));
+        let dip = icp(&s0, &s1, &[], None, Some(delpre));
+
+        let mut kevery = Kevery::new();
+        kevery
+            .process_event(&delegator, &sign(&delegator, &d0), &[], None)
+            .unwrap();
+        anchor(&mut kevery, &d0, &[&dip]);
+        assert!(kevery
+            .process_event(&dip, &sign(&dip, &s0), &[], None)
+            .is_err());
+        assert!(kevery.kever(dip.pre().unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_kevery_recovery_rotation() {
+        let (s0, s1, s2) = (signers(1, true), signers(1, true), signers(1, true));
+        let icp = icp(&s0, &s1, &[], None, None);
+        let pre = icp.pre().unwrap();
+        let ixn1 = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        let ixn2 = interact(pre, ixn1.said_field().unwrap(), 2, None, None).unwrap();
+
+        let mut kevery = Kevery::new();
+        for (event, signers) in [(&icp, &s0), (&ixn1, &s0), (&ixn2, &s0)] {
+            kevery
+                .process_event(event, &sign(event, signers), &[], None)
+                .unwrap();
+        }
+
+        // Rotation at sn 1 supersedes both interaction events
+        let recovery = rot(pre, &icp, 1, &s1, &s2);
+        let processed = kevery
+            .process_event(&recovery, &sign(&recovery, &s1), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Accepted);
+        let kever = kevery.kever(pre).unwrap();
+        assert_eq!(kever.sn(), 1);
+        assert_eq!(kever.keys(), keys(&s1));
+        assert_eq!(kevery.kel(pre).len(), 2);
+
+        // Establishment events are never superseded
+        let ixn = interact(pre, icp.said_field().unwrap(), 1, None, None).unwrap();
+        assert!(kevery
+            .process_event(&ixn, &sign(&ixn, &s0), &[], None)
+            .is_err());
+        let late = rot(pre, &icp, 1, &s1, &s0);
+        assert!(kevery
+            .process_event(&late, &sign(&late, &s1), &[], None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_kevery_delegated_recovery_rotation() {
+        let (d0, d1) = (signers(1, true), signers(1, true));
+        let delegator = icp(&d0, &d1, &[], None, None);
+        let delpre = delegator.pre().unwrap();
+        let (s0, s1, s2, s3) = (
+            signers(1, true),
+            signers(1, true),
+            signers(1, true),
+            signers(1, true),
+        );
+        let dip = icp(&s0, &s1, &[], None, Some(delpre));
+        let pre = dip.pre().unwrap();
+
+        let mut kevery = Kevery::new();
+        kevery
+            .process_event(&delegator, &sign(&delegator, &d0), &[], None)
+            .unwrap();
+        anchor(&mut kevery, &d0, &[&dip]);
+        kevery
+            .process_event(&dip, &sign(&dip, &s0), &[], None)
+            .unwrap();
+
+        let drt = rot(pre, &dip, 1, &s1, &s2);
+        let early = rot(pre, &dip, 1, &s1, &s0);
+        anchor(&mut kevery, &d0, &[&drt, &early]);
+        let processed = kevery
+            .process_event(&drt, &sign(&drt, &s1), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Accepted);
+
+        // Anchored no later than the accepted rotation, so duplicitous
+        assert!(matches!(
+            kevery.process_event(&early, &sign(&early, &s1), &[], None),
+            Err(SignifyError::DuplicitousEvent { .. })
+        ));
+
+        // Waits for its anchor, then supersedes the accepted rotation
+        let recovery = rot(pre, &dip, 1, &s1, &s3);
+        let processed = kevery
+            .process_event(&recovery, &sign(&recovery, &s1), &[], None)
+            .unwrap();
+        assert_eq!(processed, Processed::Escrowed(Escrow::Delegation));
+        anchor(&mut kevery, &d0, &[&recovery]);
+        let kever = kevery.kever(pre).unwrap();
+        assert_eq!(kever.said(), recovery.said_field().unwrap());
+        assert_eq!(kever.ndigs(), digs(&s3));
+    }
+}
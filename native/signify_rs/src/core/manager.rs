@@ -1240,6 +1240,76 @@ mod tests {
         assert_eq!(keys1.signers[2].qb64(), keys2.signers[2].qb64());
     }
 
+    #[test]
+    fn test_creators_secp256k1() {
+        let randy = RandyCreator::new();
+        let keys = randy
+            .create(
+                None,
+                2,
+                matter_codes::ECDSA_256K1_SEED,
+                true,
+                0,
+                0,
+                0,
+                false,
+            )
+            .unwrap();
+        assert_eq!(keys.signers.len(), 2);
+        for signer in &keys.signers {
+            assert_eq!(signer.verfer().code(), matter_codes::ECDSA_256K1);
+        }
+
+        // Salty keys are deterministic and can mix algorithms
+        let salty = SaltyCreator::new(Some("0AAwMTIzNDU2Nzg5YWJjZGVm"), None, None).unwrap();
+        let codes = vec![
+            matter_codes::ECDSA_256K1_SEED.to_string(),
+            matter_codes::ED25519_SEED.to_string(),
+        ];
+        let keys1 = salty
+            .create(Some(codes.clone()), 0, "", false, 0, 0, 0, true)
+            .unwrap();
+        let keys2 = salty
+            .create(Some(codes), 0, "", false, 0, 0, 0, true)
+            .unwrap();
+        assert_eq!(keys1.signers[0].verfer().code(), matter_codes::ECDSA_256K1N);
+        assert_eq!(keys1.signers[1].verfer().code(), matter_codes::ED25519N);
+        assert_eq!(
+            keys1.signers[0].verfer().qb64(),
+            keys2.signers[0].verfer().qb64()
+        );
+    }
+
+    #[test]
+    fn test_creators_ed448() {
+        let randy = RandyCreator::new();
+        let keys = randy
+            .create(None, 1, matter_codes::ED448_SEED, false, 0, 0, 0, false)
+            .unwrap();
+        assert_eq!(keys.signers[0].verfer().code(), matter_codes::ED448N);
+
+        let salty = SaltyCreator::new(Some("0AAwMTIzNDU2Nzg5YWJjZGVm"), None, None).unwrap();
+        let keys1 = salty
+            .create(None, 2, matter_codes::ED448_SEED, true, 0, 0, 0, true)
+            .unwrap();
+        let keys2 = salty
+            .create(None, 2, matter_codes::ED448_SEED, true, 0, 0, 0, true)
+            .unwrap();
+        assert_eq!(keys1.signers[0].verfer().code(), matter_codes::ED448);
+        assert_eq!(keys1.signers[0].qb64().len(), 76);
+        assert_eq!(
+            keys1.signers[1].verfer().qb64(),
+            keys2.signers[1].verfer().qb64()
+        );
+
+        let message = b"test message";
+        let cigar = keys1.signers[0].cigar(message).unwrap();
+        assert!(keys2.signers[0]
+            .verfer()
+            .verify(cigar.raw(), message)
+            .unwrap());
+    }
+
     #[test]
     fn test_salty_creator_different_paths() {
         let creator = SaltyCreator::new(None, Some(Tier::Low), Some("test")).unwrap();
@@ -1342,6 +1412,78 @@ mod tests {
         assert_eq!(sit.new.ridx, 1);
     }
 
+    #[test]
+    fn test_manager_secp256r1_incept_and_rotate() {
+        let salter = Salter::new(Tier::Low).unwrap();
+        let mut mgr = Manager::new(
+            Some(Box::new(Keeper::new())),
+            None,
+            None,
+            None,
+            Some(Algos::Salty),
+            Some(&salter),
+            Some(Tier::Low),
+        )
+        .unwrap();
+
+        // Mix a P-256 key with an Ed25519 key in the current set
+        let icodes = vec![
+            matter_codes::ECDSA_256R1_SEED.to_string(),
+            matter_codes::ED25519_SEED.to_string(),
+        ];
+        let ncodes = vec![matter_codes::ECDSA_256R1_SEED.to_string(); 2];
+        let (verfers, digers) = mgr
+            .incept(
+                Some(icodes),
+                0,
+                matter_codes::ED25519_SEED,
+                Some(ncodes.clone()),
+                0,
+                matter_codes::ED25519_SEED,
+                matter_codes::BLAKE3_256,
+                None,
+                None,
+                Some("p256"),
+                None,
+                true,
+                true,
+                true,
+            )
+            .unwrap();
+
+        assert_eq!(verfers[0].code(), matter_codes::ECDSA_256R1);
+        assert_eq!(verfers[1].code(), matter_codes::ED25519);
+        assert_eq!(digers.len(), 2);
+
+        // Signatures from regenerated keys verify against the P-256 key
+        let message = b"test message";
+        let sigs = mgr.sign(message, None, Some(&verfers), true, None).unwrap();
+        let sigs = String::from_utf8(sigs).unwrap();
+        let siger = Siger::from_qb64(&sigs[..88], Some(verfers[0].clone())).unwrap();
+        assert!(siger.verify(message).unwrap());
+
+        let pre = verfers[0].qb64().to_string();
+        let (new_verfers, new_digers) = mgr
+            .rotate(
+                &pre,
+                Some(ncodes),
+                0,
+                matter_codes::ED25519_SEED,
+                matter_codes::BLAKE3_256,
+                true,
+                true,
+            )
+            .unwrap();
+
+        // Prior next keys become current and match their digests
+        assert_eq!(new_verfers.len(), 2);
+        for (verfer, diger) in new_verfers.iter().zip(&digers) {
+            assert_eq!(verfer.code(), matter_codes::ECDSA_256R1);
+            assert!(diger.verify(verfer.qb64b()).unwrap());
+        }
+        assert_eq!(new_digers.len(), 2);
+    }
+
     #[test]
     fn test_manager_sign_fails_without_keys() {
         let mgr = Manager::new(
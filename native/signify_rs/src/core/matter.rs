@@ -1,10 +1,17 @@
-use crate::core::codes::{extract_code, raw_size, sizage};
+use crate::core::codes::{
+    extract_code, is_variable, raw_size, sizage, LARGE_VRZ_DEX, SMALL_VRZ_DEX,
+};
+use crate::core::utils::{b2_to_b64, b64_to_int, int_to_b64, read_int};
 /// Matter - Base class for all CESR primitives
 use crate::error::{Result, SignifyError};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 
 /// Matter is the base class for all CESR (Composable Event Streaming Representation) primitives.
 /// It handles encoding/decoding between raw bytes, qb64 (qualified base64), and qb2 (binary).
+///
+/// Raw material is pre-padded with zero bits so the code and the material each
+/// align on 24 bit boundaries. Variable size codes also prepend `ls` lead bytes
+/// and carry the material size in quadlets in their soft part.
 #[derive(Debug, Clone)]
 pub struct Matter {
     /// CESR code identifying the primitive type
@@ -53,29 +60,49 @@ impl Matter {
     }
 
     /// Create Matter from raw bytes and code
+    ///
+    /// For variable size codes any member of the family may be given (e.g.
+    /// `STR_B64_L0` or `BYTES_L0`); the code is adjusted to the lead size
+    /// required by `raw` and promoted to the large family when the material
+    /// does not fit the small soft size.
     pub fn from_raw(raw: &[u8], code: &str) -> Result<Self> {
-        // Validate code
-        let _sz = sizage(code)?;
-        let expected_size = raw_size(code)?;
+        let (code, soft) = if is_variable(code) {
+            Self::vrz_code(raw.len(), code)?
+        } else {
+            let expected_size = raw_size(code)?;
+            if raw.len() != expected_size {
+                return Err(SignifyError::InvalidSize {
+                    expected: expected_size,
+                    actual: raw.len(),
+                });
+            }
+            (code.to_string(), String::new())
+        };
 
-        if raw.len() != expected_size {
-            return Err(SignifyError::InvalidSize {
-                expected: expected_size,
-                actual: raw.len(),
-            });
+        let sz = sizage(&code)?;
+        let both = format!("{}{}", code, soft);
+        let cs = both.len();
+
+        // Pad size in chars equals the pad size in bytes needed to align raw
+        let ps = (3 - ((raw.len() + sz.ls) % 3)) % 3;
+        if ps != cs % 4 {
+            return Err(SignifyError::InvalidCode(format!(
+                "Invalid code size {} for raw size {} with code {}",
+                cs,
+                raw.len(),
+                code
+            )));
         }
 
-        // Encode to base64
-        let b64 = URL_SAFE_NO_PAD.encode(raw);
-        let qb64 = format!("{}{}", code, b64);
+        // Prepad raw with zeros so pad bits end up between code and material
+        let mut padded = vec![0u8; ps + sz.ls];
+        padded.extend_from_slice(raw);
+        let qb64 = format!("{}{}", both, &URL_SAFE_NO_PAD.encode(&padded)[ps..]);
+        let qb2 = URL_SAFE_NO_PAD.decode(&qb64)?;
         let qb64b = qb64.as_bytes().to_vec();
 
-        // Create qb2 (binary encoding)
-        let mut qb2 = code.as_bytes().to_vec();
-        qb2.extend_from_slice(raw);
-
         Ok(Self {
-            code: code.to_string(),
+            code,
             raw: raw.to_vec(),
             qb64,
             qb64b,
@@ -83,6 +110,32 @@ impl Matter {
         })
     }
 
+    /// Select the variable size code and soft size for raw material of `rs` bytes
+    fn vrz_code(rs: usize, code: &str) -> Result<(String, String)> {
+        let ls = (3 - (rs % 3)) % 3;
+        let size = (rs + ls) / 3;
+        let selector = code.chars().next().unwrap_or_default();
+        let hard = sizage(code)?.hs;
+        let kind = &code[hard - 1..hard];
+
+        if SMALL_VRZ_DEX.contains(&selector) && size < 64usize.pow(2) {
+            Ok((
+                format!("{}{}", SMALL_VRZ_DEX[ls], kind),
+                int_to_b64(size, 2),
+            ))
+        } else if size < 64usize.pow(4) {
+            Ok((
+                format!("{}AA{}", LARGE_VRZ_DEX[ls], kind),
+                int_to_b64(size, 4),
+            ))
+        } else {
+            Err(SignifyError::InvalidSize {
+                expected: (64usize.pow(4) - 1) * 3,
+                actual: rs,
+            })
+        }
+    }
+
     /// Create Matter from qb64 string
     pub fn from_qb64(qb64: &str) -> Result<Self> {
         if qb64.is_empty() {
@@ -92,37 +145,67 @@ impl Matter {
         // Extract code
         let code = extract_code(qb64)?;
         let sz = sizage(&code)?;
+        let cs = sz.hs + sz.ss;
 
-        // Validate length
-        if let Some(fs) = sz.fs {
-            if qb64.len() != fs {
-                return Err(SignifyError::InvalidSize {
-                    expected: fs,
-                    actual: qb64.len(),
-                });
-            }
+        if qb64.len() < cs {
+            return Err(SignifyError::InvalidCesr(format!(
+                "qb64 too short for code: {}",
+                qb64
+            )));
         }
 
-        // Extract and decode data
-        let b64_data = &qb64[sz.hs..];
-        let raw = URL_SAFE_NO_PAD
-            .decode(b64_data)
-            .map_err(|e| SignifyError::Base64Error(e))?;
+        // Variable size codes give the material size in quadlets in the soft part
+        let fs = match sz.fs {
+            Some(fs) => fs,
+            None => cs + b64_to_int(&qb64[sz.hs..cs])? * 4,
+        };
 
-        // Verify raw size
-        let expected_raw_size = raw_size(&code)?;
-        if raw.len() != expected_raw_size {
+        // Validate length
+        if qb64.len() != fs {
             return Err(SignifyError::InvalidSize {
-                expected: expected_raw_size,
-                actual: raw.len(),
+                expected: fs,
+                actual: qb64.len(),
             });
         }
 
-        let qb64b = qb64.as_bytes().to_vec();
+        // Replace the code with zero pad chars and decode
+        let ps = cs % 4;
+        let base = format!("{}{}", "A".repeat(ps), &qb64[cs..]);
+        let paw = URL_SAFE_NO_PAD.decode(base)?;
+        if paw.len() < ps + sz.ls {
+            return Err(SignifyError::InvalidCesr(format!(
+                "qb64 too short for lead size: {}",
+                qb64
+            )));
+        }
 
-        // Create qb2
-        let mut qb2 = code.as_bytes().to_vec();
-        qb2.extend_from_slice(&raw);
+        if read_int(&paw[..ps], ps) != 0 {
+            return Err(SignifyError::InvalidCesr(format!(
+                "Non zeroed prepad bits in qb64: {}",
+                qb64
+            )));
+        }
+        if paw[ps..ps + sz.ls].iter().any(|&b| b != 0) {
+            return Err(SignifyError::InvalidCesr(format!(
+                "Non zeroed lead bytes in qb64: {}",
+                qb64
+            )));
+        }
+        let raw = paw[ps + sz.ls..].to_vec();
+
+        // Verify raw size
+        if sz.fs.is_some() {
+            let expected_raw_size = raw_size(&code)?;
+            if raw.len() != expected_raw_size {
+                return Err(SignifyError::InvalidSize {
+                    expected: expected_raw_size,
+                    actual: raw.len(),
+                });
+            }
+        }
+
+        let qb64b = qb64.as_bytes().to_vec();
+        let qb2 = URL_SAFE_NO_PAD.decode(qb64)?;
 
         Ok(Self {
             code,
@@ -139,42 +222,45 @@ impl Matter {
             return Err(SignifyError::InvalidCesr("Empty qb2".to_string()));
         }
 
-        // Extract code
-        let code_str = String::from_utf8_lossy(&qb2[..std::cmp::min(4, qb2.len())]);
-        let code = extract_code(&code_str)?;
-        let sz = sizage(&code)?;
+        // Convert just enough leading bytes to read the code and soft size
+        let hard = extract_code(&b2_to_b64(qb2, 4.min(qb2.len() * 4 / 3)))?;
+        let sz = sizage(&hard)?;
+        let cs = sz.hs + sz.ss;
+        if qb2.len() * 4 / 3 < cs {
+            return Err(SignifyError::InvalidCesr(
+                "qb2 too short for code".to_string(),
+            ));
+        }
 
-        // Extract raw data
-        let raw = qb2[sz.hs..].to_vec();
+        let fs = match sz.fs {
+            Some(fs) => fs,
+            None => cs + b64_to_int(&b2_to_b64(qb2, cs)[sz.hs..])? * 4,
+        };
 
-        // Verify size
-        let expected_raw_size = raw_size(&code)?;
-        if raw.len() != expected_raw_size {
+        // Full size is always a multiple of 4 chars, so 3 bytes per quadlet
+        let bfs = fs * 3 / 4;
+        if qb2.len() != bfs {
             return Err(SignifyError::InvalidSize {
-                expected: expected_raw_size,
-                actual: raw.len(),
+                expected: bfs,
+                actual: qb2.len(),
             });
         }
 
-        // Encode to qb64
-        let b64 = URL_SAFE_NO_PAD.encode(&raw);
-        let qb64 = format!("{}{}", code, b64);
-        let qb64b = qb64.as_bytes().to_vec();
-
-        Ok(Self {
-            code,
-            raw,
-            qb64,
-            qb64b,
-            qb2: qb2.to_vec(),
-        })
+        Self::from_qb64(&URL_SAFE_NO_PAD.encode(qb2))
     }
-
     /// Get the CESR code
     pub fn code(&self) -> &str {
         &self.code
     }
 
+    /// Get the soft part of the code (size for variable size codes)
+    pub fn soft(&self) -> &str {
+        match sizage(&self.code) {
+            Ok(sz) => &self.qb64[sz.hs..sz.hs + sz.ss],
+            Err(_) => "",
+        }
+    }
+
     /// Get the raw binary data
     pub fn raw(&self) -> &[u8] {
         &self.raw
@@ -210,6 +296,7 @@ impl Matter {
 mod tests {
     use super::*;
     use crate::core::codes::matter_codes;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 
     #[test]
     fn test_matter_from_raw() {
@@ -285,6 +372,108 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_matter_prepad_encoding() {
+        // Pad bits sit between code and material, so a one char code on 32
+        // bytes leaves the first data char with its top two bits clear
+        let raw = vec![255u8; 32];
+        let matter = Matter::from_raw(&raw, matter_codes::ED25519).unwrap();
+        assert_eq!(&matter.qb64()[..2], "DP");
+        assert_eq!(matter.qb2().len(), 33);
+        assert_eq!(
+            matter.qb2(),
+            URL_SAFE_NO_PAD.decode(matter.qb64()).unwrap().as_slice()
+        );
+
+        // Non zeroed pad bits are rejected
+        let bad = format!("DS{}", &matter.qb64()[2..]);
+        assert!(Matter::from_qb64(&bad).is_err());
+    }
+
+    #[test]
+    fn test_matter_variable_lead_sizes() {
+        let cases = [
+            (0usize, matter_codes::STR_B64_L0),
+            (1, matter_codes::STR_B64_L2),
+            (2, matter_codes::STR_B64_L1),
+            (3, matter_codes::STR_B64_L0),
+            (4, matter_codes::STR_B64_L2),
+            (5, matter_codes::STR_B64_L1),
+        ];
+        for (rs, code) in cases {
+            let raw: Vec<u8> = (0..rs as u8).map(|b| b + 1).collect();
+            let matter = Matter::from_raw(&raw, matter_codes::STR_B64_L0).unwrap();
+            assert_eq!(matter.code(), code);
+            assert_eq!(matter.qb64().len() % 4, 0);
+            assert_eq!(matter.soft(), int_to_b64(rs.div_ceil(3), 2));
+
+            let from_qb64 = Matter::from_qb64(matter.qb64()).unwrap();
+            assert_eq!(from_qb64.raw(), raw.as_slice());
+            assert_eq!(from_qb64.code(), code);
+
+            let from_qb64b = Matter::new(MatterOpts {
+                qb64b: Some(matter.qb64b().to_vec()),
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(from_qb64b.raw(), raw.as_slice());
+
+            let from_qb2 = Matter::from_qb2(matter.qb2()).unwrap();
+            assert_eq!(from_qb2.raw(), raw.as_slice());
+            assert_eq!(from_qb2.qb64(), matter.qb64());
+        }
+    }
+
+    #[test]
+    fn test_matter_variable_known_values() {
+        // Lead size 1: two raw bytes are prepended with one zero byte
+        let matter = Matter::from_raw(b"ab", matter_codes::BYTES_L0).unwrap();
+        assert_eq!(matter.code(), matter_codes::BYTES_L1);
+        assert_eq!(matter.qb64(), "5BABAGFi");
+
+        let matter = Matter::from_raw(b"abc", matter_codes::BYTES_L0).unwrap();
+        assert_eq!(matter.qb64(), "4BABYWJj");
+
+        let empty = Matter::from_raw(b"", matter_codes::STR_B64_L0).unwrap();
+        assert_eq!(empty.qb64(), "4AAA");
+        assert!(Matter::from_qb64("4AAA").unwrap().raw().is_empty());
+
+        // Non zeroed lead byte is rejected
+        assert!(Matter::from_qb64("5BABAWFi").is_err());
+    }
+
+    #[test]
+    fn test_matter_variable_big_promotion() {
+        let raw = vec![7u8; 64 * 64 * 3];
+        let matter = Matter::from_raw(&raw, matter_codes::BYTES_L0).unwrap();
+        assert_eq!(matter.code(), matter_codes::BYTES_BIG_L0);
+        assert_eq!(matter.soft(), "ABAA");
+
+        let raw = vec![9u8; 64 * 64 * 3 + 1];
+        let matter = Matter::from_raw(&raw, matter_codes::STR_B64_BIG_L0).unwrap();
+        assert_eq!(matter.code(), matter_codes::STR_B64_BIG_L2);
+        assert_eq!(
+            Matter::from_qb64(matter.qb64()).unwrap().raw(),
+            raw.as_slice()
+        );
+        assert_eq!(
+            Matter::from_qb2(matter.qb2()).unwrap().raw(),
+            raw.as_slice()
+        );
+
+        // Large codes are never demoted to the small family
+        let matter = Matter::from_raw(&[1, 2, 3, 4], matter_codes::BYTES_BIG_L0).unwrap();
+        assert_eq!(matter.code(), matter_codes::BYTES_BIG_L2);
+    }
+
+    #[test]
+    fn test_matter_qb2_truncated() {
+        let matter = Matter::from_raw(b"abcdef", matter_codes::BYTES_L0).unwrap();
+        let qb2 = matter.qb2();
+        assert!(Matter::from_qb2(&qb2[..qb2.len() - 1]).is_err());
+        assert!(Matter::from_qb64(&matter.qb64()[..6]).is_err());
+    }
+
     #[test]
     fn test_matter_empty_material() {
         let result = Matter::new(MatterOpts::default());
@@ -1,48 +1,81 @@
 /// Core KERI primitives
+pub mod bada;
+pub mod basing;
+pub mod bexter;
 pub mod cigar;
 pub mod cipher;
 pub mod codes;
 pub mod counter;
+pub mod dater;
 pub mod decrypter;
 pub mod diger;
 pub mod encrypter;
 pub mod eventing;
+pub mod exchanging;
 pub mod indexer;
+pub mod kever;
+pub mod kevery;
 pub mod manager;
 pub mod matter;
+pub mod pather;
 pub mod prefixer;
+pub mod routing;
 pub mod saider;
 pub mod salter;
+pub mod seal;
 pub mod seqner;
 pub mod serder;
 pub mod siger;
 pub mod signer;
+pub mod state;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod texter;
+pub mod tholder;
 pub mod utils;
 pub mod verfer;
 
-// TODO: Implement these modules (see IMPLEMENTATION_GUIDE.md)
-// pub mod tholder;
-
+pub use bada::AcceptedReply;
+pub use basing::{Baser, FirstSeen};
+pub use bexter::Bexter;
 pub use cigar::Cigar;
 pub use cipher::Cipher;
-pub use codes::{counter_codes, indexer_codes, matter_codes};
-pub use counter::{Counter, CounterCodex};
+#[allow(deprecated)]
+pub use codes::counter_codes;
+pub use codes::{indexer_codes, matter_codes};
+pub use counter::{Counter, CounterCodex, CounterCodexV2};
+pub use dater::Dater;
 pub use decrypter::{DecryptedMatter, Decrypter};
 pub use diger::Diger;
 pub use encrypter::Encrypter;
-pub use eventing::incept;
+pub use eventing::{
+    end_role_add, end_role_cut, incept, interact, key_state_notice, loc_scheme, messagize, query,
+    receipt, reply, rotate, MessagizeOpts, RotateArgs,
+};
+pub use exchanging::{exchange, Embed};
 pub use indexer::{Indexer, IndexerCodex};
+pub use kever::Kever;
+pub use kevery::{AidVerification, Duplicity, Escrow, KelEvent, Kevery, KeyStateNotice, Processed};
 pub use manager::{
     ri_key, Algos, Creator, Creatory, Keeper, KeyStore, Keys, Manager, PrePrm, PreSit, PubLot,
     PubPath, PubSet, RandyCreator, SaltyCreator,
 };
 pub use matter::{Matter, MatterOpts};
+pub use pather::Pather;
 pub use prefixer::{DerivationCode, Prefixer};
+pub use routing::{EndRoles, LocSchemes, ReplyHandler, Revery};
 pub use saider::Saider;
 pub use salter::{Salter, Tier};
+pub use seal::{
+    parse_seals, seals_to_data, Seal, SealDigest, SealEvent, SealLast, SealRoot, SealSource,
+    SealTrans,
+};
 pub use seqner::Seqner;
 pub use serder::Serder;
 pub use siger::Siger;
 pub use signer::{IndexedSignature, Signer};
+pub use state::{KeyStateRecord, StateEstEvent};
+pub use texter::Texter;
+pub use tholder::{Fraction, Thold, Tholder, Weight};
 pub use utils::*;
 pub use verfer::Verfer;
@@ -0,0 +1,95 @@
+/// Pather - SAD paths as CESR primitives
+///
+/// A SAD path locates a nested field of a self-addressing data structure,
+/// e.g. `["e", "acdc"]` for the `acdc` embed of an exchange message. The path
+/// is joined into Base64 text with `-` separators, so `["e", "acdc"]` becomes
+/// `-e-acdc` and the root path is `-`, and carried as a Bexter.
+use crate::core::bexter::Bexter;
+use crate::error::{Result, SignifyError};
+
+/// SAD path primitive
+#[derive(Debug, Clone)]
+pub struct Pather {
+    bexter: Bexter,
+}
+
+impl Pather {
+    /// Create Pather from path components
+    pub fn new(path: &[&str]) -> Result<Self> {
+        if let Some(part) = path.iter().find(|p| p.is_empty() || p.contains('-')) {
+            return Err(SignifyError::InvalidInput(format!(
+                "Invalid path component: {:?}",
+                part
+            )));
+        }
+        let bext = format!("-{}", path.join("-"));
+        Ok(Self {
+            bexter: Bexter::new(&bext)?,
+        })
+    }
+
+    /// Create Pather from qb64
+    pub fn from_qb64(qb64: &str) -> Result<Self> {
+        Self::from_bexter(Bexter::from_qb64(qb64)?)
+    }
+
+    /// Create Pather from qb2
+    pub fn from_qb2(qb2: &[u8]) -> Result<Self> {
+        Self::from_bexter(Bexter::from_qb2(qb2)?)
+    }
+
+    fn from_bexter(bexter: Bexter) -> Result<Self> {
+        if !bexter.bext().starts_with('-') {
+            return Err(SignifyError::InvalidFormat(format!(
+                "Invalid SAD path: {}",
+                bexter.bext()
+            )));
+        }
+        Ok(Self { bexter })
+    }
+
+    /// Get the path components
+    pub fn path(&self) -> Vec<String> {
+        let bext = self.bexter.bext();
+        bext[1..]
+            .split('-')
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Get qb64 encoding
+    pub fn qb64(&self) -> &str {
+        self.bexter.qb64()
+    }
+
+    /// Get qb2 encoding
+    pub fn qb2(&self) -> &[u8] {
+        self.bexter.qb2()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pather() {
+        let pather = Pather::new(&["e", "acdc"]).unwrap();
+        assert_eq!(pather.qb64(), "4AACA-e-acdc");
+        assert_eq!(pather.path(), ["e", "acdc"]);
+
+        let pather = Pather::from_qb64(pather.qb64()).unwrap();
+        assert_eq!(pather.path(), ["e", "acdc"]);
+        let pather = Pather::from_qb2(pather.qb2()).unwrap();
+        assert_eq!(pather.path(), ["e", "acdc"]);
+
+        let root = Pather::new(&[]).unwrap();
+        assert!(root.path().is_empty());
+        assert_eq!(Pather::from_qb64(root.qb64()).unwrap().path().len(), 0);
+
+        assert!(Pather::new(&["a-b"]).is_err());
+        assert!(Pather::new(&["a", ""]).is_err());
+        assert!(Pather::new(&["a b"]).is_err());
+    }
+}
@@ -1,16 +1,18 @@
 /// Prefixer - Prefix derivation and verification for KERI identifiers
 ///
 /// Prefixer handles identifier prefix derivation from inception events (icp, dip, vcp).
-/// Supports three derivation methods:
-/// - Ed25519N: Non-transferable single key (prefix = key)
-/// - Ed25519: Transferable single key (prefix = key)
-/// - Blake3_256: Self-addressing (prefix = BLAKE3 hash of event)
+/// Supports these derivation methods:
+/// - Basic: Single key (prefix = key), non-transferable or transferable, for
+///   Ed25519, Ed448, ECDSA secp256k1 and ECDSA secp256r1 keys
+/// - Digests: Self-addressing (prefix = digest of event), for every digest code
+///   (Blake3, Blake2b, Blake2s, SHA3 and SHA2 in 256 and 512 bit sizes)
+use crate::core::codes::sizage;
+use crate::core::diger::Diger;
 use crate::core::matter::Matter;
 use crate::core::matter_codes;
 use crate::core::serder::Serder;
 use crate::core::verfer::Verfer;
 use crate::error::{Result, SignifyError};
-use blake3;
 use serde_json::Value;
 
 const DUMMY: char = '#';
@@ -18,9 +20,23 @@ const DUMMY: char = '#';
 /// Derivation method for prefix
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DerivationCode {
-    Ed25519N,  // Non-transferable
-    Ed25519,   // Transferable
-    Blake3256, // Self-addressing
+    Ed25519N,    // Non-transferable
+    Ed25519,     // Transferable
+    Ecdsa256k1N, // Non-transferable
+    Ecdsa256k1,  // Transferable
+    Ecdsa256r1N, // Non-transferable
+    Ecdsa256r1,  // Transferable
+    Ed448N,      // Non-transferable
+    Ed448,       // Transferable
+    Blake3256,   // Self-addressing
+    Blake2b256,  // Self-addressing
+    Blake2s256,  // Self-addressing
+    Sha3256,     // Self-addressing
+    Sha2256,     // Self-addressing
+    Blake3512,   // Self-addressing
+    Blake2b512,  // Self-addressing
+    Sha3512,     // Self-addressing
+    Sha2512,     // Self-addressing
 }
 
 impl DerivationCode {
@@ -28,7 +44,21 @@ impl DerivationCode {
         match code {
             matter_codes::ED25519N => Ok(Self::Ed25519N),
             matter_codes::ED25519 => Ok(Self::Ed25519),
+            matter_codes::ECDSA_256K1N => Ok(Self::Ecdsa256k1N),
+            matter_codes::ECDSA_256K1 => Ok(Self::Ecdsa256k1),
+            matter_codes::ECDSA_256R1N => Ok(Self::Ecdsa256r1N),
+            matter_codes::ECDSA_256R1 => Ok(Self::Ecdsa256r1),
+            matter_codes::ED448N => Ok(Self::Ed448N),
+            matter_codes::ED448 => Ok(Self::Ed448),
             matter_codes::BLAKE3_256 => Ok(Self::Blake3256),
+            matter_codes::BLAKE2B_256 => Ok(Self::Blake2b256),
+            matter_codes::BLAKE2S_256 => Ok(Self::Blake2s256),
+            matter_codes::SHA3_256 => Ok(Self::Sha3256),
+            matter_codes::SHA2_256 => Ok(Self::Sha2256),
+            matter_codes::BLAKE3_512 => Ok(Self::Blake3512),
+            matter_codes::BLAKE2B_512 => Ok(Self::Blake2b512),
+            matter_codes::SHA3_512 => Ok(Self::Sha3512),
+            matter_codes::SHA2_512 => Ok(Self::Sha2512),
             _ => Err(SignifyError::InvalidCode(format!(
                 "Unsupported derivation code: {}",
                 code
@@ -36,16 +66,54 @@ impl DerivationCode {
         }
     }
 
-    fn to_code(&self) -> &'static str {
+    fn to_code(self) -> &'static str {
         match self {
             Self::Ed25519N => matter_codes::ED25519N,
             Self::Ed25519 => matter_codes::ED25519,
+            Self::Ecdsa256k1N => matter_codes::ECDSA_256K1N,
+            Self::Ecdsa256k1 => matter_codes::ECDSA_256K1,
+            Self::Ecdsa256r1N => matter_codes::ECDSA_256R1N,
+            Self::Ecdsa256r1 => matter_codes::ECDSA_256R1,
+            Self::Ed448N => matter_codes::ED448N,
+            Self::Ed448 => matter_codes::ED448,
             Self::Blake3256 => matter_codes::BLAKE3_256,
+            Self::Blake2b256 => matter_codes::BLAKE2B_256,
+            Self::Blake2s256 => matter_codes::BLAKE2S_256,
+            Self::Sha3256 => matter_codes::SHA3_256,
+            Self::Sha2256 => matter_codes::SHA2_256,
+            Self::Blake3512 => matter_codes::BLAKE3_512,
+            Self::Blake2b512 => matter_codes::BLAKE2B_512,
+            Self::Sha3512 => matter_codes::SHA3_512,
+            Self::Sha2512 => matter_codes::SHA2_512,
         }
     }
+
+    /// Whether the prefix is a digest of the inception event
+    pub fn is_digestive(self) -> bool {
+        !matches!(
+            self,
+            Self::Ed25519N
+                | Self::Ed25519
+                | Self::Ecdsa256k1N
+                | Self::Ecdsa256k1
+                | Self::Ecdsa256r1N
+                | Self::Ecdsa256r1
+                | Self::Ed448N
+                | Self::Ed448
+        )
+    }
+
+    /// Whether the prefix is a key that can never be rotated
+    pub fn is_non_transferable(self) -> bool {
+        matches!(
+            self,
+            Self::Ed25519N | Self::Ecdsa256k1N | Self::Ecdsa256r1N | Self::Ed448N
+        )
+    }
 }
 
 /// Prefixer for KERI identifier prefix handling
+#[derive(Debug, Clone)]
 pub struct Prefixer {
     matter: Matter,
     derivation: DerivationCode,
@@ -64,6 +132,12 @@ impl Prefixer {
         Self::new(matter)
     }
 
+    /// Create Prefixer from qb2
+    pub fn from_qb2(qb2: &[u8]) -> Result<Self> {
+        let matter = Matter::from_qb2(qb2)?;
+        Self::new(matter)
+    }
+
     /// Create Prefixer by deriving from inception event
     pub fn from_event(serder: &Serder, code: Option<&str>) -> Result<Self> {
         let ilk = serder
@@ -104,10 +178,10 @@ impl Prefixer {
             Matter::from_qb64(prefix_qb64)?
         } else {
             // No prefix yet, derive it
-            let (raw, code) = match derivation {
-                DerivationCode::Ed25519N => Self::derive_ed25519n(sad)?,
-                DerivationCode::Ed25519 => Self::derive_ed25519(sad)?,
-                DerivationCode::Blake3256 => Self::derive_blake3_256(serder)?,
+            let (raw, code) = if derivation.is_digestive() {
+                Self::derive_digest(serder, derivation.to_code())?
+            } else {
+                Self::derive_basic(sad, derivation)?
             };
             Matter::from_raw(&raw, code)?
         };
@@ -115,8 +189,8 @@ impl Prefixer {
         Ok(Self { matter, derivation })
     }
 
-    /// Derive Ed25519N (non-transferable) prefix from event
-    fn derive_ed25519n(sad: &Value) -> Result<(Vec<u8>, &'static str)> {
+    /// Derive basic prefix from the single signing key of an event
+    fn derive_basic(sad: &Value, derivation: DerivationCode) -> Result<(Vec<u8>, &'static str)> {
         let keys = sad
             .get("k")
             .and_then(|v| v.as_array())
@@ -135,98 +209,69 @@ impl Prefixer {
 
         let verfer = Verfer::from_qb64(key_qb64)?;
 
-        if verfer.code() != matter_codes::ED25519N {
+        let code = derivation.to_code();
+        if verfer.code() != code {
             return Err(SignifyError::InvalidCode(format!(
                 "Mismatch derivation code: expected {}, got {}",
-                matter_codes::ED25519N,
+                code,
                 verfer.code()
             )));
         }
 
-        // Non-transferable identifiers must have empty next keys
-        let next = sad.get("n").and_then(|v| v.as_array());
-        if let Some(n) = next {
-            if !n.is_empty() {
-                return Err(SignifyError::InvalidFormat(
-                    "Non-empty next keys for non-transferable identifier".to_string(),
-                ));
+        if derivation.is_non_transferable() {
+            // Non-transferable identifiers must have empty next keys
+            let next = sad.get("n").and_then(|v| v.as_array());
+            if let Some(n) = next {
+                if !n.is_empty() {
+                    return Err(SignifyError::InvalidFormat(
+                        "Non-empty next keys for non-transferable identifier".to_string(),
+                    ));
+                }
             }
-        }
 
-        // Non-transferable identifiers must have empty backers
-        let backers = sad.get("b").and_then(|v| v.as_array());
-        if let Some(b) = backers {
-            if !b.is_empty() {
-                return Err(SignifyError::InvalidFormat(
-                    "Non-empty backers for non-transferable identifier".to_string(),
-                ));
+            // Non-transferable identifiers must have empty backers
+            let backers = sad.get("b").and_then(|v| v.as_array());
+            if let Some(b) = backers {
+                if !b.is_empty() {
+                    return Err(SignifyError::InvalidFormat(
+                        "Non-empty backers for non-transferable identifier".to_string(),
+                    ));
+                }
             }
-        }
 
-        // Non-transferable identifiers must have empty anchors
-        let anchors = sad.get("a").and_then(|v| v.as_array());
-        if let Some(a) = anchors {
-            if !a.is_empty() {
-                return Err(SignifyError::InvalidFormat(
-                    "Non-empty anchors for non-transferable identifier".to_string(),
-                ));
+            // Non-transferable identifiers must have empty anchors
+            let anchors = sad.get("a").and_then(|v| v.as_array());
+            if let Some(a) = anchors {
+                if !a.is_empty() {
+                    return Err(SignifyError::InvalidFormat(
+                        "Non-empty anchors for non-transferable identifier".to_string(),
+                    ));
+                }
             }
         }
 
-        let raw = verfer.raw().to_vec();
-        let code = matter_codes::ED25519N;
-        Ok((raw, code))
+        Ok((verfer.raw().to_vec(), code))
     }
 
-    /// Derive Ed25519 (transferable) prefix from event
-    fn derive_ed25519(sad: &Value) -> Result<(Vec<u8>, &'static str)> {
-        let keys = sad
-            .get("k")
-            .and_then(|v| v.as_array())
-            .ok_or_else(|| SignifyError::InvalidFormat("Missing 'k' field".to_string()))?;
-
-        if keys.len() != 1 {
-            return Err(SignifyError::InvalidFormat(format!(
-                "Basic derivation needs exactly 1 key, got {}",
-                keys.len()
-            )));
-        }
-
-        let key_qb64 = keys[0]
-            .as_str()
-            .ok_or_else(|| SignifyError::InvalidFormat("Key must be string".to_string()))?;
-
-        let verfer = Verfer::from_qb64(key_qb64)?;
-
-        if verfer.code() != matter_codes::ED25519 {
-            return Err(SignifyError::InvalidCode(format!(
-                "Mismatch derivation code: expected {}, got {}",
-                matter_codes::ED25519,
-                verfer.code()
-            )));
-        }
-
-        let raw = verfer.raw().to_vec();
-        let code = matter_codes::ED25519;
-        Ok((raw, code))
-    }
-
-    /// Derive Blake3-256 (self-addressing) prefix from event
-    fn derive_blake3_256(serder: &Serder) -> Result<(Vec<u8>, &'static str)> {
+    /// Derive self-addressing prefix from event with the given digest code
+    fn derive_digest(serder: &Serder, code: &'static str) -> Result<(Vec<u8>, &'static str)> {
         let ilk = serder
             .ilk()
             .ok_or_else(|| SignifyError::InvalidFormat("Missing ilk field".to_string()))?;
 
         if ilk != "icp" && ilk != "dip" && ilk != "vcp" {
             return Err(SignifyError::InvalidFormat(format!(
-                "Invalid ilk {} for BLAKE3 derivation",
+                "Invalid ilk {} for digest derivation",
                 ilk
             )));
         }
 
-        // Create a copy of the event with dummy prefix
+        // Create a copy of the event with dummy prefix of the digest's qb64 length
+        let fs = sizage(code)?
+            .fs
+            .ok_or_else(|| SignifyError::InvalidCode(format!("No qb64 size for code {}", code)))?;
         let mut sad = serder.sad().clone();
-        let dummy_prefix = DUMMY.to_string().repeat(44); // BLAKE3-256 qb64 length is 44
+        let dummy_prefix = DUMMY.to_string().repeat(fs);
 
         sad["i"] = Value::String(dummy_prefix.clone());
         sad["d"] = Value::String(dummy_prefix);
@@ -235,10 +280,9 @@ impl Prefixer {
         let temp_serder = Serder::new(sad, None, None)?;
 
         // Hash the serialized event
-        let raw_bytes = temp_serder.raw().as_bytes();
-        let hash = blake3::hash(raw_bytes);
+        let raw = Diger::compute_digest(code, temp_serder.raw().as_bytes())?;
 
-        Ok((hash.as_bytes().to_vec(), matter_codes::BLAKE3_256))
+        Ok((raw, code))
     }
 
     /// Verify that the prefix matches the event
@@ -254,16 +298,16 @@ impl Prefixer {
             )));
         }
 
-        let result = match self.derivation {
-            DerivationCode::Ed25519N => self.verify_ed25519n(serder, prefixed),
-            DerivationCode::Ed25519 => self.verify_ed25519(serder, prefixed),
-            DerivationCode::Blake3256 => self.verify_blake3_256(serder, prefixed),
+        let result = if self.derivation.is_digestive() {
+            self.verify_digest(serder, prefixed)
+        } else {
+            self.verify_basic(serder, prefixed)
         };
 
         Ok(result.unwrap_or(false))
     }
 
-    fn verify_ed25519n(&self, serder: &Serder, prefixed: bool) -> Result<bool> {
+    fn verify_basic(&self, serder: &Serder, prefixed: bool) -> Result<bool> {
         let sad = serder.sad();
         let pre = self.qb64();
 
@@ -296,60 +340,26 @@ impl Prefixer {
         }
 
         // Check empty next keys
-        if let Some(n) = sad.get("n").and_then(|v| v.as_array()) {
-            if !n.is_empty() {
-                return Ok(false);
+        if self.derivation.is_non_transferable() {
+            if let Some(n) = sad.get("n").and_then(|v| v.as_array()) {
+                if !n.is_empty() {
+                    return Ok(false);
+                }
             }
         }
 
         Ok(true)
     }
 
-    fn verify_ed25519(&self, serder: &Serder, prefixed: bool) -> Result<bool> {
-        let sad = serder.sad();
-        let pre = self.qb64();
-
-        let keys = match sad.get("k").and_then(|v| v.as_array()) {
-            Some(k) => k,
-            None => return Ok(false),
-        };
-
-        if keys.len() != 1 {
+    fn verify_digest(&self, serder: &Serder, prefixed: bool) -> Result<bool> {
+        // For self-addressing prefixes, re-derive the digest from the event
+        let (raw, _) = Self::derive_digest(serder, self.derivation.to_code())?;
+        if raw != self.raw() {
             return Ok(false);
         }
 
-        let key = match keys[0].as_str() {
-            Some(k) => k,
-            None => return Ok(false),
-        };
-
-        if key != pre {
-            return Ok(false);
-        }
-
-        if prefixed {
-            let i = match sad.get("i").and_then(|v| v.as_str()) {
-                Some(i) => i,
-                None => return Ok(false),
-            };
-            if i != pre {
-                return Ok(false);
-            }
-        }
-
-        Ok(true)
-    }
-
-    fn verify_blake3_256(&self, serder: &Serder, prefixed: bool) -> Result<bool> {
-        // For Blake3-256 (self-addressing), verify by checking the SAID in the serder
-        // The serder's SAID should match our prefix
-        let said = match serder.said_field() {
-            Some(s) => s,
-            None => return Ok(false),
-        };
-
         // The 'd' field should match our prefix for self-addressing identifiers
-        if said != self.qb64() {
+        if serder.said_field() != Some(self.qb64()) {
             return Ok(false);
         }
 
@@ -409,7 +419,7 @@ mod tests {
     #[test]
     fn test_prefixer_from_qb64() {
         // Ed25519 key
-        let qb64 = "DSuhyBcPZEZLK-fcw5tzHn2N46wRCG_ZOoeKtWTOunRA";
+        let qb64 = "DKxy2sgzfplyr-tgwIxS19f2OchFHtLwPWD3v4oYimBx";
         let prefixer = Prefixer::from_qb64(qb64).unwrap();
         assert_eq!(prefixer.qb64(), qb64);
         assert_eq!(prefixer.code(), matter_codes::ED25519);
@@ -540,6 +550,62 @@ mod tests {
         assert_eq!(prefixer.qb64(), identifier);
     }
 
+    #[test]
+    fn test_prefixer_digest_derivations() {
+        let salt = [4u8; 16];
+        let salter = Salter::from_raw(&salt, Tier::Low).unwrap();
+        let signer = salter
+            .signer(matter_codes::ED25519_SEED, true, "test:0", None, true)
+            .unwrap();
+        let next = salter
+            .signer(matter_codes::ED25519_SEED, true, "test:1", None, true)
+            .unwrap();
+        let keys = vec![signer.verfer().qb64().to_string()];
+
+        for code in crate::core::codes::DIGEST_CODES {
+            let ndigs = vec![Diger::new(code, next.verfer().qb64b())
+                .unwrap()
+                .qb64()
+                .to_string()];
+            let serder = incept(
+                keys.clone(),
+                None,
+                ndigs,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(code),
+                false,
+                None,
+            )
+            .unwrap();
+
+            let pre = serder.pre().unwrap().to_string();
+            assert!(pre.starts_with(code));
+            assert_eq!(serder.said_field(), Some(pre.as_str()));
+            assert_eq!(serder.said(None).unwrap(), pre);
+            assert_eq!(
+                Serder::from_raw(serder.raw()).unwrap().said(None).unwrap(),
+                pre
+            );
+
+            let prefixer = Prefixer::from_event(&serder, Some(code)).unwrap();
+            assert!(prefixer.derivation().is_digestive());
+            assert_eq!(prefixer.qb64(), pre);
+            assert!(prefixer.verify(&serder, true).unwrap());
+
+            // Altered event no longer verifies against the prefix
+            let mut sad = serder.sad().clone();
+            sad["bt"] = serde_json::json!("1");
+            let altered = Serder::new(sad, None, None).unwrap();
+            assert!(!prefixer.verify(&altered, true).unwrap());
+        }
+    }
+
     #[test]
     fn test_prefixer_invalid_ilk() {
         use serde_json::json;
@@ -0,0 +1,447 @@
+//! Revery - Reply message processor
+//!
+//! Revery dispatches reply (`rpy`) messages by route to the handler of the
+//! longest matching route, analogous to the keripy Router and Revery. A
+//! handler registered for `/end/role` handles `/end/role/add` and
+//! `/end/role/cut`. The handler names the identifier that must sign the
+//! reply and the key of the record the reply updates. Endpoint role and
+//! location scheme handlers are registered by default.
+//!
+//! Replies are accepted under the best available data acceptance (BADA)
+//! rules. A reply must be signed by the identifier its handler names: a
+//! transferable signer with indexed signatures (`-F`) that verify against
+//! the establishment event of its KEL they name, a non-transferable signer
+//! with a receipt couple (`-C`). A reply replaces the reply accepted for the
+//! same record only when signed at a later establishment event of the
+//! signer or, at the same one, with a later datetime.
+//!
+//! With a `Baser` attached, accepted replies are stored before their
+//! handler applies them, and restored on startup and applied again by the
+//! handler of their route, including handlers added later.
+
+use crate::cesr_parser::Message;
+use crate::core::bada::{fresher, parse_dt, AcceptedReply};
+use crate::core::basing::Baser;
+use crate::core::eventing::{ILK_RPY, ROUTE_END_ROLE_ADD, ROUTE_END_ROLE_CUT, ROUTE_LOC_SCHEME};
+use crate::core::kevery::Kevery;
+use crate::error::{Result, SignifyError};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// Route of the endpoint role handler
+pub const ROUTE_END_ROLE: &str = "/end/role";
+
+/// Handler for the replies on a route
+pub trait ReplyHandler: Debug {
+    /// Identifier that must sign a reply on `route` with body `data` and the
+    /// key of the record it updates
+    fn authorize(&self, route: &str, data: &Value) -> Result<(String, String)>;
+
+    /// Apply a reply once accepted
+    fn apply(&mut self, _reply: &AcceptedReply) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Endpoint role authorizations and revocations on `/end/role/add` and
+/// `/end/role/cut`, signed by the controller `cid` and kept by
+/// `(cid, role, eid)`
+#[derive(Debug, Clone, Default)]
+pub struct EndRoles;
+
+impl ReplyHandler for EndRoles {
+    fn authorize(&self, route: &str, data: &Value) -> Result<(String, String)> {
+        if route != ROUTE_END_ROLE_ADD && route != ROUTE_END_ROLE_CUT {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Unsupported endpoint role route {}",
+                route
+            )));
+        }
+        let cid = field(data, "cid", route)?;
+        let role = field(data, "role", route)?;
+        let eid = field(data, "eid", route)?;
+        Ok((cid.to_string(), record_key(&[cid, role, eid])))
+    }
+}
+
+/// Endpoint locations on `/loc/scheme`, signed by the endpoint `eid` and
+/// kept by `(eid, scheme)`
+#[derive(Debug, Clone, Default)]
+pub struct LocSchemes;
+
+impl ReplyHandler for LocSchemes {
+    fn authorize(&self, route: &str, data: &Value) -> Result<(String, String)> {
+        if route != ROUTE_LOC_SCHEME {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Unsupported location route {}",
+                route
+            )));
+        }
+        let eid = field(data, "eid", route)?;
+        let scheme = field(data, "scheme", route)?;
+        if !data["url"].is_string() {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Missing url for {}",
+                route
+            )));
+        }
+        Ok((eid.to_string(), record_key(&[eid, scheme])))
+    }
+}
+
+/// Reply message processor
+#[derive(Debug)]
+pub struct Revery {
+    routes: Vec<(String, Box<dyn ReplyHandler>)>,
+    replies: HashMap<(String, String), AcceptedReply>,
+    db: Option<Baser>,
+}
+
+impl Default for Revery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Revery {
+    /// Create processor with the endpoint role and location scheme handlers
+    pub fn new() -> Self {
+        let routes: Vec<(String, Box<dyn ReplyHandler>)> = vec![
+            (ROUTE_END_ROLE.to_string(), Box::new(EndRoles)),
+            (ROUTE_LOC_SCHEME.to_string(), Box::new(LocSchemes)),
+        ];
+        Self {
+            routes,
+            replies: HashMap::new(),
+            db: None,
+        }
+    }
+
+    /// Create processor storing accepted replies in `db`, with the replies
+    /// stored there restored and applied by their handlers
+    pub fn with_baser(db: Baser) -> Result<Self> {
+        let mut revery = Self::new();
+        for (route, key, reply) in db.replies()? {
+            revery.replies.insert((route, key), reply);
+        }
+        for (route, handler) in &mut revery.routes {
+            restore(&revery.replies, route, handler.as_mut())?;
+        }
+        revery.db = Some(db);
+        Ok(revery)
+    }
+
+    /// Register `handler` for `route` and the routes below it, replacing the
+    /// handler registered for it before
+    ///
+    /// The handler applies the replies accepted for its route before.
+    pub fn add_route(&mut self, route: &str, mut handler: Box<dyn ReplyHandler>) -> Result<()> {
+        restore(&self.replies, route, handler.as_mut())?;
+        self.routes.retain(|(r, _)| r != route);
+        self.routes.push((route.to_string(), handler));
+        Ok(())
+    }
+
+    /// Process a signed reply message from the CESR parser
+    ///
+    /// Returns whether the reply was accepted, `false` when the reply
+    /// accepted for its record before is as fresh or fresher. `kevery`
+    /// provides the key state of transferable signers.
+    pub fn process_reply(&mut self, kevery: &Kevery, message: &Message) -> Result<bool> {
+        let serder = &message.serder;
+        if serder.ilk() != Some(ILK_RPY) {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Expected reply, got {}",
+                serder.ilk().unwrap_or_default()
+            )));
+        }
+        let sad = serder.sad();
+        let route = sad["r"].as_str().unwrap_or_default();
+        let dt = sad["dt"].as_str().unwrap_or_default();
+        parse_dt(dt)?;
+
+        let (family, handler) = self
+            .routes
+            .iter_mut()
+            .filter(|(r, _)| routes_to(r, route))
+            .max_by_key(|(r, _)| r.len())
+            .ok_or_else(|| SignifyError::NotFound(format!("Handler for route {}", route)))?;
+        let (aid, key) = handler.authorize(route, &sad["a"])?;
+        let est_sn = kevery.verify_reply(serder, &aid, &message.attachments)?;
+
+        let id = (family.clone(), key);
+        if let Some(prior) = self.replies.get(&id) {
+            if !fresher(est_sn, dt, prior.est_sn, prior.dt())? {
+                return Ok(false);
+            }
+        }
+
+        let reply = AcceptedReply {
+            serder: serder.clone(),
+            aid,
+            est_sn,
+        };
+        if let Some(db) = &self.db {
+            db.put_reply(&id.0, &id.1, &reply)?;
+        }
+        handler.apply(&reply)?;
+        self.replies.insert(id, reply);
+        Ok(true)
+    }
+
+    /// Reply accepted for the record `key` of the handler of `route`
+    pub fn reply(&self, route: &str, key: &str) -> Option<&AcceptedReply> {
+        self.replies.get(&(route.to_string(), key.to_string()))
+    }
+
+    /// Whether `eid` is authorized for `role` by `cid`, `None` if neither
+    /// authorized nor revoked
+    pub fn end_role(&self, cid: &str, role: &str, eid: &str) -> Option<bool> {
+        self.reply(ROUTE_END_ROLE, &record_key(&[cid, role, eid]))
+            .map(|reply| reply.route() == ROUTE_END_ROLE_ADD)
+    }
+
+    /// URL of `eid` for `scheme`, `None` if unknown or cut by an empty url
+    pub fn loc_scheme(&self, eid: &str, scheme: &str) -> Option<&str> {
+        self.reply(ROUTE_LOC_SCHEME, &record_key(&[eid, scheme]))
+            .and_then(|reply| reply.data()["url"].as_str())
+            .filter(|url| !url.is_empty())
+    }
+}
+
+/// Apply the replies accepted for the handler of `route`, in the order of
+/// their record keys
+fn restore(
+    replies: &HashMap<(String, String), AcceptedReply>,
+    route: &str,
+    handler: &mut dyn ReplyHandler,
+) -> Result<()> {
+    let mut restored: Vec<_> = replies.iter().filter(|((r, _), _)| r == route).collect();
+    restored.sort_by_key(|(id, _)| *id);
+    for (_, reply) in restored {
+        handler.apply(reply)?;
+    }
+    Ok(())
+}
+
+/// Key of a record from its parts
+pub fn record_key(parts: &[&str]) -> String {
+    parts.join(".")
+}
+
+/// Whether a handler registered for `handled` handles `route`
+fn routes_to(handled: &str, route: &str) -> bool {
+    route
+        .strip_prefix(handled)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Non-empty string field of a reply body
+fn field<'a>(data: &'a Value, label: &str, route: &str) -> Result<&'a str> {
+    data[label]
+        .as_str()
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| SignifyError::InvalidEvent(format!("Missing {} for {}", label, route)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::eventing::{end_role_add, end_role_cut, loc_scheme, reply};
+    use crate::core::test_support::{controller, couple, siger, signed, signer};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const T0: &str = "2024-01-01T00:00:00.000000+00:00";
+    const T1: &str = "2024-01-02T00:00:00.000000+00:00";
+    const T2: &str = "2024-01-03T00:00:00.000000+00:00";
+
+    #[test]
+    fn test_revery_end_role() {
+        let mut kevery = Kevery::new();
+        let ([s0, s1], icp, rot) = controller(&mut kevery);
+        let cid = icp.pre().unwrap();
+        let eid = signer(false).verfer().qb64().to_string();
+        let mut revery = Revery::new();
+        assert_eq!(revery.end_role(cid, "agent", &eid), None);
+
+        let add = end_role_add(cid, "agent", &eid, Some(T1)).unwrap();
+        assert!(revery
+            .process_reply(&kevery, &signed(&add, &s0, &icp))
+            .unwrap());
+        assert_eq!(revery.end_role(cid, "agent", &eid), Some(true));
+        assert_eq!(revery.end_role(cid, "mailbox", &eid), None);
+        let accepted = revery
+            .reply(ROUTE_END_ROLE, &record_key(&[cid, "agent", &eid]))
+            .unwrap();
+        assert_eq!((accepted.aid.as_str(), accepted.est_sn), (cid, Some(0)));
+
+        // Replayed or older replies at the same establishment event
+        assert!(!revery
+            .process_reply(&kevery, &signed(&add, &s0, &icp))
+            .unwrap());
+        let cut = end_role_cut(cid, "agent", &eid, Some(T0)).unwrap();
+        assert!(!revery
+            .process_reply(&kevery, &signed(&cut, &s0, &icp))
+            .unwrap());
+        assert_eq!(revery.end_role(cid, "agent", &eid), Some(true));
+
+        // Only the controller may authorize its endpoints
+        let other = signer(true);
+        assert!(matches!(
+            revery.process_reply(&kevery, &signed(&cut, &other, &icp)),
+            Err(SignifyError::VerificationFailed)
+        ));
+        let wrong = end_role_add(&eid, "agent", &eid, Some(T2)).unwrap();
+        assert!(revery
+            .process_reply(&kevery, &signed(&wrong, &s0, &icp))
+            .is_err());
+
+        // A later establishment event wins over a later datetime
+        kevery
+            .process_event(&rot, &[siger(&rot, &s1)], &[], None)
+            .unwrap();
+        assert!(revery
+            .process_reply(&kevery, &signed(&cut, &s1, &rot))
+            .unwrap());
+        assert_eq!(revery.end_role(cid, "agent", &eid), Some(false));
+        let later = end_role_add(cid, "agent", &eid, Some(T2)).unwrap();
+        assert!(!revery
+            .process_reply(&kevery, &signed(&later, &s0, &icp))
+            .unwrap());
+        assert!(revery
+            .process_reply(&kevery, &signed(&later, &s1, &rot))
+            .unwrap());
+        assert_eq!(revery.end_role(cid, "agent", &eid), Some(true));
+    }
+
+    #[test]
+    fn test_revery_loc_scheme() {
+        let kevery = Kevery::new();
+        let mut revery = Revery::new();
+        let wit = signer(false);
+        let eid = wit.verfer().qb64();
+
+        let loc = loc_scheme(eid, "http", "http://localhost:5642/", Some(T1)).unwrap();
+        assert!(revery.process_reply(&kevery, &couple(&loc, &wit)).unwrap());
+        assert_eq!(
+            revery.loc_scheme(eid, "http"),
+            Some("http://localhost:5642/")
+        );
+        assert_eq!(revery.loc_scheme(eid, "tcp"), None);
+
+        let stale = loc_scheme(eid, "http", "http://localhost:5643/", Some(T0)).unwrap();
+        assert!(!revery
+            .process_reply(&kevery, &couple(&stale, &wit))
+            .unwrap());
+        assert!(matches!(
+            revery.process_reply(&kevery, &couple(&loc, &signer(false))),
+            Err(SignifyError::InvalidEvent(_))
+        ));
+
+        let cut = loc_scheme(eid, "http", "", Some(T2)).unwrap();
+        assert!(revery.process_reply(&kevery, &couple(&cut, &wit)).unwrap());
+        assert_eq!(revery.loc_scheme(eid, "http"), None);
+
+        // Unrouted replies and other messages
+        let unrouted = reply("/unknown", None, Some(T2)).unwrap();
+        assert!(matches!(
+            revery.process_reply(&kevery, &couple(&unrouted, &wit)),
+            Err(SignifyError::NotFound(_))
+        ));
+        let partial = reply("/loc/schemes", None, Some(T2)).unwrap();
+        assert!(revery
+            .process_reply(&kevery, &couple(&partial, &wit))
+            .is_err());
+    }
+
+    #[derive(Debug, Default)]
+    struct Names {
+        applied: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl ReplyHandler for Names {
+        fn authorize(&self, _route: &str, data: &Value) -> Result<(String, String)> {
+            let aid = field(data, "aid", "/name")?;
+            Ok((aid.to_string(), aid.to_string()))
+        }
+
+        fn apply(&mut self, reply: &AcceptedReply) -> Result<()> {
+            self.applied.borrow_mut().push(reply.route().to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_revery_routes() {
+        assert!(routes_to("/end/role", "/end/role/add"));
+        assert!(routes_to("/end/role", "/end/role"));
+        assert!(!routes_to("/end/role", "/end/roles"));
+
+        let kevery = Kevery::new();
+        let mut revery = Revery::new();
+        let names = Names::default();
+        let applied = names.applied.clone();
+        revery.add_route("/name", Box::new(names)).unwrap();
+        let wit = signer(false);
+        let data = serde_json::json!({"aid": wit.verfer().qb64(), "name": "wit"});
+        let rpy = reply("/name/set", Some(data), Some(T0)).unwrap();
+        assert!(revery.process_reply(&kevery, &couple(&rpy, &wit)).unwrap());
+        assert_eq!(*applied.borrow(), ["/name/set"]);
+    }
+
+    #[test]
+    fn test_revery_baser() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keri.redb");
+        let mut kevery = Kevery::new();
+        let ([s0, _], icp, _) = controller(&mut kevery);
+        let cid = icp.pre().unwrap();
+        let wit = signer(false);
+        let eid = wit.verfer().qb64();
+
+        let data = serde_json::json!({"aid": eid, "name": "wit"});
+        let name = reply("/name/set", Some(data), Some(T1)).unwrap();
+        {
+            let mut revery = Revery::with_baser(Baser::open(&path).unwrap()).unwrap();
+            revery
+                .add_route("/name", Box::new(Names::default()))
+                .unwrap();
+            assert!(revery.process_reply(&kevery, &couple(&name, &wit)).unwrap());
+            let add = end_role_add(cid, "witness", eid, Some(T1)).unwrap();
+            assert!(revery
+                .process_reply(&kevery, &signed(&add, &s0, &icp))
+                .unwrap());
+            let loc = loc_scheme(eid, "tcp", "tcp://localhost:5632/", Some(T1)).unwrap();
+            assert!(revery.process_reply(&kevery, &couple(&loc, &wit)).unwrap());
+        }
+
+        let mut revery = Revery::with_baser(Baser::open(&path).unwrap()).unwrap();
+        assert_eq!(revery.end_role(cid, "witness", eid), Some(true));
+        assert_eq!(revery.loc_scheme(eid, "tcp"), Some("tcp://localhost:5632/"));
+        let accepted = revery
+            .reply(ROUTE_END_ROLE, &record_key(&[cid, "witness", eid]))
+            .unwrap();
+        assert_eq!(accepted.est_sn, Some(0));
+        assert_eq!(
+            revery
+                .reply(ROUTE_LOC_SCHEME, &record_key(&[eid, "tcp"]))
+                .unwrap()
+                .est_sn,
+            None
+        );
+
+        // Handlers added after a restart apply the replies restored for them
+        let names = Names::default();
+        let applied = names.applied.clone();
+        revery.add_route("/name", Box::new(names)).unwrap();
+        assert_eq!(*applied.borrow(), ["/name/set"]);
+
+        // BADA holds across restarts
+        let stale = end_role_cut(cid, "witness", eid, Some(T0)).unwrap();
+        assert!(!revery
+            .process_reply(&kevery, &signed(&stale, &s0, &icp))
+            .unwrap());
+    }
+}
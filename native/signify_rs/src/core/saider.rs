@@ -2,8 +2,11 @@
 //!
 //! Provides Saider for creating and verifying SAIDs in KERI events
 
+use crate::core::codes::{is_digest, matter_codes, sizage};
 use crate::core::diger::Diger;
 use crate::core::matter::{Matter, MatterOpts};
+use crate::core::serder::Serder;
+use crate::core::utils::deversify;
 use crate::error::{Result, SignifyError};
 use serde_json::Value;
 
@@ -47,18 +50,42 @@ impl Saider {
     /// 4. Creates a Saider with that hash
     /// 5. Updates the 'd' field with the SAID qb64
     /// 6. Returns the Saider
+    ///
+    /// The empty dummy and unsized version string differ from keripy; use
+    /// `saidify_with_code` for keripy-compatible SAIDs.
     pub fn saidify(sad: &mut Value) -> Result<Self> {
-        saidify_with_label(sad, "d")
+        saidify_empty(sad, "d")
     }
 
-    /// Create a SAID for a JSON value with custom label
+    /// Create a SAID for a JSON value with custom label, like `saidify`
     pub fn saidify_with_label(sad: &mut Value, label: &str) -> Result<Self> {
-        saidify_with_label(sad, label)
+        saidify_empty(sad, label)
+    }
+
+    /// Create a keripy-compatible SAID for a JSON value using the given
+    /// digest code and label
+    ///
+    /// The label field is replaced by a dummy of '#' characters of the SAID
+    /// length and a version string `v`, if any, is sized to the serialization
+    /// and updated in the sad.
+    pub fn saidify_with_code(sad: &mut Value, code: &str, label: &str) -> Result<Self> {
+        saidify_labeled_with_code(sad, code, label)
+    }
+
+    /// Verify that this SAID is the digest of the sad and matches its label field
+    pub fn verify(&self, sad: &Value, label: &str) -> Result<bool> {
+        if sad.get(label).and_then(|v| v.as_str()) != Some(self.matter.qb64()) {
+            return Ok(false);
+        }
+
+        let mut sad = sad.clone();
+        let saider = saidify_labeled_with_code(&mut sad, self.code(), label)?;
+        Ok(saider.qb64() == self.matter.qb64())
     }
 }
 
-/// Helper function to create SAID and update the sad
-fn saidify_with_label(sad: &mut Value, label: &str) -> Result<Saider> {
+/// Helper function to create SAID over the sad with an empty label field
+fn saidify_empty(sad: &mut Value, label: &str) -> Result<Saider> {
     // Ensure the sad has the label field
     if !sad.is_object() {
         return Err(SignifyError::InvalidEvent(
@@ -84,7 +111,74 @@ fn saidify_with_label(sad: &mut Value, label: &str) -> Result<Saider> {
         .map_err(|e| SignifyError::SerializationError(format!("Failed to serialize SAD: {}", e)))?;
 
     // Create digest
-    let diger = Diger::new(crate::core::codes::matter_codes::BLAKE3_256, &json_bytes)?;
+    let diger = Diger::new(matter_codes::BLAKE3_256, &json_bytes)?;
+
+    // Create Saider from the digest
+    let saider = Saider::new(MatterOpts {
+        raw: Some(diger.raw().to_vec()),
+        code: Some(diger.code().to_string()),
+        qb64: None,
+        qb64b: None,
+        qb2: None,
+    })?;
+
+    // Update the sad with the SAID
+    let obj = sad.as_object_mut().unwrap();
+    obj.insert(label.to_string(), Value::String(saider.qb64()));
+
+    Ok(saider)
+}
+
+/// Helper function to create keripy-compatible SAID and update the sad
+fn saidify_labeled_with_code(sad: &mut Value, code: &str, label: &str) -> Result<Saider> {
+    if !is_digest(code) {
+        return Err(SignifyError::InvalidCode(format!(
+            "Unsupported digest code for SAID: {}",
+            code
+        )));
+    }
+
+    // Ensure the sad has the label field
+    if !sad.is_object() {
+        return Err(SignifyError::InvalidEvent(
+            "SAD must be a JSON object".to_string(),
+        ));
+    }
+
+    // Check for label field and set to dummy value
+    let fs = sizage(code)?
+        .fs
+        .ok_or_else(|| SignifyError::InvalidCode(format!("No qb64 size for code {}", code)))?;
+    {
+        let obj = sad.as_object_mut().unwrap();
+        if !obj.contains_key(label) {
+            return Err(SignifyError::InvalidEvent(format!(
+                "Missing id field labeled={} in sad",
+                label
+            )));
+        }
+        // Set label field to dummy value for digest calculation
+        obj.insert(label.to_string(), Value::String("#".repeat(fs)));
+    } // Drop mutable borrow here
+
+    // Serialize to JSON (canonical form), sizing the version string if present
+    let versioned = sad
+        .get("v")
+        .and_then(|v| v.as_str())
+        .map(|vs| deversify(vs).is_ok())
+        .unwrap_or(false);
+    let json_bytes = if versioned {
+        let serder = Serder::new(sad.clone(), None, None)?;
+        *sad = serder.sad().clone();
+        serder.raw().as_bytes().to_vec()
+    } else {
+        serde_json::to_vec(&sad).map_err(|e| {
+            SignifyError::SerializationError(format!("Failed to serialize SAD: {}", e))
+        })?
+    };
+
+    // Create digest
+    let diger = Diger::new(code, &json_bytes)?;
 
     // Create Saider from the digest
     let saider = Saider::new(MatterOpts {
@@ -146,6 +240,8 @@ mod tests {
         // Verify SAID was set
         assert_ne!(sad["d"], "");
         assert_eq!(sad["d"], saider.qb64());
+        // Version string is left as given
+        assert_eq!(sad["v"], "KERI1.0JSON_000000");
     }
 
     #[test]
@@ -159,6 +255,33 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_saidify_with_code() {
+        let mut sad = json!({
+            "v": "ACDC10JSON000000_",
+            "d": "",
+            "i": "EaU6JR2nmwyZ-i0d8JZAoTNZH3ULvYAfSVPzhzS6b5CM",
+            "a": {"name": "test"}
+        });
+
+        let saider = Saider::saidify_with_code(&mut sad, matter_codes::SHA3_256, "d").unwrap();
+
+        assert!(saider.qb64().starts_with(matter_codes::SHA3_256));
+        assert_eq!(sad["d"], saider.qb64());
+        // Version string is sized to the serialization
+        let serder = Serder::new(sad.clone(), None, None).unwrap();
+        assert_eq!(sad["v"], serder.sad()["v"]);
+        assert_ne!(sad["v"], "ACDC10JSON000000_");
+        assert!(saider.verify(&sad, "d").unwrap());
+
+        // Tampering breaks verification
+        sad["a"]["name"] = json!("other");
+        assert!(!saider.verify(&sad, "d").unwrap());
+
+        // Non digest codes are rejected
+        assert!(Saider::saidify_with_code(&mut sad, matter_codes::ED25519, "d").is_err());
+    }
+
     #[test]
     fn test_saidify_with_custom_label() {
         let mut sad = json!({
@@ -0,0 +1,267 @@
+//! Seals - Typed anchors carried in the `a` list of KERI events
+//!
+//! Field layouts match keripy's seal namedtuples, so a seal serializes to the
+//! same JSON object keripy puts in an event and parses back from one.
+
+use crate::error::{Result, SignifyError};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// SealDigest - Digest of arbitrary data `{d}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SealDigest {
+    /// Digest qb64
+    pub d: String,
+}
+
+/// SealRoot - Merkle tree root digest `{rd}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SealRoot {
+    /// Root digest qb64
+    pub rd: String,
+}
+
+/// SealEvent - Key event of another identifier `{i,s,d}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SealEvent {
+    /// Identifier prefix
+    pub i: String,
+    /// Sequence number in hex
+    pub s: String,
+    /// Event SAID
+    pub d: String,
+}
+
+/// SealLast - Latest establishment event of an identifier `{i}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SealLast {
+    /// Identifier prefix
+    pub i: String,
+}
+
+/// SealTrans - Establishment event of a transferable signer `{s,d}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SealTrans {
+    /// Sequence number in hex
+    pub s: String,
+    /// Event SAID
+    pub d: String,
+}
+
+/// SealSource - Source event of a delegation or issuance `{s,d}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SealSource {
+    /// Sequence number in hex
+    pub s: String,
+    /// Event SAID
+    pub d: String,
+}
+
+impl SealEvent {
+    /// Create an event seal from a sequence number
+    pub fn new(i: &str, sn: u64, d: &str) -> Self {
+        Self {
+            i: i.to_string(),
+            s: format!("{:x}", sn),
+            d: d.to_string(),
+        }
+    }
+}
+
+impl SealSource {
+    /// Create a source seal from a sequence number
+    pub fn new(sn: u64, d: &str) -> Self {
+        Self {
+            s: format!("{:x}", sn),
+            d: d.to_string(),
+        }
+    }
+}
+
+impl From<SealTrans> for SealSource {
+    fn from(seal: SealTrans) -> Self {
+        Self {
+            s: seal.s,
+            d: seal.d,
+        }
+    }
+}
+
+impl From<SealSource> for SealTrans {
+    fn from(seal: SealSource) -> Self {
+        Self {
+            s: seal.s,
+            d: seal.d,
+        }
+    }
+}
+
+/// Seal - Any typed seal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Seal {
+    Digest(SealDigest),
+    Root(SealRoot),
+    Event(SealEvent),
+    Last(SealLast),
+    Trans(SealTrans),
+    Source(SealSource),
+}
+
+impl Seal {
+    /// Serialize to the JSON object placed in an event's `a` list
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Digest(seal) => json!({"d": seal.d}),
+            Self::Root(seal) => json!({"rd": seal.rd}),
+            Self::Event(seal) => json!({"i": seal.i, "s": seal.s, "d": seal.d}),
+            Self::Last(seal) => json!({"i": seal.i}),
+            Self::Trans(seal) => json!({"s": seal.s, "d": seal.d}),
+            Self::Source(seal) => json!({"s": seal.s, "d": seal.d}),
+        }
+    }
+
+    /// Parse a seal from a JSON object by its field labels
+    ///
+    /// SealTrans and SealSource share the `{s,d}` layout, which parses as
+    /// `Seal::Source`; convert with `SealTrans::from` where needed.
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let obj = value.as_object().ok_or_else(|| {
+            SignifyError::InvalidEvent(format!("Seal must be an object: {}", value))
+        })?;
+        let mut labels: Vec<&str> = obj.keys().map(|k| k.as_str()).collect();
+        labels.sort_unstable();
+
+        let seal = match labels.as_slice() {
+            ["d"] => serde_json::from_value(value.clone()).map(Self::Digest),
+            ["rd"] => serde_json::from_value(value.clone()).map(Self::Root),
+            ["d", "i", "s"] => serde_json::from_value(value.clone()).map(Self::Event),
+            ["i"] => serde_json::from_value(value.clone()).map(Self::Last),
+            ["d", "s"] => serde_json::from_value(value.clone()).map(Self::Source),
+            _ => {
+                return Err(SignifyError::InvalidEvent(format!(
+                    "Unknown seal fields: {:?}",
+                    labels
+                )))
+            }
+        };
+
+        seal.map_err(|e| SignifyError::InvalidEvent(format!("Invalid seal {}: {}", value, e)))
+    }
+}
+
+impl From<SealDigest> for Seal {
+    fn from(seal: SealDigest) -> Self {
+        Self::Digest(seal)
+    }
+}
+
+impl From<SealRoot> for Seal {
+    fn from(seal: SealRoot) -> Self {
+        Self::Root(seal)
+    }
+}
+
+impl From<SealEvent> for Seal {
+    fn from(seal: SealEvent) -> Self {
+        Self::Event(seal)
+    }
+}
+
+impl From<SealLast> for Seal {
+    fn from(seal: SealLast) -> Self {
+        Self::Last(seal)
+    }
+}
+
+impl From<SealTrans> for Seal {
+    fn from(seal: SealTrans) -> Self {
+        Self::Trans(seal)
+    }
+}
+
+impl From<SealSource> for Seal {
+    fn from(seal: SealSource) -> Self {
+        Self::Source(seal)
+    }
+}
+
+/// Serialize seals into an event `a` list
+pub fn seals_to_data(seals: &[Seal]) -> Vec<Value> {
+    seals.iter().map(Seal::to_value).collect()
+}
+
+/// Parse seals out of an event `a` list
+pub fn parse_seals(data: &Value) -> Result<Vec<Seal>> {
+    data.as_array()
+        .ok_or_else(|| SignifyError::InvalidEvent("Seal list must be an array".to_string()))?
+        .iter()
+        .map(Seal::from_value)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_roundtrip() {
+        let seals: Vec<Seal> = vec![
+            SealDigest {
+                d: "EDig".to_string(),
+            }
+            .into(),
+            SealRoot {
+                rd: "ERoot".to_string(),
+            }
+            .into(),
+            SealEvent::new("EPre", 10, "ESaid").into(),
+            SealLast {
+                i: "EPre".to_string(),
+            }
+            .into(),
+            SealSource::new(1, "ESrc").into(),
+        ];
+
+        let data = seals_to_data(&seals);
+        assert_eq!(
+            Value::Array(data.clone()),
+            json!([
+                {"d": "EDig"},
+                {"rd": "ERoot"},
+                {"i": "EPre", "s": "a", "d": "ESaid"},
+                {"i": "EPre"},
+                {"s": "1", "d": "ESrc"}
+            ])
+        );
+        // Field order follows keripy
+        assert_eq!(
+            serde_json::to_string(&data[2]).unwrap(),
+            r#"{"i":"EPre","s":"a","d":"ESaid"}"#
+        );
+
+        assert_eq!(parse_seals(&Value::Array(data)).unwrap(), seals);
+
+        // SealTrans shares the SealSource layout
+        let trans: Seal = SealTrans {
+            s: "0".to_string(),
+            d: "EEst".to_string(),
+        }
+        .into();
+        let parsed = Seal::from_value(&trans.to_value()).unwrap();
+        assert_eq!(parsed, Seal::Source(SealSource::new(0, "EEst")));
+    }
+
+    #[test]
+    fn test_seal_invalid() {
+        assert!(Seal::from_value(&json!("EDig")).is_err());
+        assert!(Seal::from_value(&json!({"x": "EDig"})).is_err());
+        assert!(Seal::from_value(&json!({"d": 1})).is_err());
+        assert!(parse_seals(&json!({"d": "EDig"})).is_err());
+    }
+}
@@ -10,6 +10,7 @@ use crate::error::{Result, SignifyError};
 ///
 /// Uses Matter code "0A" (Salt_128/Huge) for 16-byte sequence numbers.
 /// Provides conversion between u128 sequence numbers and CESR encoding.
+#[derive(Debug, Clone)]
 pub struct Seqner {
     matter: Matter,
 }
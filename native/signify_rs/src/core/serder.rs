@@ -1,5 +1,6 @@
 use crate::core::{
     matter_codes,
+    seal::Seal,
     utils::{deversify, versify, Protocols, Serials, Version},
     Diger,
 };
@@ -66,16 +67,12 @@ impl Serder {
         let (proto, version, kind, size) = deversify(&version_str)?;
 
         // Get digest code from 'd' field if present
-        let code = if let Some(d) = sad.get("d").and_then(|v| v.as_str()) {
-            // Extract code from qb64 SAID (first character)
-            if !d.is_empty() {
-                d[0..1].to_string()
-            } else {
-                matter_codes::BLAKE3_256.to_string()
-            }
-        } else {
-            matter_codes::BLAKE3_256.to_string()
-        };
+        let code = sad
+            .get("d")
+            .and_then(|v| v.as_str())
+            .and_then(|d| crate::core::codes::extract_code(d).ok())
+            .filter(|c| crate::core::codes::is_digest(c))
+            .unwrap_or_else(|| matter_codes::BLAKE3_256.to_string());
 
         Ok(Self {
             raw: raw.to_string(),
@@ -105,7 +102,8 @@ impl Serder {
     ///
     /// This is the core SAID calculation algorithm:
     /// 1. Clone the SAD
-    /// 2. Replace 'd' field with dummy value (correct length of '#' chars)
+    /// 2. Replace 'd' field with dummy value (correct length of '#' chars), and
+    ///    the 'i' field too for self-addressing inception events
     /// 3. Update version string with correct size
     /// 4. Serialize to JSON
     /// 5. Hash the serialized data
@@ -127,11 +125,25 @@ impl Serder {
         let dummy = "#".repeat(qb64_size);
         sad_obj.insert("d".to_string(), json!(dummy));
 
+        // Self-addressing prefixes of inception events are part of the SAID too
+        let incepting = matches!(
+            sad_obj.get("t").and_then(|v| v.as_str()),
+            Some("icp") | Some("dip") | Some("vcp")
+        );
+        let self_addressing = sad_obj
+            .get("i")
+            .and_then(|v| v.as_str())
+            .and_then(|i| crate::core::codes::extract_code(i).ok())
+            .is_some_and(|c| crate::core::codes::is_digest(&c));
+        if incepting && self_addressing {
+            sad_obj.insert("i".to_string(), json!(dummy));
+        }
+
         // Update version string with size
         let (raw, _, _, sad, _) = Self::sizeify(sad, kind)?;
 
-        // Hash the serialized data
-        let digest_bytes = blake3::hash(raw.as_bytes()).as_bytes().to_vec();
+        // Hash the serialized data with the algorithm of the digest code
+        let digest_bytes = Diger::compute_digest(code, raw.as_bytes())?;
 
         Ok((digest_bytes, sad))
     }
@@ -227,6 +239,21 @@ impl Serder {
         self.sad.get("d").and_then(|v| v.as_str())
     }
 
+    /// Parse the typed seals in the 'a' field
+    ///
+    /// Entries of `a` that are not seals, such as anchored data, are skipped.
+    pub fn seals(&self) -> Vec<Seal> {
+        self.sad
+            .get("a")
+            .and_then(|data| data.as_array())
+            .map(|data| {
+                data.iter()
+                    .filter_map(|value| Seal::from_value(value).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Get the sequence number from the 's' field
     pub fn sn(&self) -> Option<u64> {
         self.sad
@@ -341,6 +368,34 @@ mod tests {
         assert_eq!(serder.said_field(), Some("EaU6JR2nmwyZ"));
     }
 
+    #[test]
+    fn test_serder_seals() {
+        let sad = json!({
+            "v": "KERI10JSON000000_",
+            "t": "ixn",
+            "d": "",
+            "i": "EPre",
+            "s": "1",
+            "p": "EPrior",
+            "a": [
+                {"i": "EDelegate", "s": "0", "d": "EDelegateSaid"},
+                {"name": "John Doe"},
+                "text",
+                {"d": "EAnchoredData"}
+            ]
+        });
+
+        let serder = Serder::new(sad, None, None).unwrap();
+        let seals = serder.seals();
+        assert_eq!(seals.len(), 2);
+        assert!(matches!(&seals[0], Seal::Event(seal) if seal.i == "EDelegate"));
+        assert!(matches!(&seals[1], Seal::Digest(seal) if seal.d == "EAnchoredData"));
+
+        // Attributes of other messages are not a seal list
+        let rpy = json!({"v": "KERI10JSON000000_", "t": "rpy", "d": "", "a": {"d": "EDig"}});
+        assert!(Serder::new(rpy, None, None).unwrap().seals().is_empty());
+    }
+
     #[test]
     fn test_serder_derive_said() {
         let sad = json!({
@@ -363,6 +418,29 @@ mod tests {
         assert_eq!(d_field.len(), 44); // Blake3-256 qb64 size
     }
 
+    #[test]
+    fn test_serder_said_digest_codes() {
+        let sad = json!({
+            "v": "KERI10JSON000000_",
+            "t": "icp",
+            "d": "",
+            "i": "test",
+            "s": "0"
+        });
+        let serder = Serder::new(sad, None, None).unwrap();
+
+        for code in crate::core::codes::DIGEST_CODES {
+            let said = serder.said(Some(code)).unwrap();
+            let (_, dummied) = Serder::derive_said(serder.sad(), code, None).unwrap();
+            let dummied = Serder::new(dummied, None, None).unwrap();
+
+            assert!(said.starts_with(code));
+            assert_eq!(said.len(), dummied.sad()["d"].as_str().unwrap().len());
+            let diger = Diger::from_qb64(&said).unwrap();
+            assert!(diger.verify(dummied.raw().as_bytes()).unwrap());
+        }
+    }
+
     #[test]
     fn test_serder_pretty() {
         let sad = json!({
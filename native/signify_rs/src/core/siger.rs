@@ -6,6 +6,7 @@ use crate::core::{Indexer, IndexerCodex, Verfer};
 use crate::error::{Result, SignifyError};
 
 /// Indexed signature with optional verifier
+#[derive(Debug, Clone)]
 pub struct Siger {
     indexer: Indexer,
     verfer: Option<Verfer>,
@@ -48,11 +49,22 @@ impl Siger {
         Ok(Self { indexer, verfer })
     }
 
+    /// Create Siger from qb2 bytes
+    pub fn from_qb2(qb2: &[u8], verfer: Option<Verfer>) -> Result<Self> {
+        let indexer = Indexer::from_qb2(qb2)?;
+        Ok(Self { indexer, verfer })
+    }
+
     /// Get qb64 encoding
     pub fn qb64(&self) -> String {
         self.indexer.qb64()
     }
 
+    /// Get qb2 encoding
+    pub fn qb2(&self) -> Vec<u8> {
+        self.indexer.qb2()
+    }
+
     /// Get signature index
     pub fn index(&self) -> u32 {
         self.indexer.index()
@@ -158,6 +170,22 @@ mod tests {
         assert_eq!(siger.raw(), siger2.raw());
     }
 
+    #[test]
+    fn test_siger_qb2_roundtrip() {
+        let signer = Signer::from_seed(&[1u8; 32], matter_codes::ED25519_SEED, true).unwrap();
+        let message = b"qb2 message";
+        let sig = signer.sign(message).unwrap();
+        let siger = Siger::new(&sig, IndexerCodex::ED25519_SIG, 3, None, None).unwrap();
+
+        let qb2 = siger.qb2();
+        assert_eq!(qb2.len(), 66);
+
+        let siger2 = Siger::from_qb2(&qb2, Some(signer.verfer().clone())).unwrap();
+        assert_eq!(siger2.qb64(), siger.qb64());
+        assert_eq!(siger2.index(), 3);
+        assert!(siger2.verify(message).unwrap());
+    }
+
     #[test]
     fn test_siger_invalid_code() {
         let sig = vec![0u8; 64];
@@ -1,9 +1,90 @@
-use crate::core::{matter_codes, Matter, Verfer};
-/// Signer - Ed25519 signing with CESR encoding
+use crate::core::codes::raw_size;
+use crate::core::{matter_codes, Cigar, IndexerCodex, Matter, Siger, Verfer};
+/// Signer - Ed25519, Ed448 and ECDSA secp256k1/secp256r1 signing with CESR encoding
 use crate::error::{Result, SignifyError};
 use ed25519_dalek::{Signer as DalekSigner, SigningKey};
 
-/// Signer wraps an Ed25519 keypair for signing operations
+/// Signature codes for one signing algorithm
+struct SigCodes {
+    /// Verfer codes (transferable, non-transferable)
+    verfer: (&'static str, &'static str),
+    /// Non-indexed signature code
+    cigar: &'static str,
+    /// Indexed signature codes (both, current only, big both, big current only)
+    siger: [&'static str; 4],
+}
+
+/// Look up signature codes by seed code
+fn sig_codes(code: &str) -> Result<SigCodes> {
+    match code {
+        matter_codes::ED25519_SEED => Ok(SigCodes {
+            verfer: (matter_codes::ED25519, matter_codes::ED25519N),
+            cigar: matter_codes::ED25519_SIG,
+            siger: [
+                IndexerCodex::ED25519_SIG,
+                IndexerCodex::ED25519_CRT_SIG,
+                IndexerCodex::ED25519_BIG_SIG,
+                IndexerCodex::ED25519_BIG_CRT_SIG,
+            ],
+        }),
+        matter_codes::ECDSA_256K1_SEED => Ok(SigCodes {
+            verfer: (matter_codes::ECDSA_256K1, matter_codes::ECDSA_256K1N),
+            cigar: matter_codes::ECDSA_256K1_SIG,
+            siger: [
+                IndexerCodex::ECDSA_256K1_SIG,
+                IndexerCodex::ECDSA_256K1_CRT_SIG,
+                IndexerCodex::ECDSA_256K1_BIG_SIG,
+                IndexerCodex::ECDSA_256K1_BIG_CRT_SIG,
+            ],
+        }),
+        matter_codes::ED448_SEED => Ok(SigCodes {
+            verfer: (matter_codes::ED448, matter_codes::ED448N),
+            cigar: matter_codes::ED448_SIG,
+            siger: [
+                IndexerCodex::ED448_SIG,
+                IndexerCodex::ED448_CRT_SIG,
+                IndexerCodex::ED448_BIG_SIG,
+                IndexerCodex::ED448_BIG_CRT_SIG,
+            ],
+        }),
+        matter_codes::ECDSA_256R1_SEED => Ok(SigCodes {
+            verfer: (matter_codes::ECDSA_256R1, matter_codes::ECDSA_256R1N),
+            cigar: matter_codes::ECDSA_256R1_SIG,
+            siger: [
+                IndexerCodex::ECDSA_256R1_SIG,
+                IndexerCodex::ECDSA_256R1_CRT_SIG,
+                IndexerCodex::ECDSA_256R1_BIG_SIG,
+                IndexerCodex::ECDSA_256R1_BIG_CRT_SIG,
+            ],
+        }),
+        _ => Err(SignifyError::UnsupportedAlgorithm(format!(
+            "Unsupported signer code: {}. Expected {}, {}, {} or {}",
+            code,
+            matter_codes::ED25519_SEED,
+            matter_codes::ED448_SEED,
+            matter_codes::ECDSA_256K1_SEED,
+            matter_codes::ECDSA_256R1_SEED
+        ))),
+    }
+}
+
+/// Ed448 signing key from a 56 byte CESR seed, zero extended to the 57 byte
+/// RFC 8032 private key
+fn ed448_signing_key(seed: &[u8]) -> Result<ed448_goldilocks::SigningKey> {
+    let mut secret = [0u8; ed448_goldilocks::SECRET_KEY_LENGTH];
+    secret[..seed.len()].copy_from_slice(seed);
+    ed448_goldilocks::SigningKey::try_from(&secret[..])
+        .map_err(|e| SignifyError::InvalidKey(format!("Invalid Ed448 seed: {}", e)))
+}
+
+/// Signer wraps an Ed25519, Ed448 or ECDSA secp256k1/secp256r1 keypair for signing operations
+///
+/// The CESR Ed448 seed code carries 56 bytes while RFC 8032 private keys are
+/// 57 bytes, so an Ed448 seed is used as the RFC 8032 private key `seed || 0x00`.
+/// This derivation is specific to this library and not interoperable: an
+/// Ed448 seed gives other keys in implementations that extend it differently,
+/// and RFC 8032 private keys not ending in a zero byte cannot be imported.
+/// Ed448 signatures and public keys are plain RFC 8032 and verify anywhere.
 #[derive(Debug)]
 pub struct Signer {
     matter: Matter, // Stores the seed
@@ -13,53 +94,88 @@ pub struct Signer {
 impl Signer {
     /// Create a new random Signer
     pub fn new_random(code: &str, transferable: bool) -> Result<Self> {
-        if code != matter_codes::ED25519_SEED {
-            return Err(SignifyError::UnsupportedAlgorithm(format!(
-                "Unsupported signer code: {}. Expected {}",
-                code,
-                matter_codes::ED25519_SEED
-            )));
-        }
+        sig_codes(code)?;
 
         // Generate random seed
-        use rand::RngCore;
-        let mut rng = rand::thread_rng();
-        let mut seed = [0u8; 32];
-        rng.fill_bytes(&mut seed);
+        let seed = match code {
+            matter_codes::ECDSA_256K1_SEED => {
+                // Not every 32 byte string is a valid secp256k1 scalar
+                k256::ecdsa::SigningKey::random(&mut rand::thread_rng())
+                    .to_bytes()
+                    .to_vec()
+            }
+            matter_codes::ECDSA_256R1_SEED => {
+                p256::ecdsa::SigningKey::random(&mut rand::thread_rng())
+                    .to_bytes()
+                    .to_vec()
+            }
+            _ => {
+                use rand::RngCore;
+                let mut rng = rand::thread_rng();
+                let mut seed = vec![0u8; raw_size(code)?];
+                rng.fill_bytes(&mut seed);
+                seed
+            }
+        };
 
         Self::from_seed(&seed, code, transferable)
     }
 
     /// Create Signer from seed
+    ///
+    /// Ed448 seeds are 56 bytes and zero extended, see [`Signer`].
     pub fn from_seed(seed: &[u8], code: &str, transferable: bool) -> Result<Self> {
-        if code != matter_codes::ED25519_SEED {
-            return Err(SignifyError::UnsupportedAlgorithm(format!(
-                "Unsupported signer code: {}. Expected {}",
-                code,
-                matter_codes::ED25519_SEED
-            )));
-        }
+        let codes = sig_codes(code)?;
 
-        if seed.len() != 32 {
+        let size = raw_size(code)?;
+        if seed.len() != size {
             return Err(SignifyError::InvalidSize {
-                expected: 32,
+                expected: size,
                 actual: seed.len(),
             });
         }
 
-        // Generate signing key from seed
-        let signing_key = SigningKey::from_bytes(seed.try_into().unwrap());
-        let verifying_key = signing_key.verifying_key();
+        // Derive public key from seed
+        let public = match code {
+            matter_codes::ECDSA_256K1_SEED => {
+                let signing_key = k256::ecdsa::SigningKey::from_slice(seed).map_err(|e| {
+                    SignifyError::InvalidKey(format!("Invalid secp256k1 seed: {}", e))
+                })?;
+                // Compressed SEC1 point
+                signing_key
+                    .verifying_key()
+                    .to_encoded_point(true)
+                    .as_bytes()
+                    .to_vec()
+            }
+            matter_codes::ECDSA_256R1_SEED => {
+                let signing_key = p256::ecdsa::SigningKey::from_slice(seed).map_err(|e| {
+                    SignifyError::InvalidKey(format!("Invalid secp256r1 seed: {}", e))
+                })?;
+                signing_key
+                    .verifying_key()
+                    .to_encoded_point(true)
+                    .as_bytes()
+                    .to_vec()
+            }
+            matter_codes::ED448_SEED => {
+                ed448_signing_key(seed)?.verifying_key().to_bytes().to_vec()
+            }
+            _ => {
+                let signing_key = SigningKey::from_bytes(seed.try_into().unwrap());
+                signing_key.verifying_key().as_bytes().to_vec()
+            }
+        };
 
         let matter = Matter::from_raw(seed, code)?;
 
         // Create verfer with appropriate code
         let verfer_code = if transferable {
-            matter_codes::ED25519
+            codes.verfer.0
         } else {
-            matter_codes::ED25519N
+            codes.verfer.1
         };
-        let verfer = Verfer::from_raw(verifying_key.as_bytes(), verfer_code)?;
+        let verfer = Verfer::from_raw(&public, verfer_code)?;
 
         Ok(Self { matter, verfer })
     }
@@ -68,9 +184,9 @@ impl Signer {
     pub fn from_qb64(qb64: &str, transferable: bool) -> Result<Self> {
         let matter = Matter::from_qb64(qb64)?;
 
-        if matter.code() != matter_codes::ED25519_SEED {
+        if sig_codes(matter.code()).is_err() {
             return Err(SignifyError::InvalidCode(format!(
-                "Expected ED25519_SEED code, got {}",
+                "Expected signing seed code, got {}",
                 matter.code()
             )));
         }
@@ -79,12 +195,32 @@ impl Signer {
     }
 
     /// Sign serialized data
-    /// Returns raw signature bytes (64 bytes for Ed25519)
+    /// Returns raw signature bytes (64 bytes for Ed25519, 114 for Ed448, r || s for ECDSA)
     pub fn sign(&self, ser: &[u8]) -> Result<Vec<u8>> {
-        let signing_key = SigningKey::from_bytes(self.matter.raw().try_into().unwrap());
-        let signature = signing_key.sign(ser);
-
-        Ok(signature.to_bytes().to_vec())
+        match self.matter.code() {
+            matter_codes::ECDSA_256K1_SEED => {
+                let signing_key = k256::ecdsa::SigningKey::from_slice(self.matter.raw())
+                    .map_err(|e| SignifyError::CryptoError(e.to_string()))?;
+                let signature: k256::ecdsa::Signature = signing_key.sign(ser);
+                Ok(signature.to_bytes().to_vec())
+            }
+            matter_codes::ECDSA_256R1_SEED => {
+                let signing_key = p256::ecdsa::SigningKey::from_slice(self.matter.raw())
+                    .map_err(|e| SignifyError::CryptoError(e.to_string()))?;
+                let signature: p256::ecdsa::Signature = signing_key.sign(ser);
+                Ok(signature.to_bytes().to_vec())
+            }
+            matter_codes::ED448_SEED => {
+                let signing_key = ed448_signing_key(self.matter.raw())?;
+                Ok(signing_key.sign_raw(ser).to_bytes().to_vec())
+            }
+            _ => {
+                let signing_key = SigningKey::from_bytes(self.matter.raw().try_into().unwrap());
+                let signature = signing_key.sign(ser);
+
+                Ok(signature.to_bytes().to_vec())
+            }
+        }
     }
 
     /// Sign and return indexed signature (for multi-sig scenarios)
@@ -93,6 +229,44 @@ impl Signer {
         Ok(IndexedSignature { signature, index })
     }
 
+    /// Sign and return non-indexed signature with the code for this key type
+    pub fn cigar(&self, ser: &[u8]) -> Result<Cigar> {
+        let codes = sig_codes(self.matter.code())?;
+        let sig = self.sign(ser)?;
+        Cigar::new(&sig, codes.cigar, Some(self.verfer.clone()))
+    }
+
+    /// Sign and return indexed signature with the code for this key type
+    ///
+    /// # Arguments
+    /// * `index` - Index of the signing key in the current key list
+    /// * `only` - Key only appears in the current list, not the prior next list
+    /// * `ondex` - Index of the key's digest in the prior next list (defaults to `index`)
+    pub fn siger(&self, ser: &[u8], index: u32, only: bool, ondex: Option<u32>) -> Result<Siger> {
+        let codes = sig_codes(self.matter.code())?;
+        let sig = self.sign(ser)?;
+
+        // Small codes hold a single index below 64
+        let (code, ondex) = if only {
+            let code = if index <= 63 {
+                codes.siger[1]
+            } else {
+                codes.siger[3]
+            };
+            (code, None)
+        } else {
+            let ondex = ondex.unwrap_or(index);
+            let code = if ondex == index && index <= 63 {
+                codes.siger[0]
+            } else {
+                codes.siger[2]
+            };
+            (code, Some(ondex))
+        };
+
+        Siger::new(&sig, code, index, ondex, Some(self.verfer.clone()))
+    }
+
     /// Get the verifier (public key)
     pub fn verfer(&self) -> &Verfer {
         &self.verfer
@@ -110,7 +284,7 @@ impl Signer {
 
     /// Check if transferable
     pub fn transferable(&self) -> bool {
-        self.verfer.code() == matter_codes::ED25519
+        self.verfer.transferable()
     }
 }
 
@@ -227,4 +401,175 @@ mod tests {
         let sig2 = signer2.sign(message).unwrap();
         assert_eq!(sig1, sig2);
     }
+
+    #[test]
+    fn test_signer_secp256k1() {
+        // Private key 1 gives the curve generator point
+        let mut seed = [0u8; 32];
+        seed[31] = 1;
+        let signer = Signer::from_seed(&seed, matter_codes::ECDSA_256K1_SEED, true).unwrap();
+
+        assert_eq!(signer.verfer().code(), matter_codes::ECDSA_256K1);
+        assert_eq!(
+            hex::encode(signer.verfer().raw()),
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        assert!(signer.transferable());
+
+        let message = b"test message";
+        let sig = signer.sign(message).unwrap();
+        assert_eq!(sig.len(), 64);
+        assert!(signer.verfer().verify(&sig, message).unwrap());
+        assert!(!signer.verfer().verify(&sig, b"wrong message").unwrap());
+
+        // Round trip through qb64 seed
+        let signer2 = Signer::from_qb64(signer.qb64(), false).unwrap();
+        assert_eq!(signer2.verfer().code(), matter_codes::ECDSA_256K1N);
+        assert_eq!(signer2.verfer().raw(), signer.verfer().raw());
+
+        // Zero is not a valid secp256k1 key
+        assert!(Signer::from_seed(&[0u8; 32], matter_codes::ECDSA_256K1_SEED, true).is_err());
+
+        let random = Signer::new_random(matter_codes::ECDSA_256K1_SEED, true).unwrap();
+        assert!(random
+            .verfer()
+            .verify(&random.sign(message).unwrap(), message)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_signer_cigar_and_siger_codes() {
+        let message = b"test message";
+        let ed = Signer::from_seed(&[1u8; 32], matter_codes::ED25519_SEED, true).unwrap();
+        let k1 = Signer::from_seed(&[1u8; 32], matter_codes::ECDSA_256K1_SEED, true).unwrap();
+
+        let cigar = ed.cigar(message).unwrap();
+        assert_eq!(cigar.code(), matter_codes::ED25519_SIG);
+        assert!(cigar.verify(message).unwrap());
+        let cigar = k1.cigar(message).unwrap();
+        assert_eq!(cigar.code(), matter_codes::ECDSA_256K1_SIG);
+        assert!(cigar.verify(message).unwrap());
+
+        let siger = k1.siger(message, 3, false, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ECDSA_256K1_SIG);
+        assert!(siger.verify(message).unwrap());
+        let siger = k1.siger(message, 3, true, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ECDSA_256K1_CRT_SIG);
+        let siger = k1.siger(message, 3, false, Some(5)).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ECDSA_256K1_BIG_SIG);
+        assert_eq!(siger.ondex(), 5);
+        let siger = k1.siger(message, 64, true, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ECDSA_256K1_BIG_CRT_SIG);
+
+        let siger = ed.siger(message, 0, false, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ED25519_SIG);
+        assert!(siger.verify(message).unwrap());
+
+        // Round trip through qb64 keeps the signature verifiable
+        let parsed = Siger::from_qb64(&siger.qb64(), Some(ed.verfer().clone())).unwrap();
+        assert!(parsed.verify(message).unwrap());
+    }
+
+    #[test]
+    fn test_signer_ed448() {
+        let seed = [7u8; 56];
+        let signer = Signer::from_seed(&seed, matter_codes::ED448_SEED, true).unwrap();
+
+        assert_eq!(signer.verfer().code(), matter_codes::ED448);
+        assert_eq!(signer.verfer().raw().len(), 57);
+        assert_eq!(signer.qb64().len(), 76);
+        assert_eq!(signer.verfer().qb64().len(), 80);
+        assert!(Signer::from_seed(&[7u8; 32], matter_codes::ED448_SEED, true).is_err());
+
+        let message = b"test message";
+        let cigar = signer.cigar(message).unwrap();
+        assert_eq!(cigar.code(), matter_codes::ED448_SIG);
+        assert_eq!(cigar.qb64().len(), 156);
+        assert!(cigar.verify(message).unwrap());
+        assert!(!cigar.verify(b"wrong message").unwrap());
+
+        // Medium and big index codes
+        let siger = signer.siger(message, 1, false, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ED448_SIG);
+        assert_eq!(siger.qb64().len(), 156);
+        assert!(siger.verify(message).unwrap());
+        let siger = signer.siger(message, 1, true, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ED448_CRT_SIG);
+        let siger = signer.siger(message, 100, false, Some(2)).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ED448_BIG_SIG);
+        assert_eq!(siger.qb64().len(), 160);
+        let parsed = Siger::from_qb2(&siger.qb2(), Some(signer.verfer().clone())).unwrap();
+        assert_eq!((parsed.index(), parsed.ondex()), (100, 2));
+        assert!(parsed.verify(message).unwrap());
+
+        let signer = Signer::from_qb64(signer.qb64(), false).unwrap();
+        assert_eq!(signer.verfer().code(), matter_codes::ED448N);
+        assert!(!signer.transferable());
+
+        let random = Signer::new_random(matter_codes::ED448_SEED, true).unwrap();
+        assert!(random.cigar(message).unwrap().verify(message).unwrap());
+    }
+
+    #[test]
+    fn test_signer_ed448_seed_extension() {
+        // Reference key and signature from OpenSSL (Python cryptography)
+        // for the 57 byte private key seed || 0x00
+        let signer = Signer::from_seed(&[7u8; 56], matter_codes::ED448_SEED, true).unwrap();
+        assert_eq!(
+            hex::encode(signer.verfer().raw()),
+            "9b09a47ed2b5943c5124fff2d84e68b8c3b845c5bd4b24da264759a36034e6b3\
+             434338c5902dc396863c0249cd3dc36674b74b982fd0e65780"
+        );
+        assert_eq!(
+            hex::encode(signer.sign(b"test message").unwrap()),
+            "1e134fe91867b7a44d82855aa156f6d056ef29fab48e0d4c92bb5958f43ca817\
+             3deb19c0157a9eefb447250098140079a0ca7bbbb2c8a97580b8369ec19804f6\
+             19d81433dc83476db7d5c89298d0bfa84dcb57efa3e3f12195c155398c729785\
+             dff15fd47e46632d4e3c4eb4d48134f51000"
+        );
+    }
+
+    #[test]
+    fn test_signer_secp256r1() {
+        // Private key 1 gives the P-256 generator point
+        let mut seed = [0u8; 32];
+        seed[31] = 1;
+        let signer = Signer::from_seed(&seed, matter_codes::ECDSA_256R1_SEED, true).unwrap();
+
+        assert_eq!(signer.verfer().code(), matter_codes::ECDSA_256R1);
+        assert_eq!(
+            hex::encode(signer.verfer().raw()),
+            "036b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"
+        );
+        assert_eq!(signer.qb64().len(), 44);
+        assert_eq!(signer.verfer().qb64().len(), 48);
+
+        let message = b"test message";
+        let cigar = signer.cigar(message).unwrap();
+        assert_eq!(cigar.code(), matter_codes::ECDSA_256R1_SIG);
+        assert!(cigar.verify(message).unwrap());
+        assert!(!cigar.verify(b"wrong message").unwrap());
+
+        // Small and big index codes
+        let siger = signer.siger(message, 1, false, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ECDSA_256R1_SIG);
+        assert!(siger.verify(message).unwrap());
+        let siger = signer.siger(message, 1, true, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ECDSA_256R1_CRT_SIG);
+        let siger = signer.siger(message, 100, false, Some(2)).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ECDSA_256R1_BIG_SIG);
+        assert_eq!(siger.qb64().len(), 92);
+        let parsed = Siger::from_qb2(&siger.qb2(), Some(signer.verfer().clone())).unwrap();
+        assert_eq!((parsed.index(), parsed.ondex()), (100, 2));
+        assert!(parsed.verify(message).unwrap());
+        let siger = signer.siger(message, 100, true, None).unwrap();
+        assert_eq!(siger.code(), IndexerCodex::ECDSA_256R1_BIG_CRT_SIG);
+
+        let signer = Signer::from_qb64(signer.qb64(), false).unwrap();
+        assert_eq!(signer.verfer().code(), matter_codes::ECDSA_256R1N);
+        assert!(!signer.transferable());
+
+        let random = Signer::new_random(matter_codes::ECDSA_256R1_SEED, true).unwrap();
+        assert!(random.cigar(message).unwrap().verify(message).unwrap());
+    }
 }
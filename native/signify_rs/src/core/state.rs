@@ -0,0 +1,178 @@
+//! Key state records
+//!
+//! A key state record summarizes the validated key state of an identifier at
+//! its latest event, as keripy and KERIA exchange it in key state notice
+//! (`/ksn`) replies and in agent state. Numbers are hex strings, thresholds
+//! are kept as their `kt`/`nt` JSON values and the fields serialize in
+//! keripy's order.
+
+use crate::error::{Result, SignifyError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Latest establishment event of a key state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateEstEvent {
+    /// Sequence number (hex)
+    pub s: String,
+    /// SAID
+    pub d: String,
+    /// Witnesses removed
+    #[serde(default)]
+    pub br: Vec<String>,
+    /// Witnesses added
+    #[serde(default)]
+    pub ba: Vec<String>,
+}
+
+/// Key state of an identifier at its latest event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyStateRecord {
+    /// Version of the record
+    #[serde(default = "default_vn")]
+    pub vn: Vec<u32>,
+    /// Identifier prefix
+    pub i: String,
+    /// Sequence number of the latest event (hex)
+    pub s: String,
+    /// SAID of the prior event
+    #[serde(default)]
+    pub p: String,
+    /// SAID of the latest event
+    pub d: String,
+    /// First-seen ordinal of the latest event (hex)
+    #[serde(default)]
+    pub f: String,
+    /// When the latest event was first seen
+    #[serde(default)]
+    pub dt: String,
+    /// Ilk of the latest event
+    pub et: String,
+    /// Signing threshold
+    pub kt: Value,
+    /// Current signing keys
+    pub k: Vec<String>,
+    /// Next signing threshold
+    pub nt: Value,
+    /// Next key digests
+    pub n: Vec<String>,
+    /// Witness threshold (hex)
+    pub bt: String,
+    /// Witnesses
+    pub b: Vec<String>,
+    /// Configuration traits
+    #[serde(default)]
+    pub c: Vec<String>,
+    /// Latest establishment event
+    pub ee: StateEstEvent,
+    /// Delegator prefix, empty if not delegated
+    #[serde(default)]
+    pub di: String,
+}
+
+fn default_vn() -> Vec<u32> {
+    vec![1, 0]
+}
+
+impl KeyStateRecord {
+    /// Parse a key state record from its JSON value, e.g. the `a` section of
+    /// a `/ksn` reply or the `agent` state from KERIA
+    pub fn from_value(value: &Value) -> Result<Self> {
+        serde_json::from_value(value.clone())
+            .map_err(|e| SignifyError::InvalidArgument(format!("Invalid key state record: {}", e)))
+    }
+
+    /// JSON value of the record
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).expect("key state record serializes")
+    }
+
+    /// Sequence number of the latest event
+    pub fn sn(&self) -> Result<u64> {
+        hex(&self.s, "s")
+    }
+
+    /// Sequence number of the latest establishment event
+    pub fn est_sn(&self) -> Result<u64> {
+        hex(&self.ee.s, "ee.s")
+    }
+
+    /// Whether the identifier is delegated
+    pub fn delegated(&self) -> bool {
+        !self.di.is_empty()
+    }
+}
+
+fn hex(value: &str, label: &str) -> Result<u64> {
+    u64::from_str_radix(value, 16).map_err(|_| {
+        SignifyError::InvalidArgument(format!("Invalid {} {} in key state record", label, value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_key_state_record() {
+        // Agent state as KERIA returns it
+        let agent = json!({
+            "vn": [1, 0],
+            "i": "EEXekkGu9IAzav6pZVJhkLnjtjM5v3AcyA-pdKUcaGei",
+            "s": "0",
+            "p": "",
+            "d": "EEXekkGu9IAzav6pZVJhkLnjtjM5v3AcyA-pdKUcaGei",
+            "f": "0",
+            "dt": "2023-08-23T15:16:07.553000+00:00",
+            "et": "dip",
+            "kt": "1",
+            "k": ["DMZh_y-H5C3cSbZZST-fqnsmdNTReZxIh0t2xSTOJQ8q"],
+            "nt": "1",
+            "n": ["EM9M2EQNCBK0MyAhVYBvR98Q0tefpvHgE-lHLs82XgqC"],
+            "bt": "0",
+            "b": [],
+            "c": [],
+            "ee": {
+                "s": "0",
+                "d": "EEXekkGu9IAzav6pZVJhkLnjtjM5v3AcyA-pdKUcaGei",
+                "br": [],
+                "ba": []
+            },
+            "di": "ELUvZ8aJEHAQE-0nsevyYTP98rBbGJUrTj5an-pCmwrK"
+        });
+
+        let ksr = KeyStateRecord::from_value(&agent).unwrap();
+        assert_eq!(ksr.et, "dip");
+        assert_eq!(ksr.kt, json!("1"));
+        assert_eq!(ksr.sn().unwrap(), 0);
+        assert_eq!(ksr.est_sn().unwrap(), 0);
+        assert!(ksr.delegated());
+        assert_eq!(ksr.to_value(), agent);
+        assert_eq!(
+            serde_json::to_string(&ksr).unwrap(),
+            serde_json::to_string(&agent).unwrap()
+        );
+
+        // Optional fields default, weighted thresholds stay JSON
+        let mut minimal = agent.clone();
+        for label in ["vn", "p", "f", "dt", "c", "di"] {
+            minimal.as_object_mut().unwrap().remove(label);
+        }
+        minimal["kt"] = json!(["1/2", "1/2"]);
+        minimal["s"] = json!("1a");
+        let ksr = KeyStateRecord::from_value(&minimal).unwrap();
+        assert_eq!(ksr.vn, [1, 0]);
+        assert_eq!(ksr.sn().unwrap(), 26);
+        assert_eq!(ksr.kt, json!(["1/2", "1/2"]));
+        assert!(!ksr.delegated());
+
+        minimal.as_object_mut().unwrap().remove("ee");
+        assert!(KeyStateRecord::from_value(&minimal).is_err());
+        minimal["ee"] = json!({"s": "x", "d": ""});
+        assert!(KeyStateRecord::from_value(&minimal)
+            .unwrap()
+            .est_sn()
+            .is_err());
+    }
+}
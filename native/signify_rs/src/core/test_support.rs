@@ -0,0 +1,162 @@
+//! Fixture factories shared by the key event, database and routing tests
+
+use crate::cesr_parser::{parse_stream, Message};
+use crate::core::cigar::Cigar;
+use crate::core::codes::matter_codes;
+use crate::core::diger::Diger;
+use crate::core::eventing::{incept, messagize, rotate, MessagizeOpts, RotateArgs};
+use crate::core::indexer::IndexerCodex;
+use crate::core::kevery::{KelEvent, Kevery};
+use crate::core::seal::SealEvent;
+use crate::core::serder::Serder;
+use crate::core::siger::Siger;
+use crate::core::signer::Signer;
+use std::slice::from_ref;
+
+/// Random Ed25519 signer
+pub fn signer(transferable: bool) -> Signer {
+    Signer::new_random(matter_codes::ED25519_SEED, transferable).unwrap()
+}
+
+/// `n` random Ed25519 signers
+pub fn signers(n: usize, transferable: bool) -> Vec<Signer> {
+    (0..n).map(|_| signer(transferable)).collect()
+}
+
+/// Public keys of the signers
+pub fn keys(signers: &[Signer]) -> Vec<String> {
+    signers
+        .iter()
+        .map(|s| s.verfer().qb64().to_string())
+        .collect()
+}
+
+/// Next key digest committing to the signer's public key
+pub fn dig(signer: &Signer) -> String {
+    Diger::new(matter_codes::BLAKE3_256, signer.verfer().qb64b())
+        .unwrap()
+        .qb64()
+        .to_string()
+}
+
+/// Next key digests committing to the signers' public keys
+pub fn digs(signers: &[Signer]) -> Vec<String> {
+    signers.iter().map(dig).collect()
+}
+
+/// Signature of the signer on the event at index 0
+pub fn siger(serder: &Serder, signer: &Signer) -> Siger {
+    let sig = signer.sign(serder.raw().as_bytes()).unwrap();
+    Siger::new(&sig, IndexerCodex::ED25519_SIG, 0, None, None).unwrap()
+}
+
+/// Signatures of the signers on the event, indexed by their position
+pub fn sign(serder: &Serder, signers: &[Signer]) -> Vec<Siger> {
+    signers
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let sig = s.sign(serder.raw().as_bytes()).unwrap();
+            Siger::new(&sig, IndexerCodex::ED25519_SIG, i as u32, None, None).unwrap()
+        })
+        .collect()
+}
+
+/// Receipt couple of the signer on the event, carrying the signer's verfer
+pub fn cigar(serder: &Serder, signer: &Signer) -> Cigar {
+    let sig = signer.sign(serder.raw().as_bytes()).unwrap();
+    Cigar::new(
+        &sig,
+        matter_codes::ED25519_SIG,
+        Some(signer.verfer().clone()),
+    )
+    .unwrap()
+}
+
+/// Message signed by `signer` at the establishment event `est`
+pub fn signed(serder: &Serder, signer: &Signer, est: &Serder) -> Message {
+    let seal = SealEvent::new(
+        est.pre().unwrap(),
+        est.sn().unwrap(),
+        est.said_field().unwrap(),
+    );
+    let opts = MessagizeOpts {
+        sigers: vec![siger(serder, signer)],
+        seal: Some(seal.into()),
+        ..Default::default()
+    };
+    parse_stream(&messagize(serder, opts).unwrap())
+        .unwrap()
+        .remove(0)
+}
+
+/// Message signed by the non-transferable `signer` with a receipt couple
+pub fn couple(serder: &Serder, signer: &Signer) -> Message {
+    let opts = MessagizeOpts {
+        cigars: vec![cigar(serder, signer)],
+        ..Default::default()
+    };
+    parse_stream(&messagize(serder, opts).unwrap())
+        .unwrap()
+        .remove(0)
+}
+
+/// Event with the signature of the signer and no receipts
+pub fn event(serder: Serder, signer: &Signer) -> KelEvent {
+    let sigers = vec![siger(&serder, signer)];
+    KelEvent {
+        serder,
+        sigers,
+        wigers: Vec::new(),
+        cigars: Vec::new(),
+        trans_receipts: Vec::new(),
+        source: None,
+    }
+}
+
+/// Inception of the current signers committing to the next ones, delegated
+/// by `delpre` when given
+pub fn icp(
+    current: &[Signer],
+    next: &[Signer],
+    wits: &[Signer],
+    cnfg: Option<Vec<String>>,
+    delpre: Option<&str>,
+) -> Serder {
+    incept(
+        keys(current),
+        None,
+        digs(next),
+        None,
+        None,
+        Some(keys(wits)),
+        cnfg,
+        None,
+        None,
+        None,
+        Some(matter_codes::BLAKE3_256),
+        false,
+        delpre,
+    )
+    .unwrap()
+}
+
+/// Controller with its inception event accepted and a rotation event to its
+/// next signer, with the signers of both
+pub fn controller(kevery: &mut Kevery) -> ([Signer; 2], Serder, Serder) {
+    let (s0, s1, s2) = (signer(true), signer(true), signer(true));
+    let icp = icp(from_ref(&s0), from_ref(&s1), &[], None, None);
+    let rot = rotate(RotateArgs {
+        pre: icp.pre().unwrap().to_string(),
+        keys: keys(from_ref(&s1)),
+        dig: icp.said_field().unwrap().to_string(),
+        sn: 1,
+        ndigs: vec![dig(&s2)],
+        ..Default::default()
+    })
+    .unwrap();
+    kevery
+        .process_event(&icp, &[siger(&icp, &s0)], &[], None)
+        .unwrap();
+    ([s0, s1], icp, rot)
+}
@@ -0,0 +1,114 @@
+/// Texter - Variable length byte strings as CESR primitives
+///
+/// Texter carries arbitrary bytes (text, paths, ciphertext) using the Bytes
+/// variable size codes. The lead size is chosen from the byte length.
+use crate::core::codes::matter_codes;
+use crate::core::matter::Matter;
+use crate::error::{Result, SignifyError};
+
+/// Variable length byte string primitive
+#[derive(Debug, Clone)]
+pub struct Texter {
+    matter: Matter,
+}
+
+impl Texter {
+    /// Create Texter from raw bytes
+    pub fn new(raw: &[u8]) -> Result<Self> {
+        let matter = Matter::from_raw(raw, matter_codes::BYTES_L0)?;
+        Ok(Self { matter })
+    }
+
+    /// Create Texter from a UTF-8 string
+    pub fn from_text(text: &str) -> Result<Self> {
+        Self::new(text.as_bytes())
+    }
+
+    /// Create Texter from qb64
+    pub fn from_qb64(qb64: &str) -> Result<Self> {
+        Self::from_matter(Matter::from_qb64(qb64)?)
+    }
+
+    /// Create Texter from qb2
+    pub fn from_qb2(qb2: &[u8]) -> Result<Self> {
+        Self::from_matter(Matter::from_qb2(qb2)?)
+    }
+
+    /// Wrap existing Matter, validating it carries a Bytes code
+    pub fn from_matter(matter: Matter) -> Result<Self> {
+        if !matches!(
+            matter.code(),
+            matter_codes::BYTES_L0
+                | matter_codes::BYTES_L1
+                | matter_codes::BYTES_L2
+                | matter_codes::BYTES_BIG_L0
+                | matter_codes::BYTES_BIG_L1
+                | matter_codes::BYTES_BIG_L2
+        ) {
+            return Err(SignifyError::InvalidCode(format!(
+                "Invalid code {} for Texter",
+                matter.code()
+            )));
+        }
+        Ok(Self { matter })
+    }
+
+    /// Get the text, replacing invalid UTF-8 sequences
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(self.matter.raw()).to_string()
+    }
+
+    /// Get raw bytes
+    pub fn raw(&self) -> &[u8] {
+        self.matter.raw()
+    }
+
+    /// Get qb64 encoding
+    pub fn qb64(&self) -> &str {
+        self.matter.qb64()
+    }
+
+    /// Get qb2 encoding
+    pub fn qb2(&self) -> &[u8] {
+        self.matter.qb2()
+    }
+
+    /// Get code
+    pub fn code(&self) -> &str {
+        self.matter.code()
+    }
+
+    /// Get underlying Matter
+    pub fn matter(&self) -> &Matter {
+        &self.matter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texter_roundtrip() {
+        for text in ["", "a", "ab", "abc", "/a/b/c", "ünïcödé text"] {
+            let texter = Texter::from_text(text).unwrap();
+            assert_eq!(texter.text(), text);
+            assert_eq!(Texter::from_qb64(texter.qb64()).unwrap().text(), text);
+            assert_eq!(Texter::from_qb2(texter.qb2()).unwrap().text(), text);
+        }
+    }
+
+    #[test]
+    fn test_texter_codes() {
+        assert_eq!(Texter::new(b"abc").unwrap().code(), matter_codes::BYTES_L0);
+        assert_eq!(Texter::new(b"abcd").unwrap().code(), matter_codes::BYTES_L2);
+        assert_eq!(
+            Texter::new(b"abcde").unwrap().code(),
+            matter_codes::BYTES_L1
+        );
+
+        let big = Texter::new(&vec![0xA5; 20_000]).unwrap();
+        assert_eq!(big.code(), matter_codes::BYTES_BIG_L1);
+        assert_eq!(Texter::from_qb64(big.qb64()).unwrap().raw(), big.raw());
+    }
+}
@@ -0,0 +1,560 @@
+/// Tholder - signing threshold for KERI key sets
+///
+/// A threshold is either unweighted, a minimum count of signatures, or
+/// weighted, a list of clauses of fractional weights which are all satisfied
+/// when the weights of the signing keys in every clause sum to at least one.
+/// A weight may itself be nested, `{"1/2": ["1/2", "1/2"]}`, in which case it
+/// only counts when its own sub-weights are satisfied.
+use crate::error::{Result, SignifyError};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Largest denominator of a reduced weight
+pub const MAX_DENOMINATOR: u64 = u32::MAX as u64;
+
+/// Fractional weight in the range `0..=1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fraction {
+    num: u64,
+    den: u64,
+}
+
+impl Fraction {
+    /// Create reduced fraction, rejecting weights outside `0..=1` and
+    /// denominators above [`MAX_DENOMINATOR`]
+    pub fn new(num: u64, den: u64) -> Result<Self> {
+        if den == 0 || num > den {
+            return Err(SignifyError::InvalidThreshold(format!(
+                "Invalid weight {}/{}, must be between 0 and 1",
+                num, den
+            )));
+        }
+
+        let g = gcd(num, den);
+        if den / g > MAX_DENOMINATOR {
+            return Err(SignifyError::InvalidThreshold(format!(
+                "Invalid weight {}/{}, denominator larger than {}",
+                num, den, MAX_DENOMINATOR
+            )));
+        }
+        Ok(Self {
+            num: num / g,
+            den: den / g,
+        })
+    }
+
+    /// Get numerator
+    pub fn num(&self) -> u64 {
+        self.num
+    }
+
+    /// Get denominator
+    pub fn den(&self) -> u64 {
+        self.den
+    }
+}
+
+impl FromStr for Fraction {
+    type Err = SignifyError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || SignifyError::InvalidThreshold(format!("Invalid weight: {}", s));
+        let (num, den) = match s.split_once('/') {
+            Some((num, den)) => (num, den),
+            None => (s, "1"),
+        };
+
+        let num = num.trim().parse::<u64>().map_err(|_| invalid())?;
+        let den = den.trim().parse::<u64>().map_err(|_| invalid())?;
+        Self::new(num, den)
+    }
+}
+
+impl fmt::Display for Fraction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.num == 0 || self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a.max(1)
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Exact running sum of fractions, which stops growing at one
+#[derive(Debug, Clone, Copy)]
+struct Sum {
+    num: u128,
+    den: u128,
+}
+
+impl Sum {
+    fn zero() -> Self {
+        Self { num: 0, den: 1 }
+    }
+
+    /// Add a weight, or `None` when the sum no longer fits
+    fn add(self, w: Fraction) -> Option<Self> {
+        if self.at_least_one() {
+            return Some(self);
+        }
+
+        let num = self
+            .num
+            .checked_mul(w.den as u128)?
+            .checked_add((w.num as u128).checked_mul(self.den)?)?;
+        let den = self.den.checked_mul(w.den as u128)?;
+        if num >= den {
+            return Some(Self { num: 1, den: 1 });
+        }
+        let g = gcd128(num, den);
+        Some(Self {
+            num: num / g,
+            den: den / g,
+        })
+    }
+
+    /// Sum of `weights`, or `None` when it does not fit
+    fn of<'a>(weights: impl IntoIterator<Item = &'a Fraction>) -> Option<Self> {
+        weights
+            .into_iter()
+            .try_fold(Self::zero(), |sum, w| sum.add(*w))
+    }
+
+    fn at_least_one(&self) -> bool {
+        self.num >= self.den
+    }
+}
+
+fn gcd128(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a.max(1)
+    } else {
+        gcd128(b, a % b)
+    }
+}
+
+/// Weight of one key, or of a nested group of keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Weight {
+    /// Weight of a single key
+    Simple(Fraction),
+    /// Weight counted when the nested key weights are satisfied
+    Nested(Fraction, Vec<Fraction>),
+}
+
+impl Weight {
+    /// Number of keys covered by this weight
+    fn size(&self) -> usize {
+        match self {
+            Weight::Simple(_) => 1,
+            Weight::Nested(_, weights) => weights.len(),
+        }
+    }
+
+    fn sith(&self) -> Value {
+        match self {
+            Weight::Simple(w) => json!(w.to_string()),
+            Weight::Nested(w, weights) => {
+                let mut map = Map::new();
+                map.insert(
+                    w.to_string(),
+                    Value::Array(weights.iter().map(|w| json!(w.to_string())).collect()),
+                );
+                Value::Object(map)
+            }
+        }
+    }
+}
+
+/// Threshold value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Thold {
+    /// Minimum number of signatures
+    Unweighted(usize),
+    /// Clauses of weights, all of which must be satisfied
+    Weighted(Vec<Vec<Weight>>),
+}
+
+/// Tholder for signing thresholds (`kt` and `nt` fields)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tholder {
+    thold: Thold,
+    size: usize,
+}
+
+impl Tholder {
+    /// Create Tholder from a `kt`/`nt` field value
+    ///
+    /// Accepts an integer, a hex string such as `"2"`, a list of weight
+    /// strings, a list of such lists, or any of these lists as a JSON string.
+    pub fn new(sith: &Value) -> Result<Self> {
+        match sith {
+            Value::Number(n) => {
+                let n = n.as_u64().ok_or_else(|| {
+                    SignifyError::InvalidThreshold(format!("Invalid threshold: {}", n))
+                })?;
+                Ok(Self::unweighted(n as usize))
+            }
+            Value::String(s) => {
+                let s = s.trim();
+                if s.starts_with('[') {
+                    let value: Value = serde_json::from_str(s).map_err(|e| {
+                        SignifyError::InvalidThreshold(format!("Invalid threshold {}: {}", s, e))
+                    })?;
+                    Self::from_weighted(&value)
+                } else {
+                    let n = usize::from_str_radix(s, 16).map_err(|_| {
+                        SignifyError::InvalidThreshold(format!("Invalid threshold hex: {}", s))
+                    })?;
+                    Ok(Self::unweighted(n))
+                }
+            }
+            Value::Array(_) => Self::from_weighted(sith),
+            _ => Err(SignifyError::InvalidThreshold(format!(
+                "Invalid threshold: {}",
+                sith
+            ))),
+        }
+    }
+
+    /// Create unweighted threshold of `n` signatures
+    pub fn unweighted(n: usize) -> Self {
+        Self {
+            thold: Thold::Unweighted(n),
+            size: n,
+        }
+    }
+
+    fn from_weighted(sith: &Value) -> Result<Self> {
+        let list = sith
+            .as_array()
+            .filter(|list| !list.is_empty())
+            .ok_or_else(|| {
+                SignifyError::InvalidThreshold(format!("Empty weighted threshold: {}", sith))
+            })?;
+
+        // A flat list is a single clause
+        let clauses: Vec<&Value> = if list.iter().all(|c| c.is_array()) {
+            list.iter().collect()
+        } else if list.iter().any(|c| c.is_array()) {
+            return Err(SignifyError::InvalidThreshold(format!(
+                "Mixed clauses and weights in threshold: {}",
+                sith
+            )));
+        } else {
+            vec![sith]
+        };
+
+        let mut thold = Vec::with_capacity(clauses.len());
+        for clause in clauses {
+            let clause = clause.as_array().unwrap();
+            if clause.is_empty() {
+                return Err(SignifyError::InvalidThreshold(format!(
+                    "Empty clause in threshold: {}",
+                    sith
+                )));
+            }
+
+            let weights = clause
+                .iter()
+                .map(Self::weight)
+                .collect::<Result<Vec<_>>>()?;
+            let total = Sum::of(weights.iter().map(|w| match w {
+                Weight::Simple(w) | Weight::Nested(w, _) => w,
+            }))
+            .ok_or_else(|| {
+                SignifyError::InvalidThreshold(format!(
+                    "Clause weights overflow in threshold: {}",
+                    sith
+                ))
+            })?;
+            if !total.at_least_one() {
+                return Err(SignifyError::InvalidThreshold(format!(
+                    "Clause weights sum to less than 1 in threshold: {}",
+                    sith
+                )));
+            }
+            thold.push(weights);
+        }
+
+        let size = thold.iter().flatten().map(Weight::size).sum();
+        Ok(Self {
+            thold: Thold::Weighted(thold),
+            size,
+        })
+    }
+
+    fn weight(value: &Value) -> Result<Weight> {
+        match value {
+            Value::String(s) => Ok(Weight::Simple(s.parse()?)),
+            Value::Object(map) if map.len() == 1 => {
+                let (w, nested) = map.iter().next().unwrap();
+                let weights = nested
+                    .as_array()
+                    .filter(|list| !list.is_empty())
+                    .ok_or_else(|| {
+                        SignifyError::InvalidThreshold(format!("Invalid nested weight: {}", value))
+                    })?
+                    .iter()
+                    .map(|w| {
+                        w.as_str()
+                            .ok_or_else(|| {
+                                SignifyError::InvalidThreshold(format!("Invalid weight: {}", w))
+                            })?
+                            .parse()
+                    })
+                    .collect::<Result<Vec<Fraction>>>()?;
+
+                let total = Sum::of(&weights).ok_or_else(|| {
+                    SignifyError::InvalidThreshold(format!("Nested weights overflow: {}", value))
+                })?;
+                if !total.at_least_one() {
+                    return Err(SignifyError::InvalidThreshold(format!(
+                        "Nested weights sum to less than 1: {}",
+                        value
+                    )));
+                }
+                Ok(Weight::Nested(w.parse()?, weights))
+            }
+            _ => Err(SignifyError::InvalidThreshold(format!(
+                "Invalid weight: {}",
+                value
+            ))),
+        }
+    }
+
+    /// Get threshold value
+    pub fn thold(&self) -> &Thold {
+        &self.thold
+    }
+
+    /// Whether threshold is weighted
+    pub fn weighted(&self) -> bool {
+        matches!(self.thold, Thold::Weighted(_))
+    }
+
+    /// Number of keys the threshold applies to
+    ///
+    /// For unweighted thresholds this is the threshold itself.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Get unweighted threshold count, if unweighted
+    pub fn num(&self) -> Option<usize> {
+        match self.thold {
+            Thold::Unweighted(n) => Some(n),
+            Thold::Weighted(_) => None,
+        }
+    }
+
+    /// Get `kt`/`nt` field value
+    ///
+    /// Unweighted thresholds are lowercase hex strings, weighted thresholds
+    /// a list of weights, or a list of clauses when there are several.
+    pub fn sith(&self) -> Value {
+        match &self.thold {
+            Thold::Unweighted(n) => json!(format!("{:x}", n)),
+            Thold::Weighted(clauses) => {
+                let mut sith: Vec<Value> = clauses
+                    .iter()
+                    .map(|clause| Value::Array(clause.iter().map(Weight::sith).collect()))
+                    .collect();
+                if sith.len() == 1 {
+                    sith.pop().unwrap()
+                } else {
+                    Value::Array(sith)
+                }
+            }
+        }
+    }
+
+    /// Get `kt`/`nt` field value serialized as a JSON string
+    pub fn json(&self) -> String {
+        self.sith().to_string()
+    }
+
+    /// Whether signatures by the keys at `indices` satisfy the threshold
+    pub fn satisfy(&self, indices: &[u32]) -> bool {
+        let signed: HashSet<usize> = indices.iter().map(|i| *i as usize).collect();
+
+        match &self.thold {
+            Thold::Unweighted(n) => signed.len() >= *n,
+            Thold::Weighted(clauses) => {
+                // Key indices run across all clauses in order, and a sum
+                // that overflows fails its clause
+                let mut idx = 0;
+                clauses.iter().all(|clause| {
+                    let mut total = Some(Sum::zero());
+                    for weight in clause {
+                        match weight {
+                            Weight::Simple(w) => {
+                                if signed.contains(&idx) {
+                                    total = total.and_then(|total| total.add(*w));
+                                }
+                                idx += 1;
+                            }
+                            Weight::Nested(w, weights) => {
+                                let mut nested = Some(Sum::zero());
+                                for sw in weights {
+                                    if signed.contains(&idx) {
+                                        nested = nested.and_then(|nested| nested.add(*sw));
+                                    }
+                                    idx += 1;
+                                }
+                                if nested.is_some_and(|nested| nested.at_least_one()) {
+                                    total = total.and_then(|total| total.add(*w));
+                                }
+                            }
+                        }
+                    }
+                    total.is_some_and(|total| total.at_least_one())
+                })
+            }
+        }
+    }
+}
+
+impl FromStr for Tholder {
+    type Err = SignifyError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(&Value::String(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tholder_unweighted() {
+        let tholder: Tholder = "b".parse().unwrap();
+        assert!(!tholder.weighted());
+        assert_eq!(tholder.num(), Some(11));
+        assert_eq!(tholder.size(), 11);
+        assert_eq!(tholder.sith(), json!("b"));
+        assert_eq!(tholder.json(), "\"b\"");
+
+        let tholder = Tholder::new(&json!(2)).unwrap();
+        assert_eq!(tholder.sith(), json!("2"));
+        assert!(tholder.satisfy(&[0, 2]));
+        assert!(!tholder.satisfy(&[1, 1]));
+
+        assert!("x".parse::<Tholder>().is_err());
+        assert!(Tholder::new(&json!(-1)).is_err());
+    }
+
+    #[test]
+    fn test_tholder_weighted_single_clause() {
+        let tholder = Tholder::new(&json!(["1/2", "1/2", "1/4", "1/4", "1/4"])).unwrap();
+        assert!(tholder.weighted());
+        assert_eq!(tholder.num(), None);
+        assert_eq!(tholder.size(), 5);
+        assert_eq!(tholder.sith(), json!(["1/2", "1/2", "1/4", "1/4", "1/4"]));
+        assert_eq!(tholder.json(), r#"["1/2","1/2","1/4","1/4","1/4"]"#);
+
+        assert!(tholder.satisfy(&[0, 1]));
+        assert!(tholder.satisfy(&[0, 2, 4]));
+        assert!(tholder.satisfy(&[1, 2, 3, 4]));
+        assert!(!tholder.satisfy(&[0, 2]));
+        assert!(!tholder.satisfy(&[2, 3, 4]));
+        assert!(!tholder.satisfy(&[]));
+
+        // JSON string form parses to the same threshold
+        let parsed: Tholder = r#"["1/2", "1/2", "1/4", "1/4", "1/4"]"#.parse().unwrap();
+        assert_eq!(parsed, tholder);
+    }
+
+    #[test]
+    fn test_tholder_weighted_clauses() {
+        let sith = json!([["1/2", "1/2", "1/4", "1/4", "1/4"], ["1", "1"]]);
+        let tholder = Tholder::new(&sith).unwrap();
+        assert_eq!(tholder.size(), 7);
+        assert_eq!(tholder.sith(), sith);
+
+        // Both clauses must be satisfied
+        assert!(tholder.satisfy(&[0, 1, 5]));
+        assert!(tholder.satisfy(&[0, 2, 4, 6]));
+        assert!(!tholder.satisfy(&[0, 1]));
+        assert!(!tholder.satisfy(&[5, 6]));
+
+        // Reduced fractions and whole weights serialize canonically
+        let tholder = Tholder::new(&json!(["2/4", "1/2", "1/1", "0"])).unwrap();
+        assert_eq!(tholder.sith(), json!(["1/2", "1/2", "1", "0"]));
+    }
+
+    #[test]
+    fn test_tholder_weighted_nested() {
+        let sith = json!([{"1/3": ["1/2", "1/2", "1/2"]}, "1/3", "1/2", {"1/2": ["1", "1"]}]);
+        let tholder = Tholder::new(&sith).unwrap();
+        assert_eq!(tholder.size(), 7);
+        assert_eq!(tholder.sith(), sith);
+
+        // Nested weight 1/3 needs two of keys 0..3, 1/2 needs either of 5, 6
+        assert!(tholder.satisfy(&[0, 1, 3, 4]));
+        assert!(tholder.satisfy(&[0, 2, 4, 6]));
+        assert!(!tholder.satisfy(&[0, 3, 6]));
+        assert!(!tholder.satisfy(&[3, 5]));
+    }
+
+    #[test]
+    fn test_tholder_invalid() {
+        // Clause weights must sum to at least one
+        assert!(Tholder::new(&json!(["1/2", "1/4"])).is_err());
+        assert!(Tholder::new(&json!([["1/2", "1/2"], ["1/3"]])).is_err());
+        assert!(Tholder::new(&json!([{"1": ["1/3", "1/3"]}])).is_err());
+
+        // Weights must be fractions between 0 and 1
+        assert!(Tholder::new(&json!(["3/2"])).is_err());
+        assert!(Tholder::new(&json!(["1/0", "1"])).is_err());
+        assert!(Tholder::new(&json!(["0.5", "0.5"])).is_err());
+        assert!(Tholder::new(&json!([1, 1])).is_err());
+
+        // Empty lists and mixed clauses are rejected
+        assert!(Tholder::new(&json!([])).is_err());
+        assert!(Tholder::new(&json!([["1"], []])).is_err());
+        assert!(Tholder::new(&json!([["1"], "1"])).is_err());
+        assert!(Tholder::new(&json!({"1": ["1"]})).is_err());
+    }
+
+    #[test]
+    fn test_tholder_large_denominators() {
+        // Denominators beyond the bound are rejected outright
+        let sith = json!([
+            "1/18446744073709551557",
+            "1/18446744073709551533",
+            "1/18446744073709551521",
+            "1"
+        ]);
+        assert!(Tholder::new(&sith).is_err());
+        assert!(Fraction::new(2, 2 * MAX_DENOMINATOR).is_ok());
+
+        // Sums of coprime denominators that overflow fail closed
+        let primes = [
+            4294967291u64,
+            4294967279,
+            4294967231,
+            4294967197,
+            4294967189,
+        ];
+        let mut weights: Vec<Value> = primes.iter().map(|p| json!(format!("1/{}", p))).collect();
+        weights.push(json!("1"));
+        assert!(Tholder::new(&Value::Array(weights.clone())).is_err());
+
+        weights.rotate_right(1);
+        let tholder = Tholder::new(&Value::Array(weights)).unwrap();
+        assert!(tholder.satisfy(&[0]));
+        assert!(!tholder.satisfy(&[1, 2, 3, 4, 5]));
+    }
+}
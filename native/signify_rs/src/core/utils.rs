@@ -1,5 +1,6 @@
 /// Utility functions for KERI operations
 use crate::error::{Result, SignifyError};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use serde_json::Value;
 
 /// Concatenate byte slices
@@ -38,6 +39,9 @@ pub enum Ilks {
     Drt, // Delegated Rotation
     Rct, // Receipt
     Vrc, // Validator Receipt
+    Qry, // Query
+    Rpy, // Reply
+    Exn, // Exchange
 }
 
 impl Ilks {
@@ -50,10 +54,22 @@ impl Ilks {
             Ilks::Drt => "drt",
             Ilks::Rct => "rct",
             Ilks::Vrc => "vrc",
+            Ilks::Qry => "qry",
+            Ilks::Rpy => "rpy",
+            Ilks::Exn => "exn",
         }
     }
 }
 
+/// Current UTC time as an ISO-8601 timestamp with microseconds
+///
+/// Matches the `dt` format keripy emits, e.g. `2021-01-01T00:00:00.000000+00:00`.
+pub fn now_iso8601() -> String {
+    chrono::Utc::now()
+        .format("%Y-%m-%dT%H:%M:%S%.6f+00:00")
+        .to_string()
+}
+
 /// Serialization types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Serials {
@@ -80,6 +96,7 @@ pub struct Version {
 }
 
 pub const VRSN_1_0: Version = Version { major: 1, minor: 0 };
+pub const VRSN_2_0: Version = Version { major: 2, minor: 0 };
 
 /// Create version string for KERI events
 /// Format: KERI10JSON000260_
@@ -93,20 +110,20 @@ pub fn versify(
     let kind = kind.unwrap_or(Serials::JSON);
 
     format!(
-        "{}{}{}{}{}_",
+        "{}{}{}{}{:06x}_",
         proto.as_str(),
         version.major,
         version.minor,
         kind.as_str(),
-        format!("{:06x}", size).to_uppercase()
+        size
     )
 }
 
 /// Parse version string
 pub fn deversify(vs: &str) -> Result<(Protocols, Version, Serials, usize)> {
-    if vs.len() < 17 {
+    if vs.len() < 17 || !vs.is_ascii() {
         return Err(SignifyError::InvalidEvent(format!(
-            "Version string too short: {}",
+            "Version string too short or not ASCII: {}",
             vs
         )));
     }
@@ -122,9 +139,17 @@ pub fn deversify(vs: &str) -> Result<(Protocols, Version, Serials, usize)> {
         }
     };
 
-    let major = vs.chars().nth(4).unwrap().to_digit(10).unwrap() as u8;
-    let minor = vs.chars().nth(5).unwrap().to_digit(10).unwrap() as u8;
-    let version = Version { major, minor };
+    // Major and minor versions are single hex digits
+    let digit = |i: usize| {
+        (vs.as_bytes()[i] as char)
+            .to_digit(16)
+            .map(|d| d as u8)
+            .ok_or_else(|| SignifyError::InvalidEvent(format!("Invalid version: {}", &vs[4..6])))
+    };
+    let version = Version {
+        major: digit(4)?,
+        minor: digit(5)?,
+    };
 
     let kind = match &vs[6..10] {
         "JSON" => Serials::JSON,
@@ -145,18 +170,56 @@ pub fn deversify(vs: &str) -> Result<(Protocols, Version, Serials, usize)> {
     Ok((proto, version, kind, size))
 }
 
-/// Convert integer to base64
+/// Base64 URL-safe alphabet in index order
+const B64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Convert integer to Base64 URL-safe digits, left padded with 'A' (zero)
+/// to `length` characters
 pub fn int_to_b64(num: usize, length: usize) -> String {
-    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    let mut n = num;
+    let mut result = Vec::with_capacity(length);
+
+    for _ in 0..length {
+        result.push(B64_CHARS[n & 0x3f]);
+        n >>= 6;
+    }
+
+    result.reverse();
+    String::from_utf8(result).unwrap()
+}
 
-    let bytes = num.to_be_bytes();
-    let b64 = URL_SAFE_NO_PAD.encode(&bytes[bytes.len() - ((length * 3 + 3) / 4)..]);
+/// Convert Base64 URL-safe digits to integer
+pub fn b64_to_int(s: &str) -> Result<usize> {
+    let mut result = 0usize;
 
-    if b64.len() > length {
-        b64[b64.len() - length..].to_string()
-    } else {
-        format!("{:0>width$}", b64, width = length)
+    for &b in s.as_bytes() {
+        let val = match b {
+            b'A'..=b'Z' => (b - b'A') as usize,
+            b'a'..=b'z' => (b - b'a' + 26) as usize,
+            b'0'..=b'9' => (b - b'0' + 52) as usize,
+            b'-' => 62,
+            b'_' => 63,
+            _ => {
+                return Err(SignifyError::InvalidFormat(format!(
+                    "Invalid base64url character: {}",
+                    b as char
+                )))
+            }
+        };
+        result = (result << 6) | val;
     }
+
+    Ok(result)
+}
+
+/// Convert the first `n` Base64 chars worth of bits in `b2` to Base64
+///
+/// Used to read the code of a qb2 primitive before its size is known.
+pub fn b2_to_b64(b2: &[u8], n: usize) -> String {
+    let bytes = (n * 3).div_ceil(4);
+    let mut b64 = URL_SAFE_NO_PAD.encode(&b2[..bytes.min(b2.len())]);
+    b64.truncate(n);
+    b64
 }
 
 /// Read integer from bytes
@@ -191,6 +254,11 @@ mod tests {
         let vs = versify(Protocols::KERI, None, None, 608);
         assert!(vs.starts_with("KERI10JSON"));
         assert!(vs.ends_with("_"));
+        // Size is lowercase hex like keripy
+        assert_eq!(
+            versify(Protocols::KERI, None, None, 0x29d),
+            "KERI10JSON00029d_"
+        );
     }
 
     #[test]
@@ -203,6 +271,23 @@ mod tests {
         assert_eq!(version.minor, 0);
         assert_eq!(kind, Serials::JSON);
         assert_eq!(size, 0x260);
+
+        // Malformed version strings are errors, not panics
+        assert!(deversify("KERIx0JSON000260_").is_err());
+        assert!(deversify("KERI10JSON00026é_").is_err());
+    }
+
+    #[test]
+    fn test_b64_int_conversions() {
+        assert_eq!(int_to_b64(0, 2), "AA");
+        assert_eq!(int_to_b64(1, 1), "B");
+        assert_eq!(int_to_b64(64, 2), "BA");
+        assert_eq!(int_to_b64(4095, 2), "__");
+
+        assert_eq!(b64_to_int("AA").unwrap(), 0);
+        assert_eq!(b64_to_int("BA").unwrap(), 64);
+        assert_eq!(b64_to_int(&int_to_b64(123456, 4)).unwrap(), 123456);
+        assert!(b64_to_int("A=").is_err());
     }
 
     #[test]
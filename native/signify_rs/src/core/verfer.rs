@@ -1,9 +1,9 @@
 use crate::core::{matter_codes, Matter};
-/// Verfer - Ed25519 signature verification with CESR encoding
+/// Verfer - Ed25519, Ed448 and ECDSA secp256k1/secp256r1 signature verification with CESR encoding
 use crate::error::{Result, SignifyError};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 
-/// Verfer wraps a Matter containing an Ed25519 public key
+/// Verfer wraps a Matter containing an Ed25519, Ed448 or ECDSA public key
 #[derive(Debug, Clone)]
 pub struct Verfer {
     matter: Matter,
@@ -85,6 +85,68 @@ impl Verfer {
                     Err(_) => Ok(false),
                 }
             }
+            matter_codes::ECDSA_256K1 | matter_codes::ECDSA_256K1N => {
+                if sig.len() != 64 {
+                    return Err(SignifyError::InvalidSize {
+                        expected: 64,
+                        actual: sig.len(),
+                    });
+                }
+
+                // Parse compressed SEC1 public key
+                let verifying_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(self.matter.raw())
+                    .map_err(|e| SignifyError::CryptoError(e.to_string()))?;
+
+                // Parse r || s signature, accepting high-S signatures from
+                // other KERI implementations
+                let signature = match k256::ecdsa::Signature::from_slice(sig) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                };
+                let signature = signature.normalize_s().unwrap_or(signature);
+
+                Ok(verifying_key.verify(ser, &signature).is_ok())
+            }
+            matter_codes::ECDSA_256R1 | matter_codes::ECDSA_256R1N => {
+                if sig.len() != 64 {
+                    return Err(SignifyError::InvalidSize {
+                        expected: 64,
+                        actual: sig.len(),
+                    });
+                }
+
+                let verifying_key = p256::ecdsa::VerifyingKey::from_sec1_bytes(self.matter.raw())
+                    .map_err(|e| SignifyError::CryptoError(e.to_string()))?;
+
+                let signature = match p256::ecdsa::Signature::from_slice(sig) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                };
+
+                Ok(verifying_key.verify(ser, &signature).is_ok())
+            }
+            matter_codes::ED448 | matter_codes::ED448N => {
+                if sig.len() != ed448_goldilocks::Signature::BYTE_SIZE {
+                    return Err(SignifyError::InvalidSize {
+                        expected: ed448_goldilocks::Signature::BYTE_SIZE,
+                        actual: sig.len(),
+                    });
+                }
+
+                let verifying_key = ed448_goldilocks::VerifyingKey::from_bytes(
+                    self.matter.raw().try_into().map_err(|_| {
+                        SignifyError::CryptoError("Invalid public key length".to_string())
+                    })?,
+                )
+                .map_err(|e| SignifyError::CryptoError(e.to_string()))?;
+
+                let signature = match ed448_goldilocks::Signature::from_slice(sig) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                };
+
+                Ok(verifying_key.verify_raw(&signature, ser).is_ok())
+            }
             code => Err(SignifyError::UnsupportedAlgorithm(format!(
                 "Unsupported verifier code for verification: {}",
                 code
@@ -94,7 +156,17 @@ impl Verfer {
 
     /// Check if code is valid for a verifier
     fn is_valid_code(code: &str) -> bool {
-        matches!(code, matter_codes::ED25519 | matter_codes::ED25519N)
+        matches!(
+            code,
+            matter_codes::ED25519
+                | matter_codes::ED25519N
+                | matter_codes::ECDSA_256K1
+                | matter_codes::ECDSA_256K1N
+                | matter_codes::ECDSA_256R1
+                | matter_codes::ECDSA_256R1N
+                | matter_codes::ED448
+                | matter_codes::ED448N
+        )
     }
 
     pub fn matter(&self) -> &Matter {
@@ -107,7 +179,13 @@ impl Verfer {
 
     /// Check if this is a transferable key
     pub fn transferable(&self) -> bool {
-        self.matter.code() == matter_codes::ED25519
+        matches!(
+            self.matter.code(),
+            matter_codes::ED25519
+                | matter_codes::ECDSA_256K1
+                | matter_codes::ECDSA_256R1
+                | matter_codes::ED448
+        )
     }
 
     pub fn raw(&self) -> &[u8] {
@@ -217,4 +295,57 @@ mod tests {
         let result = verfer.verify(&bad_sig, message);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_verfer_secp256k1() {
+        use k256::ecdsa::signature::Signer as _;
+
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let public = signing_key.verifying_key().to_encoded_point(true);
+
+        let verfer = Verfer::from_raw(public.as_bytes(), matter_codes::ECDSA_256K1).unwrap();
+        assert!(verfer.transferable());
+        assert_eq!(verfer.qb64().len(), 48);
+        let verfer = Verfer::from_qb64(verfer.qb64()).unwrap();
+
+        let message = b"test message";
+        let signature: k256::ecdsa::Signature = signing_key.sign(message);
+        assert!(verfer.verify(&signature.to_bytes(), message).unwrap());
+        assert!(!verfer
+            .verify(&signature.to_bytes(), b"wrong message")
+            .unwrap());
+
+        // High-S form of the same signature also verifies
+        let (r, s) = signature.split_scalars();
+        let high = k256::ecdsa::Signature::from_scalars(r, -*s).unwrap();
+        assert!(verfer.verify(&high.to_bytes(), message).unwrap());
+
+        assert!(verfer.verify(&[0u8; 63], message).is_err());
+        assert!(!verfer.verify(&[0u8; 64], message).unwrap());
+
+        let verfer_nt = Verfer::from_raw(public.as_bytes(), matter_codes::ECDSA_256K1N).unwrap();
+        assert!(!verfer_nt.transferable());
+    }
+
+    #[test]
+    fn test_verfer_ed448_rfc8032() {
+        // RFC 8032 section 7.4 test vector for an empty message
+        let public = hex::decode(
+            "5fd7449b59b461fd2ce787ec616ad46a1da1342485a70e1f8a0ea75d80e96778\
+             edf124769b46c7061bd6783df1e50f6cd1fa1abeafe8256180",
+        )
+        .unwrap();
+        let signature = hex::decode(
+            "533a37f6bbe457251f023c0d88f976ae2dfb504a843e34d2074fd823d41a591f\
+             2b233f034f628281f2fd7a22ddd47d7828c59bd0a21bfd3980ff0d2028d4b18a\
+             9df63e006c5d1c2d345b925d8dc00b4104852db99ac5c7cdda8530a113a0f4db\
+             b61149f05a7363268c71d95808ff2e652600",
+        )
+        .unwrap();
+
+        let verfer = Verfer::from_raw(&public, matter_codes::ED448).unwrap();
+        let verfer = Verfer::from_qb64(verfer.qb64()).unwrap();
+        assert!(verfer.verify(&signature, b"").unwrap());
+        assert!(!verfer.verify(&signature, b"message").unwrap());
+    }
 }
@@ -63,6 +63,12 @@ pub enum SignifyError {
     #[error("Parse error: {0}")]
     ParseError(String),
 
+    #[error("CESR stream error at byte {offset}: {message}")]
+    StreamError { offset: usize, message: String },
+
+    #[error("Likely duplicitous event {said} for {pre} at sn {sn}")]
+    DuplicitousEvent { pre: String, sn: u64, said: String },
+
     #[error("Signature verification failed")]
     VerificationFailed,
 
@@ -81,6 +87,9 @@ pub enum SignifyError {
     #[error("Argon2 error: {0}")]
     Argon2Error(String),
 
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -111,3 +120,39 @@ impl From<argon2::Error> for SignifyError {
         SignifyError::Argon2Error(e.to_string())
     }
 }
+
+impl From<redb::Error> for SignifyError {
+    fn from(e: redb::Error) -> Self {
+        SignifyError::DatabaseError(e.to_string())
+    }
+}
+
+impl From<redb::DatabaseError> for SignifyError {
+    fn from(e: redb::DatabaseError) -> Self {
+        SignifyError::DatabaseError(e.to_string())
+    }
+}
+
+impl From<redb::TransactionError> for SignifyError {
+    fn from(e: redb::TransactionError) -> Self {
+        SignifyError::DatabaseError(e.to_string())
+    }
+}
+
+impl From<redb::TableError> for SignifyError {
+    fn from(e: redb::TableError) -> Self {
+        SignifyError::DatabaseError(e.to_string())
+    }
+}
+
+impl From<redb::StorageError> for SignifyError {
+    fn from(e: redb::StorageError) -> Self {
+        SignifyError::DatabaseError(e.to_string())
+    }
+}
+
+impl From<redb::CommitError> for SignifyError {
+    fn from(e: redb::CommitError) -> Self {
+        SignifyError::DatabaseError(e.to_string())
+    }
+}
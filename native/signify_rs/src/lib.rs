@@ -19,9 +19,11 @@ pub use app::{
     IssueCredentialResult, MakeHabArgs, SignifyClient, TraitCodex, ACDC_VERSION,
 };
 pub use core::{
-    incept, matter_codes, Cigar, Cipher, Counter, CounterCodex, DecryptedMatter, Decrypter,
-    DerivationCode, Diger, Encrypter, IndexedSignature, Indexer, IndexerCodex, Manager, Matter,
-    MatterOpts, Prefixer, Saider, Salter, Seqner, Serder, Siger, Signer, Tier, Verfer,
+    end_role_add, end_role_cut, exchange, incept, interact, loc_scheme, matter_codes, messagize,
+    query, receipt, reply, rotate, Baser, Bexter, Cigar, Cipher, Counter, CounterCodex, Dater,
+    DecryptedMatter, Decrypter, DerivationCode, Diger, Embed, Encrypter, IndexedSignature, Indexer,
+    IndexerCodex, Kever, Kevery, Manager, Matter, MatterOpts, MessagizeOpts, Pather, Prefixer,
+    RotateArgs, Saider, Salter, Seqner, Serder, Siger, Signer, Texter, Tholder, Tier, Verfer,
 };
 pub use error::{Result, SignifyError};
 
@@ -367,10 +369,10 @@ mod tests {
 
         let serder = incept(
             keys.clone(),
-            Some("2"), // Both keys required for signing (2-of-2)
+            Some(&Tholder::unweighted(2)), // Both keys required for signing (2-of-2)
             ndigs.clone(),
-            Some("2"), // Both next keys required for rotation
-            None,      // Auto-calculate ample threshold (3 witnesses -> threshold 1)
+            Some(&Tholder::unweighted(2)), // Both next keys required for rotation
+            None, // Auto-calculate ample threshold (3 witnesses -> threshold 1)
             Some(witnesses.clone()),
             Some(vec!["EO".to_string()]), // Establishment only config
             None,
@@ -4,12 +4,13 @@
 //! It provides safe wrappers around Rust types and functions for use from Elixir.
 
 use rustler::{Binary, Env, Error as RustlerError, ResourceArc, Term};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError};
 
 // Re-export core types
 use crate::app::credentialing::{CredentialFilter, Credentials};
 use crate::app::{Habery, HaberyArgs, MakeHabArgs, SignifyClient};
-use crate::core::{Signer, Verfer};
+use crate::core::eventing::{ILK_DIP, ILK_DRT, ILK_ICP, ILK_IXN, ILK_RCT, ILK_ROT};
+use crate::core::{Baser, Escrow, Kevery, Processed, Siger, Signer, Verfer};
 use crate::error::{Result as SignifyResult, SignifyError};
 
 /// Convert SignifyError to Rustler error
@@ -149,6 +150,29 @@ impl CredentialsResource {
     }
 }
 
+/// Kevery resource for Elixir
+pub struct KeveryResource {
+    inner: Arc<Mutex<Kevery>>,
+}
+
+impl KeveryResource {
+    fn new(kevery: Kevery) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(kevery)),
+        }
+    }
+
+    fn with_mut<F, R>(&self, f: F) -> SignifyResult<R>
+    where
+        F: FnOnce(&mut Kevery) -> SignifyResult<R>,
+    {
+        // Kevery applies each accepted event to its key state as a whole, so
+        // a panic in an earlier call must not lock out every later one
+        let mut kevery = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&mut kevery)
+    }
+}
+
 //
 // Signer NIFs
 //
@@ -317,7 +341,6 @@ fn client_url(resource: ResourceArc<SignifyClientResource>) -> Result<String, Ru
 fn parse_cesr_file(
     cesr_data: String,
 ) -> Result<(ResourceArc<VerferResource>, String), RustlerError> {
-    use crate::core::prefixer::Prefixer;
     use serde_json::Value;
 
     let trimmed = cesr_data.trim();
@@ -326,72 +349,15 @@ fn parse_cesr_file(
         return Err(RustlerError::Term(Box::new("Empty CESR data")));
     }
 
-    // Check if this is a KERI event stream (starts with JSON)
-    if !trimmed.starts_with('{') {
-        return Err(RustlerError::Term(Box::new(
-            "CESR data must be KERI event stream (JSON format)",
-        )));
-    }
-
-    // Parse KERI event stream to find inception event
-    let mut current_pos = 0;
-    let data_bytes = trimmed.as_bytes();
+    // Pull messages one at a time, stopping at the first inception
     let mut inception_event: Option<Value> = None;
-
-    // Parse each JSON object in the stream
-    while current_pos < data_bytes.len() {
-        // Skip whitespace and non-JSON characters
-        while current_pos < data_bytes.len() && data_bytes[current_pos] != b'{' {
-            current_pos += 1;
-        }
-
-        if current_pos >= data_bytes.len() {
+    for message in crate::cesr_parser::MessageReader::new(trimmed.as_bytes()) {
+        let message = message
+            .map_err(|e| RustlerError::Term(Box::new(format!("Invalid CESR stream: {}", e))))?;
+        if matches!(message.serder.ilk(), Some("icp") | Some("dip")) {
+            inception_event = Some(message.serder.sad().clone());
             break;
         }
-
-        // Find matching closing brace
-        let start = current_pos;
-        let mut depth = 0;
-        let mut in_string = false;
-        let mut escape = false;
-
-        while current_pos < data_bytes.len() {
-            let ch = data_bytes[current_pos];
-
-            if escape {
-                escape = false;
-                current_pos += 1;
-                continue;
-            }
-
-            if ch == b'\\' {
-                escape = true;
-            } else if ch == b'"' {
-                in_string = !in_string;
-            } else if !in_string {
-                if ch == b'{' {
-                    depth += 1;
-                } else if ch == b'}' {
-                    depth -= 1;
-                    if depth == 0 {
-                        current_pos += 1;
-                        break;
-                    }
-                }
-            }
-            current_pos += 1;
-        }
-
-        // Parse this JSON object
-        let json_str = &trimmed[start..current_pos];
-        if let Ok(event) = serde_json::from_str::<Value>(json_str) {
-            // Check if this is an inception event (icp or dip)
-            if let Some(event_type) = event.get("t").and_then(|t| t.as_str()) {
-                if (event_type == "icp" || event_type == "dip") && inception_event.is_none() {
-                    inception_event = Some(event);
-                }
-            }
-        }
     }
 
     // Extract keys from inception event
@@ -444,6 +410,107 @@ fn parse_cesr_file(
     Ok((ResourceArc::new(VerferResource::new(verfer)), identifier))
 }
 
+//
+// Kevery NIFs
+//
+
+/// Create a key event processor, logging accepted events to the database at
+/// `db_path` and restoring the key state stored there when given
+#[rustler::nif(schedule = "DirtyIo")]
+fn kevery_new(db_path: Option<String>) -> Result<ResourceArc<KeveryResource>, RustlerError> {
+    let kevery = match db_path {
+        Some(path) => Baser::open(path).and_then(Kevery::with_baser),
+        None => Ok(Kevery::new()),
+    }
+    .map_err(to_rustler_error)?;
+
+    Ok(ResourceArc::new(KeveryResource::new(kevery)))
+}
+
+/// Process the key events and receipts of a CESR stream
+///
+/// Returns a result per message of the stream: `{:ok, {outcome, escrow}}`
+/// with outcome `"accepted"`, `"duplicate"`, `"escrowed"` with the escrow it
+/// waits in, or `"ignored"` for messages other than key events and receipts,
+/// and `{:error, reason}` for a rejected message. A stream that fails to
+/// parse ends with the parse error.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn kevery_process(
+    resource: ResourceArc<KeveryResource>,
+    cesr_data: String,
+) -> Result<Vec<Result<(String, Option<String>), String>>, RustlerError> {
+    resource
+        .with_mut(|kevery| {
+            let mut results = Vec::new();
+            for message in crate::cesr_parser::MessageReader::new(cesr_data.trim().as_bytes()) {
+                let message = match message {
+                    Ok(message) => message,
+                    Err(e) => {
+                        results.push(Err(e.to_string()));
+                        break;
+                    }
+                };
+                let Some(ILK_ICP | ILK_ROT | ILK_IXN | ILK_DIP | ILK_DRT | ILK_RCT) =
+                    message.serder.ilk()
+                else {
+                    results.push(Ok(("ignored".to_string(), None)));
+                    continue;
+                };
+                let result = kevery
+                    .process_message(&message)
+                    .map(outcome)
+                    .map_err(|e| e.to_string());
+                results.push(result);
+            }
+            Ok(results)
+        })
+        .map_err(to_rustler_error)
+}
+
+/// Outcome of processing a message with the escrow it waits in
+fn outcome(processed: Processed) -> (String, Option<String>) {
+    let escrow = |escrow| match escrow {
+        Escrow::OutOfOrder => "out_of_order",
+        Escrow::PartialSigs => "partial_sigs",
+        Escrow::PartialWitness => "partial_witness",
+        Escrow::Delegation => "delegation",
+        Escrow::UnverifiedReceipt => "unverified_receipt",
+    };
+    match processed {
+        Processed::Accepted => ("accepted".to_string(), None),
+        Processed::Duplicate => ("duplicate".to_string(), None),
+        Processed::Escrowed(e) => ("escrowed".to_string(), Some(escrow(e).to_string())),
+    }
+}
+
+/// Verify qb64 indexed signatures on a payload against the key state of an
+/// identifier at `sn`, or its latest key state
+///
+/// Returns the indices of the current key signatures and the ondices of
+/// those whose keys the prior establishment event committed to.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn kevery_verify_by_aid(
+    resource: ResourceArc<KeveryResource>,
+    prefix: String,
+    sn: Option<u64>,
+    payload: Vec<u8>,
+    signatures: Vec<String>,
+) -> Result<(Vec<u32>, Vec<u32>), RustlerError> {
+    resource
+        .with_mut(|kevery| {
+            let sigers = signatures
+                .iter()
+                .map(|sig| Siger::from_qb64(sig, None))
+                .collect::<SignifyResult<Vec<_>>>()?;
+            let verified = kevery.verify_by_aid(&prefix, sn, &payload, &sigers)?;
+            Ok((
+                verified.current.iter().map(Siger::index).collect(),
+                verified.prior_next.iter().map(Siger::ondex).collect(),
+            ))
+        })
+        .map_err(to_rustler_error)
+}
+
 //
 // Credentials NIFs
 //
@@ -585,6 +652,7 @@ fn load(env: Env, _: Term) -> bool {
     rustler::resource!(HaberyResource, env);
     rustler::resource!(SignifyClientResource, env);
     rustler::resource!(CredentialsResource, env);
+    rustler::resource!(KeveryResource, env);
     true
 }
 
@@ -616,6 +684,10 @@ rustler::init!(
         credentials_delete,
         // CESR File Parsing NIFs
         parse_cesr_file,
+        // Kevery NIFs
+        kevery_new,
+        kevery_process,
+        kevery_verify_by_aid,
         // Utility NIFs
         blake3_digest,
         version,